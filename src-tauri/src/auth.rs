@@ -2,12 +2,13 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::Arc;
+use tauri::Emitter;
 use tokio::sync::Mutex;
 use tracing::{error, info, warn};
 
 const CLIENT_ID: &str = "yu2txwsc619qgqaghrv1xzf66swhad";
 const SCOPES: &str =
-    "chat:read chat:edit moderator:read:followers user:read:chat channel:read:subscriptions bits:read channel:manage:broadcast channel:read:redemptions";
+    "chat:read chat:edit moderator:read:followers user:read:chat channel:read:subscriptions bits:read channel:manage:broadcast channel:read:redemptions channel:manage:redemptions clips:edit";
 
 /// Stored token data, serialised as JSON on disk.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +18,12 @@ pub struct TokenData {
     /// Unix timestamp (seconds) when the access token expires.
     pub expires_at: i64,
     pub username: String,
+    /// Twitch user id of the account; empty in token files saved before it was stored.
+    #[serde(default)]
+    pub user_id: String,
+    /// Scopes granted to the access token.
+    #[serde(default)]
+    pub scopes: Vec<String>,
 }
 
 /// Shared auth state managed by Tauri.
@@ -29,6 +36,14 @@ pub struct AuthState {
 }
 
 impl AuthState {
+    /// Username of the stored session, without validating or refreshing it.
+    pub fn stored_username(&self) -> Option<String> {
+        load_tokens(&self.tokens_path)
+            .ok()
+            .flatten()
+            .map(|data| data.username)
+    }
+
     pub fn new(data_dir: PathBuf) -> Self {
         Self {
             http: Client::new(),
@@ -108,6 +123,9 @@ struct TwitchTokenResponse {
 #[derive(Deserialize)]
 struct TwitchValidateResponse {
     login: String,
+    user_id: String,
+    #[serde(default)]
+    scopes: Vec<String>,
 }
 
 #[derive(Deserialize)]
@@ -115,7 +133,10 @@ struct TwitchErrorResponse {
     message: String,
 }
 
-async fn validate_token(http: &Client, access_token: &str) -> Result<String, String> {
+async fn validate_token(
+    http: &Client,
+    access_token: &str,
+) -> Result<TwitchValidateResponse, String> {
     let resp = http
         .get("https://id.twitch.tv/oauth2/validate")
         .header("Authorization", format!("OAuth {access_token}"))
@@ -127,8 +148,17 @@ async fn validate_token(http: &Client, access_token: &str) -> Result<String, Str
         return Err("Token validation failed".into());
     }
 
-    let data: TwitchValidateResponse = resp.json().await.map_err(|e| e.to_string())?;
-    Ok(data.login)
+    resp.json().await.map_err(|e| e.to_string())
+}
+
+/// Scopes requested by this app that `data` was not granted. Tokens issued
+/// before a scope was added need a fresh login to pick it up.
+pub fn missing_scopes(data: &TokenData) -> Vec<String> {
+    SCOPES
+        .split(' ')
+        .filter(|scope| !data.scopes.iter().any(|s| s == scope))
+        .map(String::from)
+        .collect()
 }
 
 async fn refresh_tokens(http: &Client, refresh_token: &str) -> Result<TokenData, String> {
@@ -151,15 +181,17 @@ async fn refresh_tokens(http: &Client, refresh_token: &str) -> Result<TokenData,
     }
 
     let tokens: TwitchTokenResponse = resp.json().await.map_err(|e| e.to_string())?;
-    let username = validate_token(http, &tokens.access_token).await?;
-    info!("[auth] refresh success — user={username}");
+    let identity = validate_token(http, &tokens.access_token).await?;
+    info!("[auth] refresh success — user={}", identity.login);
     let now = chrono::Utc::now().timestamp();
 
     Ok(TokenData {
         access_token: tokens.access_token,
         refresh_token: tokens.refresh_token,
         expires_at: now + tokens.expires_in,
-        username,
+        username: identity.login,
+        user_id: identity.user_id,
+        scopes: identity.scopes,
     })
 }
 
@@ -170,9 +202,18 @@ async fn refresh_tokens(http: &Client, refresh_token: &str) -> Result<TokenData,
 pub async fn get_valid_token(state: &AuthState) -> Result<TokenData, String> {
     let _guard = state.refresh_lock.lock().await;
 
-    let data = load_tokens(&state.tokens_path)?.ok_or("Not authenticated")?;
+    let mut data = load_tokens(&state.tokens_path)?.ok_or("Not authenticated")?;
     let now = chrono::Utc::now().timestamp();
 
+    // Token files from older versions lack the user id and scopes; fill them in once
+    if data.user_id.is_empty() && now < data.expires_at - 300 {
+        if let Ok(identity) = validate_token(&state.http, &data.access_token).await {
+            data.user_id = identity.user_id;
+            data.scopes = identity.scopes;
+            store_tokens(&state.tokens_path, &data)?;
+        }
+    }
+
     // Refresh if expiring within 5 minutes
     if now >= data.expires_at - 300 {
         let refreshed = refresh_tokens(&state.http, &data.refresh_token).await?;
@@ -238,6 +279,7 @@ pub async fn auth_device_poll(
     device_code: String,
     interval: u64,
     expires_in: u64,
+    app: tauri::AppHandle,
     state: tauri::State<'_, Arc<AuthState>>,
 ) -> Result<AuthStatusResponse, String> {
     let deadline = tokio::time::Instant::now() + tokio::time::Duration::from_secs(expires_in);
@@ -267,22 +309,27 @@ pub async fn auth_device_poll(
         if resp.status().is_success() {
             let tokens: TwitchTokenResponse = resp.json().await.map_err(|e| e.to_string())?;
             info!("[auth] device flow got tokens, validating…");
-            let username = validate_token(&state.http, &tokens.access_token).await?;
+            let identity = validate_token(&state.http, &tokens.access_token).await?;
             let now = chrono::Utc::now().timestamp();
 
             let data = TokenData {
                 access_token: tokens.access_token,
                 refresh_token: tokens.refresh_token,
                 expires_at: now + tokens.expires_in,
-                username: username.clone(),
+                username: identity.login,
+                user_id: identity.user_id,
+                scopes: identity.scopes,
             };
             store_tokens(&state.tokens_path, &data)?;
-            info!("[auth] device flow complete — user={username}");
+            info!("[auth] device flow complete — user={}", data.username);
 
-            return Ok(AuthStatusResponse {
+            let status = AuthStatusResponse {
                 authenticated: true,
-                username: Some(username),
-            });
+                missing_scopes: missing_scopes(&data),
+                username: Some(data.username),
+            };
+            announce_auth(&app, &status);
+            return Ok(status);
         }
 
         // Check the error — keep polling on "authorization_pending"
@@ -303,6 +350,18 @@ pub async fn auth_device_poll(
 pub struct AuthStatusResponse {
     pub authenticated: bool,
     pub username: Option<String>,
+    /// Requested scopes the stored token lacks; non-empty means the user should log in again.
+    pub missing_scopes: Vec<String>,
+}
+
+impl AuthStatusResponse {
+    pub fn logged_out() -> Self {
+        Self {
+            authenticated: false,
+            username: None,
+            missing_scopes: Vec::new(),
+        }
+    }
 }
 
 #[tauri::command]
@@ -313,23 +372,45 @@ pub async fn auth_status(
     match get_valid_token(&state).await {
         Ok(data) => {
             info!("[auth] stored session valid — user={}", data.username);
+            let missing_scopes = missing_scopes(&data);
+            if !missing_scopes.is_empty() {
+                warn!("[auth] token lacks scopes: {}", missing_scopes.join(" "));
+            }
             Ok(AuthStatusResponse {
                 authenticated: true,
                 username: Some(data.username),
+                missing_scopes,
             })
         }
         Err(e) => {
             info!("[auth] no stored session: {e}");
-            Ok(AuthStatusResponse {
-                authenticated: false,
-                username: None,
-            })
+            Ok(AuthStatusResponse::logged_out())
         }
     }
 }
 
+/// Emitted with an [`AuthStatusResponse`] whenever the login changes: after
+/// the device flow completes and after logging out, from the UI or the tray.
+pub const AUTH_CHANGED_EVENT: &str = "auth-changed";
+
+pub fn announce_auth(app: &tauri::AppHandle, status: &AuthStatusResponse) {
+    if let Err(e) = app.emit(AUTH_CHANGED_EVENT, status) {
+        warn!("[auth] failed to emit login change: {e}");
+    }
+}
+
 #[tauri::command]
-pub async fn auth_logout(state: tauri::State<'_, Arc<AuthState>>) -> Result<(), String> {
+pub async fn auth_logout(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AuthState>>,
+) -> Result<(), String> {
+    logout(&state).await?;
+    announce_auth(&app, &AuthStatusResponse::logged_out());
+    Ok(())
+}
+
+/// Revoke the stored token (best effort) and delete it.
+pub async fn logout(state: &AuthState) -> Result<(), String> {
     info!("[auth] logging out…");
     if let Ok(Some(data)) = load_tokens(&state.tokens_path) {
         let _ = state
@@ -0,0 +1,444 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use tracing::info;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Version of the bundle layout written to `manifest.json`.
+const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// Prefix that replaces absolute asset paths inside a bundled preset.
+const BUNDLE_PATH_PREFIX: &str = "bundle://";
+
+const MANIFEST_FILE: &str = "manifest.json";
+const PRESET_FILE: &str = "preset.json";
+const ASSETS_PREFIX: &str = "assets/";
+
+/// Upper bound for any single file read from a bundle, to avoid zip bombs.
+const MAX_ENTRY_BYTES: u64 = 64 * 1024 * 1024;
+
+/// File types that are packed into bundles when a preset references them.
+const ASSET_EXTENSIONS: &[&str] = &[
+    "mp3", "wav", "ogg", "flac", "m4a", // sounds
+    "png", "jpg", "jpeg", "gif", "webp", "svg", // images
+    "ttf", "otf", "woff", "woff2", // fonts
+];
+
+/// Describes the contents of a bundle and their checksums.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    format_version: u32,
+    name: String,
+    app_version: String,
+    /// Unix millis.
+    created_at: i64,
+    preset_sha256: String,
+    assets: Vec<ManifestAsset>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ManifestAsset {
+    /// Entry name inside the zip, always under `assets/`.
+    file: String,
+    /// Absolute path the asset was packed from, for reference only.
+    original_path: String,
+    sha256: String,
+    size: u64,
+}
+
+/// A preset unpacked from a bundle, with asset paths rewritten for this machine.
+pub struct ImportedBundle {
+    pub name: String,
+    pub contents: String,
+}
+
+/// Whether `path` names a bundle rather than a plain JSON preset.
+pub fn is_bundle(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "zip")
+}
+
+/// Directory that imported bundle assets are unpacked into.
+pub fn assets_dir() -> Result<PathBuf, String> {
+    Ok(dirs::home_dir()
+        .ok_or("Could not determine home directory")?
+        .join(".config")
+        .join("streamer")
+        .join("assets"))
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Reduce a file name to characters that are safe in a zip entry and on disk.
+fn safe_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>()
+        .trim_start_matches('.')
+        .to_string()
+}
+
+/// Whether `path` has one of the [`ASSET_EXTENSIONS`].
+fn is_asset_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| ASSET_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+/// Collect absolute paths to existing asset files referenced anywhere in `value`.
+fn collect_asset_paths(value: &Value, found: &mut BTreeMap<String, PathBuf>) {
+    match value {
+        Value::String(s) => {
+            let path = Path::new(s);
+            if is_asset_file(path) && path.is_absolute() && path.is_file() {
+                found.insert(s.clone(), path.to_path_buf());
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|v| collect_asset_paths(v, found)),
+        Value::Object(map) => map.values().for_each(|v| collect_asset_paths(v, found)),
+        _ => {}
+    }
+}
+
+/// Replace every string in `value` that is a key of `replacements`.
+fn rewrite_strings(value: &mut Value, replacements: &BTreeMap<String, String>) {
+    match value {
+        Value::String(s) => {
+            if let Some(new) = replacements.get(s.as_str()) {
+                *s = new.clone();
+            }
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|v| rewrite_strings(v, replacements)),
+        Value::Object(map) => map
+            .values_mut()
+            .for_each(|v| rewrite_strings(v, replacements)),
+        _ => {}
+    }
+}
+
+/// Write `preset` and every asset file it references to a zip bundle at `dest`.
+pub fn export(dest: &Path, name: &str, preset: &str, app_version: &str) -> Result<(), String> {
+    let mut preset: Value = serde_json::from_str(preset).map_err(|e| e.to_string())?;
+
+    let mut sources = BTreeMap::new();
+    collect_asset_paths(&preset, &mut sources);
+
+    let mut assets = Vec::new();
+    let mut contents = Vec::new();
+    let mut replacements = BTreeMap::new();
+    for (original, path) in sources {
+        let bytes = std::fs::read(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let sha256 = sha256_hex(&bytes);
+        let file_name =
+            safe_file_name(path.file_name().and_then(|n| n.to_str()).unwrap_or("asset"));
+        let file = format!("{ASSETS_PREFIX}{}-{file_name}", &sha256[..12]);
+        replacements.insert(original.clone(), format!("{BUNDLE_PATH_PREFIX}{file}"));
+        assets.push(ManifestAsset {
+            file,
+            original_path: original,
+            sha256,
+            size: bytes.len() as u64,
+        });
+        contents.push(bytes);
+    }
+    rewrite_strings(&mut preset, &replacements);
+
+    let preset_json = serde_json::to_string_pretty(&preset).map_err(|e| e.to_string())?;
+    let manifest = Manifest {
+        format_version: BUNDLE_FORMAT_VERSION,
+        name: name.to_string(),
+        app_version: app_version.to_string(),
+        created_at: chrono::Utc::now().timestamp_millis(),
+        preset_sha256: sha256_hex(preset_json.as_bytes()),
+        assets,
+    };
+    let manifest_json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+
+    let file = File::create(dest).map_err(|e| e.to_string())?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut add = |name: &str, bytes: &[u8]| -> Result<(), String> {
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        zip.write_all(bytes).map_err(|e| e.to_string())
+    };
+    add(MANIFEST_FILE, manifest_json.as_bytes())?;
+    add(PRESET_FILE, preset_json.as_bytes())?;
+    for (asset, bytes) in manifest.assets.iter().zip(&contents) {
+        add(&asset.file, bytes)?;
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+
+    info!(
+        "Exported preset bundle with {} assets to: {}",
+        manifest.assets.len(),
+        dest.display()
+    );
+    Ok(())
+}
+
+/// Read one entry from the archive, bounded by [`MAX_ENTRY_BYTES`].
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Result<Vec<u8>, String> {
+    let entry = archive
+        .by_name(name)
+        .map_err(|e| format!("Bundle is missing {name}: {e}"))?;
+    let mut bytes = Vec::new();
+    entry
+        .take(MAX_ENTRY_BYTES + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| e.to_string())?;
+    if bytes.len() as u64 > MAX_ENTRY_BYTES {
+        return Err(format!("Bundle entry {name} is too large"));
+    }
+    Ok(bytes)
+}
+
+/// Unpack a bundle: verify checksums, copy assets into [`assets_dir`] and
+/// point the preset at the copied files.
+pub fn import(src: &Path) -> Result<ImportedBundle, String> {
+    import_into(src, &assets_dir()?)
+}
+
+fn import_into(src: &Path, assets_dir: &Path) -> Result<ImportedBundle, String> {
+    let file = File::open(src).map_err(|e| e.to_string())?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Invalid bundle: {e}"))?;
+
+    let manifest: Manifest = serde_json::from_slice(&read_entry(&mut archive, MANIFEST_FILE)?)
+        .map_err(|e| format!("Invalid bundle manifest: {e}"))?;
+    if manifest.format_version > BUNDLE_FORMAT_VERSION {
+        return Err(format!(
+            "Bundle format {} is newer than supported format {BUNDLE_FORMAT_VERSION}",
+            manifest.format_version
+        ));
+    }
+
+    let preset_bytes = read_entry(&mut archive, PRESET_FILE)?;
+    if sha256_hex(&preset_bytes) != manifest.preset_sha256 {
+        return Err("Bundle checksum mismatch for preset.json".into());
+    }
+    let mut preset: Value = serde_json::from_slice(&preset_bytes)
+        .map_err(|e| format!("Invalid preset in bundle: {e}"))?;
+    crate::presets::validate_preset_value(&preset)?;
+
+    // Check every asset before writing any, so a bad bundle leaves nothing behind
+    let mut unpacked = Vec::with_capacity(manifest.assets.len());
+    for asset in &manifest.assets {
+        // Never trust entry names for paths: derive the target from a sanitised name
+        let file_name = asset
+            .file
+            .strip_prefix(ASSETS_PREFIX)
+            .map(safe_file_name)
+            .filter(|n| !n.is_empty() && *n == asset.file[ASSETS_PREFIX.len()..])
+            .ok_or_else(|| format!("Invalid asset name in bundle: {}", asset.file))?;
+        if !is_asset_file(Path::new(&file_name)) {
+            return Err(format!("Unsupported asset type in bundle: {}", asset.file));
+        }
+
+        let bytes = read_entry(&mut archive, &asset.file)?;
+        if sha256_hex(&bytes) != asset.sha256 {
+            return Err(format!("Bundle checksum mismatch for {}", asset.file));
+        }
+        unpacked.push((asset, file_name, bytes));
+    }
+
+    std::fs::create_dir_all(assets_dir).map_err(|e| e.to_string())?;
+    let mut replacements = BTreeMap::new();
+    for (asset, file_name, bytes) in unpacked {
+        let target = assets_dir.join(&file_name);
+        let up_to_date = std::fs::read(&target).is_ok_and(|existing| existing == bytes);
+        if !up_to_date {
+            crate::persist::write_atomic(&target, &bytes)?;
+        }
+        replacements.insert(
+            format!("{BUNDLE_PATH_PREFIX}{}", asset.file),
+            target.to_string_lossy().to_string(),
+        );
+    }
+    rewrite_strings(&mut preset, &replacements);
+
+    info!(
+        "Unpacked preset bundle '{}' with {} assets from: {}",
+        manifest.name,
+        manifest.assets.len(),
+        src.display()
+    );
+    Ok(ImportedBundle {
+        name: manifest.name,
+        contents: serde_json::to_string_pretty(&preset).map_err(|e| e.to_string())?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bundle-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Write a zip with the given manifest, preset and extra entries.
+    fn write_zip(path: &Path, manifest: &Value, preset: &str, entries: &[(&str, &[u8])]) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        let options = SimpleFileOptions::default();
+        zip.start_file(MANIFEST_FILE, options).unwrap();
+        zip.write_all(manifest.to_string().as_bytes()).unwrap();
+        zip.start_file(PRESET_FILE, options).unwrap();
+        zip.write_all(preset.as_bytes()).unwrap();
+        for (name, bytes) in entries {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(bytes).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    fn manifest(preset: &str, assets: Value) -> Value {
+        json!({
+            "formatVersion": BUNDLE_FORMAT_VERSION, "name": "test", "appVersion": "0.0.0",
+            "createdAt": 0, "presetSha256": sha256_hex(preset.as_bytes()), "assets": assets
+        })
+    }
+
+    #[test]
+    fn round_trips_preset_and_assets() {
+        let dir = temp_dir();
+        let sound = dir.join("alert sound.mp3");
+        std::fs::write(&sound, b"ding").unwrap();
+        let sound_path = sound.to_string_lossy().to_string();
+        let preset = json!({
+            "instances": [],
+            "overlay": { "soundMappings": { "follow": { "sound": sound_path } } }
+        });
+        let bundle = dir.join("preset.zip");
+        export(&bundle, "stream", &preset.to_string(), "1.2.3").unwrap();
+
+        // Inside the bundle the absolute path is replaced by a bundle:// reference
+        let mut archive = ZipArchive::new(File::open(&bundle).unwrap()).unwrap();
+        let packed: Value =
+            serde_json::from_slice(&read_entry(&mut archive, PRESET_FILE).unwrap()).unwrap();
+        let reference = packed["overlay"]["soundMappings"]["follow"]["sound"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let entry = reference.strip_prefix(BUNDLE_PATH_PREFIX).unwrap();
+        assert!(entry.starts_with(ASSETS_PREFIX) && entry.ends_with("-alert_sound.mp3"));
+        assert_eq!(read_entry(&mut archive, entry).unwrap(), b"ding");
+
+        let assets = dir.join("assets");
+        let imported = import_into(&bundle, &assets).unwrap();
+        assert_eq!(imported.name, "stream");
+        let unpacked: Value = serde_json::from_str(&imported.contents).unwrap();
+        let target = unpacked["overlay"]["soundMappings"]["follow"]["sound"]
+            .as_str()
+            .unwrap();
+        assert!(Path::new(target).starts_with(&assets));
+        assert_eq!(std::fs::read(target).unwrap(), b"ding");
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn rejects_checksum_mismatches() {
+        let dir = temp_dir();
+        let preset = r#"{"instances":[]}"#;
+        let bundle = dir.join("tampered.zip");
+        let mut bad_preset = manifest(preset, json!([]));
+        bad_preset["presetSha256"] = json!(sha256_hex(b"something else"));
+        write_zip(&bundle, &bad_preset, preset, &[]);
+        let err = import_into(&bundle, &dir.join("assets")).err().unwrap();
+        assert!(err.contains("checksum mismatch for preset.json"));
+
+        let asset = json!([{
+            "file": "assets/a.png", "originalPath": "/a.png",
+            "sha256": sha256_hex(b"original"), "size": 8
+        }]);
+        write_zip(
+            &bundle,
+            &manifest(preset, asset),
+            preset,
+            &[("assets/a.png", b"swapped")],
+        );
+        let err = import_into(&bundle, &dir.join("assets")).err().unwrap();
+        assert!(err.contains("checksum mismatch for assets/a.png"));
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn rejects_traversal_and_absolute_entry_names() {
+        let dir = temp_dir();
+        let preset = r#"{"instances":[]}"#;
+        let bundle = dir.join("evil.zip");
+        for name in [
+            "assets/../evil.png",
+            "/tmp/evil.png",
+            "assets/sub/evil.png",
+            "assets/",
+        ] {
+            let asset = json!([{
+                "file": name, "originalPath": "/x.png",
+                "sha256": sha256_hex(b"x"), "size": 1
+            }]);
+            write_zip(&bundle, &manifest(preset, asset), preset, &[(name, b"x")]);
+            let err = import_into(&bundle, &dir.join("assets")).err().unwrap();
+            assert!(err.contains("Invalid asset name"), "{name}: {err}");
+        }
+        assert!(!dir.join("evil.png").exists());
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn rejects_invalid_presets_and_foreign_assets_without_unpacking() {
+        let dir = temp_dir();
+        let assets = dir.join("assets");
+        let bundle = dir.join("bad.zip");
+        let png = json!({
+            "file": "assets/a.png", "originalPath": "/a.png",
+            "sha256": sha256_hex(b"png"), "size": 3
+        });
+
+        let invalid = r#"{"overlay":{}}"#;
+        write_zip(
+            &bundle,
+            &manifest(invalid, json!([png])),
+            invalid,
+            &[("assets/a.png", b"png")],
+        );
+        let err = import_into(&bundle, &assets).err().unwrap();
+        assert!(err.contains("instances"), "{err}");
+
+        let preset = r#"{"instances":[]}"#;
+        let script = json!({
+            "file": "assets/run.sh", "originalPath": "/run.sh",
+            "sha256": sha256_hex(b"sh"), "size": 2
+        });
+        write_zip(
+            &bundle,
+            &manifest(preset, json!([png, script])),
+            preset,
+            &[("assets/a.png", b"png"), ("assets/run.sh", b"sh")],
+        );
+        let err = import_into(&bundle, &assets).err().unwrap();
+        assert!(err.contains("Unsupported asset type"), "{err}");
+        assert!(!assets.exists());
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,248 @@
+//! Clip creation.
+//!
+//! Helix creates clips asynchronously: `POST /clips` returns an id straight
+//! away and the clip shows up in `GET /clips` once processed. Processed clips
+//! are recorded in the broadcaster's event log as `clip_created` events,
+//! announced on [`CHANNEL_EVENT`] and to every window through
+//! [`CLIP_CREATED_EVENT`]. Clips can be created from a hotkey, the control
+//! API or with the `!clip` chat command by a moderator or the broadcaster.
+
+use crate::auth::AuthState;
+use crate::event_log::EventLogState;
+use crate::events::{ChannelEvent, ChannelEventType, CHANNEL_EVENT};
+use crate::helix::{broadcaster_id, helix_request};
+use crate::scenes;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+/// Emitted with a [`Clip`] once a clip has been processed.
+pub const CLIP_CREATED_EVENT: &str = "clip-created";
+
+/// Chat command that creates a clip.
+pub const CLIP_COMMAND: &str = "!clip";
+
+/// Minimum time between clips requested with [`CLIP_COMMAND`].
+const CHAT_COOLDOWN: Duration = Duration::from_secs(15);
+
+/// Ignores [`CLIP_COMMAND`] while a clip is being made or cooling down.
+static CHAT_GATE: scenes::CommandGate = scenes::CommandGate::new(CHAT_COOLDOWN);
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Twitch advises treating a clip that isn't returned within 15 seconds as failed.
+const POLL_ATTEMPTS: u32 = 15;
+
+/// A processed clip, as announced and recorded in the event log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Clip {
+    pub id: String,
+    pub url: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub thumbnail_url: String,
+    /// Length in seconds.
+    #[serde(default)]
+    pub duration: f64,
+    /// What triggered the clip: hotkey, chat, api or manual.
+    #[serde(default)]
+    pub source: String,
+    /// Unix timestamp in milliseconds. Only set for clips read from the log.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct CreatedClip {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct HelixClip {
+    id: String,
+    url: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    thumbnail_url: String,
+    #[serde(default)]
+    duration: f64,
+}
+
+#[derive(Deserialize)]
+struct HelixResponse<T> {
+    data: Vec<T>,
+}
+
+fn first<T: serde::de::DeserializeOwned>(body: &str) -> Result<Option<T>, String> {
+    let resp: HelixResponse<T> = serde_json::from_str(body).map_err(|e| e.to_string())?;
+    Ok(resp.data.into_iter().next())
+}
+
+fn to_event(clip: &Clip) -> Result<ChannelEvent, String> {
+    let Value::Object(data) = serde_json::to_value(clip).map_err(|e| e.to_string())? else {
+        return Err("clip is not an object".into());
+    };
+    Ok(ChannelEvent {
+        kind: ChannelEventType::ClipCreated,
+        timestamp: chrono::Utc::now().timestamp_millis(),
+        data,
+    })
+}
+
+fn from_event(event: ChannelEvent) -> Option<Clip> {
+    if event.kind != ChannelEventType::ClipCreated {
+        return None;
+    }
+    let clip: Clip = serde_json::from_value(Value::Object(event.data)).ok()?;
+    Some(Clip {
+        timestamp: Some(event.timestamp),
+        ..clip
+    })
+}
+
+/// Wait for Helix to finish processing clip `id`.
+async fn wait_processed(auth: &AuthState, id: &str) -> Result<HelixClip, String> {
+    let path = format!("/clips?id={id}");
+    for _ in 0..POLL_ATTEMPTS {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        if let Some(clip) =
+            first::<HelixClip>(&helix_request(auth, Method::GET, &path, None).await?)?
+        {
+            return Ok(clip);
+        }
+    }
+    Err("Twitch did not finish the clip; it may have failed".into())
+}
+
+/// Clip the last moments of the live stream, wait until the clip is
+/// processed, then log and announce it.
+pub async fn create(app: &AppHandle, source: &str) -> Result<Clip, String> {
+    let auth = app.state::<Arc<AuthState>>();
+    let user_id = broadcaster_id(&auth).await?;
+    let path = format!("/clips?broadcaster_id={user_id}");
+    let created = first::<CreatedClip>(&helix_request(&auth, Method::POST, &path, None).await?)?
+        .ok_or("Twitch returned no clip")?;
+    info!(
+        "[clips] created clip {} ({source}), waiting for processing",
+        created.id
+    );
+
+    let processed = wait_processed(&auth, &created.id).await?;
+    let clip = Clip {
+        id: processed.id,
+        url: processed.url,
+        title: processed.title,
+        thumbnail_url: processed.thumbnail_url,
+        duration: processed.duration,
+        source: source.to_string(),
+        timestamp: None,
+    };
+    info!("[clips] clip ready: {}", clip.url);
+
+    let channel = auth
+        .stored_username()
+        .ok_or("Unknown broadcaster; please log in again")?;
+    let event = to_event(&clip)?;
+    if let Err(e) = app.state::<EventLogState>().append(&channel, &event) {
+        warn!("[clips] failed to record clip: {e}");
+    }
+    if let Err(e) = app.emit(CHANNEL_EVENT, &event) {
+        warn!("[clips] failed to emit clip event: {e}");
+    }
+    if let Err(e) = app.emit(CLIP_CREATED_EVENT, &clip) {
+        warn!("[clips] failed to emit clip: {e}");
+    }
+    Ok(clip)
+}
+
+/// Create a clip without waiting for the result, logging failures.
+pub fn spawn_create(app: &AppHandle, source: &'static str) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = create(&app, source).await {
+            warn!("[clips] failed to create clip ({source}): {e}");
+        }
+    });
+}
+
+/// Handle a chat message from a moderator or the broadcaster. Returns whether
+/// it was a clip command. Repeats are ignored while a clip is being made and
+/// for [`CHAT_COOLDOWN`] after one was requested.
+pub fn chat_command(app: &AppHandle, text: &str) -> bool {
+    if scenes::command_args(text, CLIP_COMMAND).is_none() {
+        return false;
+    }
+    if !CHAT_GATE.try_start(Instant::now()) {
+        info!("[clips] ignoring {CLIP_COMMAND}: a clip was requested moments ago");
+        return true;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = create(&app, "chat").await {
+            warn!("[clips] failed to create clip (chat): {e}");
+        }
+        CHAT_GATE.finish();
+    });
+    true
+}
+
+/// Create a clip and wait until Twitch has processed it. The stream must be live.
+#[tauri::command]
+pub async fn create_clip(app: AppHandle) -> Result<Clip, String> {
+    create(&app, "manual").await
+}
+
+/// Clips recorded in the logged-in broadcaster's event log, oldest first.
+#[tauri::command]
+pub fn list_clips(
+    auth: tauri::State<'_, Arc<AuthState>>,
+    log: tauri::State<'_, EventLogState>,
+) -> Result<Vec<Clip>, String> {
+    let Some(channel) = auth.stored_username() else {
+        return Ok(Vec::new());
+    };
+    Ok(log
+        .read(&channel)?
+        .into_iter()
+        .filter_map(from_event)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_helix_clip_and_round_trips_through_event_log() {
+        assert!(first::<HelixClip>(r#"{"data":[],"pagination":{}}"#)
+            .unwrap()
+            .is_none());
+        let processed = first::<HelixClip>(
+            r#"{"data":[{"id":"AwkwardHelplessSalamanderSwiftRage","url":"https://clips.twitch.tv/AwkwardHelplessSalamanderSwiftRage","title":"gg","thumbnail_url":"https://example.com/t.jpg","duration":30.0,"view_count":0}]}"#,
+        )
+        .unwrap()
+        .unwrap();
+        let clip = Clip {
+            id: processed.id,
+            url: processed.url,
+            title: processed.title,
+            thumbnail_url: processed.thumbnail_url,
+            duration: processed.duration,
+            source: "chat".into(),
+            timestamp: None,
+        };
+        let event = to_event(&clip).unwrap();
+        let read = from_event(ChannelEvent::from_log_line(&event.to_log_line().unwrap()).unwrap())
+            .unwrap();
+        assert_eq!(read.url, clip.url);
+        assert_eq!(read.source, "chat");
+        assert_eq!(read.timestamp, Some(event.timestamp));
+    }
+}
@@ -0,0 +1,375 @@
+//! Local control API for stream decks and scripts.
+//!
+//! A small HTTP server on `127.0.0.1:`[`CONTROL_PORT`] runs actions sent as
+//! `POST` requests with an `Authorization: Bearer <token>` header. The token
+//! is generated on first start, stored in the data directory and shown in the
+//! settings panel. Browsers can't send that header cross-origin without a
+//! preflight this server never answers, so web pages can't trigger actions.
+//!
+//! Routes:
+//! - `POST /markers`, optionally with `{"description": "..."}`, creates a
+//!   stream marker and returns it.
+//! - `POST /clips` clips the stream and returns the clip once Twitch has
+//!   processed it.
+
+use crate::clips;
+use crate::markers;
+use crate::persist;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::Path;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{info, warn};
+
+/// Port the control API listens on, on the loopback interface only.
+pub const CONTROL_PORT: u16 = 47823;
+
+const MAX_HEAD_BYTES: usize = 8 * 1024;
+const MAX_BODY_BYTES: usize = 8 * 1024;
+
+/// Time a client gets to send its whole request.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How to reach the control API.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ControlApiInfo {
+    pub url: String,
+    pub token: String,
+}
+
+/// Managed state holding the control API address and token.
+pub struct ControlState {
+    info: ControlApiInfo,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Request {
+    method: String,
+    path: String,
+    /// Bearer token from the `Authorization` header.
+    token: Option<String>,
+    body: Vec<u8>,
+}
+
+/// An action requested through the API.
+#[derive(Debug, PartialEq)]
+enum Action {
+    Marker { description: String },
+    Clip,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct MarkerBody {
+    description: String,
+}
+
+/// A rejected request: HTTP status and message.
+type Rejection = (u16, String);
+
+fn reject(status: u16, message: impl Into<String>) -> Rejection {
+    (status, message.into())
+}
+
+/// Parse the request line and headers. Returns the request without its body
+/// and the declared body length.
+fn parse_head(head: &str) -> Result<(Request, usize), Rejection> {
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or("").split_whitespace();
+    let (Some(method), Some(target)) = (request_line.next(), request_line.next()) else {
+        return Err(reject(400, "Malformed request line"));
+    };
+    let mut request = Request {
+        method: method.to_string(),
+        path: target.split('?').next().unwrap_or(target).to_string(),
+        ..Request::default()
+    };
+    let mut length = 0;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            length = value
+                .parse()
+                .map_err(|_| reject(400, "Invalid Content-Length"))?;
+        } else if name.eq_ignore_ascii_case("authorization") {
+            request.token = value
+                .strip_prefix("Bearer ")
+                .map(|token| token.trim().to_string());
+        }
+    }
+    Ok((request, length))
+}
+
+/// Check the token and map the request to an action.
+fn route(request: &Request, token: &str) -> Result<Action, Rejection> {
+    if request.token.as_deref() != Some(token) {
+        return Err(reject(401, "Missing or wrong token"));
+    }
+    match (request.method.as_str(), request.path.as_str()) {
+        ("POST", "/markers") => {
+            let body: MarkerBody = if request.body.iter().all(u8::is_ascii_whitespace) {
+                MarkerBody::default()
+            } else {
+                serde_json::from_slice(&request.body)
+                    .map_err(|e| reject(400, format!("Invalid body: {e}")))?
+            };
+            Ok(Action::Marker {
+                description: body.description,
+            })
+        }
+        ("POST", "/clips") => Ok(Action::Clip),
+        (_, "/markers" | "/clips") => Err(reject(405, "Use POST")),
+        _ => Err(reject(404, "Not found")),
+    }
+}
+
+async fn run(app: &AppHandle, action: Action) -> Result<Value, String> {
+    match action {
+        Action::Marker { description } => {
+            let marker = markers::create(app, &description, "api").await?;
+            serde_json::to_value(marker).map_err(|e| e.to_string())
+        }
+        Action::Clip => {
+            let clip = clips::create(app, "api").await?;
+            serde_json::to_value(clip).map_err(|e| e.to_string())
+        }
+    }
+}
+
+fn response(status: u16, body: &Value) -> Vec<u8> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        413 => "Payload Too Large",
+        _ => "Bad Gateway",
+    };
+    let body = body.to_string();
+    format!(
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+    .into_bytes()
+}
+
+async fn read_request(stream: &mut TcpStream) -> Result<Request, Rejection> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 1024];
+    let head_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        if buf.len() > MAX_HEAD_BYTES {
+            return Err(reject(413, "Request head too large"));
+        }
+        let n = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|e| reject(400, e.to_string()))?;
+        if n == 0 {
+            return Err(reject(400, "Incomplete request"));
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+    let head = std::str::from_utf8(&buf[..head_end]).map_err(|_| reject(400, "Invalid head"))?;
+    let (mut request, length) = parse_head(head)?;
+    if length > MAX_BODY_BYTES {
+        return Err(reject(413, "Body too large"));
+    }
+    let mut body = buf[head_end + 4..].to_vec();
+    while body.len() < length {
+        let n = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|e| reject(400, e.to_string()))?;
+        if n == 0 {
+            return Err(reject(400, "Incomplete body"));
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    body.truncate(length);
+    request.body = body;
+    Ok(request)
+}
+
+async fn handle(app: AppHandle, mut stream: TcpStream, token: String) {
+    let request = tokio::time::timeout(READ_TIMEOUT, read_request(&mut stream))
+        .await
+        .unwrap_or_else(|_| Err(reject(408, "Timed out")));
+    let (status, body) = match request.and_then(|r| route(&r, &token)) {
+        Ok(action) => {
+            info!("[control] {action:?}");
+            match run(&app, action).await {
+                Ok(value) => (200, value),
+                Err(e) => {
+                    warn!("[control] action failed: {e}");
+                    (502, json!({ "error": e }))
+                }
+            }
+        }
+        Err((status, message)) => (status, json!({ "error": message })),
+    };
+    if let Err(e) = stream.write_all(&response(status, &body)).await {
+        warn!("[control] failed to respond: {e}");
+    }
+}
+
+/// The stored token, generating one on first use.
+fn load_token(data_dir: &Path) -> Result<String, String> {
+    let path = data_dir.join("control-token.json");
+    if let Some(Value::String(token)) = persist::read_json(&path)? {
+        if !token.is_empty() {
+            return Ok(token);
+        }
+    }
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    persist::write_json(&path, &token)?;
+    Ok(token)
+}
+
+/// Start serving the control API in the background.
+pub fn start(app: &AppHandle, data_dir: &Path) -> Result<(), String> {
+    let token = load_token(data_dir)?;
+    app.manage(ControlState {
+        info: ControlApiInfo {
+            url: format!("http://127.0.0.1:{CONTROL_PORT}"),
+            token: token.clone(),
+        },
+    });
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let listener = match TcpListener::bind(("127.0.0.1", CONTROL_PORT)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                warn!("[control] could not listen on port {CONTROL_PORT}: {e}");
+                return;
+            }
+        };
+        info!("[control] listening on 127.0.0.1:{CONTROL_PORT}");
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tauri::async_runtime::spawn(handle(app.clone(), stream, token.clone()));
+                }
+                Err(e) => warn!("[control] accept failed: {e}"),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Address and token of the control API.
+#[tauri::command]
+pub fn get_control_api(state: tauri::State<'_, ControlState>) -> ControlApiInfo {
+    state.info.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(path: &str, token: Option<&str>, body: &str) -> Request {
+        Request {
+            method: "POST".into(),
+            path: path.into(),
+            token: token.map(String::from),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn parses_request_head() {
+        let (request, length) = parse_head(
+            "POST /markers?x=1 HTTP/1.1\r\nHost: localhost\r\ncontent-length: 12\r\nAuthorization: Bearer abc",
+        )
+        .unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/markers");
+        assert_eq!(request.token.as_deref(), Some("abc"));
+        assert_eq!(length, 12);
+
+        assert_eq!(parse_head("").unwrap_err().0, 400);
+        assert_eq!(
+            parse_head("POST / HTTP/1.1\r\nContent-Length: lots")
+                .unwrap_err()
+                .0,
+            400
+        );
+    }
+
+    #[test]
+    fn routes_marker_requests() {
+        assert_eq!(
+            route(&post("/markers", Some("t"), ""), "t"),
+            Ok(Action::Marker {
+                description: String::new()
+            })
+        );
+        assert_eq!(
+            route(
+                &post("/markers", Some("t"), r#"{"description":"clutch"}"#),
+                "t"
+            ),
+            Ok(Action::Marker {
+                description: "clutch".into()
+            })
+        );
+        assert_eq!(route(&post("/markers", None, ""), "t").unwrap_err().0, 401);
+        assert_eq!(
+            route(&post("/markers", Some("x"), ""), "t").unwrap_err().0,
+            401
+        );
+        assert_eq!(
+            route(&post("/markers", Some("t"), "{"), "t").unwrap_err().0,
+            400
+        );
+        assert_eq!(
+            route(&post("/nope", Some("t"), ""), "t").unwrap_err().0,
+            404
+        );
+        let get = Request {
+            method: "GET".into(),
+            ..post("/markers", Some("t"), "")
+        };
+        assert_eq!(route(&get, "t").unwrap_err().0, 405);
+    }
+
+    #[test]
+    fn routes_clip_requests() {
+        assert_eq!(route(&post("/clips", Some("t"), ""), "t"), Ok(Action::Clip));
+        assert_eq!(route(&post("/clips", None, ""), "t").unwrap_err().0, 401);
+        let get = Request {
+            method: "GET".into(),
+            ..post("/clips", Some("t"), "")
+        };
+        assert_eq!(route(&get, "t").unwrap_err().0, 405);
+    }
+
+    #[test]
+    fn token_is_generated_once() {
+        let dir = std::env::temp_dir().join(format!("control-test-{}", uuid::Uuid::new_v4()));
+        let token = load_token(&dir).unwrap();
+        assert_eq!(token.len(), 32);
+        assert_eq!(load_token(&dir).unwrap(), token);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn responses_carry_length_and_status() {
+        let raw = String::from_utf8(response(401, &json!({ "error": "no" }))).unwrap();
+        assert!(raw.starts_with("HTTP/1.1 401 Unauthorized\r\n"));
+        assert!(raw.contains("Content-Length: 14\r\n"));
+        assert!(raw.ends_with("\r\n\r\n{\"error\":\"no\"}"));
+    }
+}
@@ -1,8 +1,23 @@
-use chrono::Local;
-use std::fs::{self, OpenOptions};
+use crate::events::ChannelEvent;
+use chrono::{Local, NaiveDate};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
 use std::io::Write;
 use std::path::PathBuf;
 use std::sync::Mutex;
+use tracing::warn;
+
+/// Flush a channel's buffer every this many events to balance I/O and data safety.
+const FLUSH_THRESHOLD: usize = 20;
+
+/// Maximum number of log files kept open at once. The least recently written
+/// file is closed when the limit is reached and reopened on demand.
+const MAX_OPEN_FILES: usize = 8;
+
+/// Twitch logins are case-insensitive; log files always use the lowercase form.
+fn normalise_channel(channel: &str) -> String {
+    channel.trim().to_lowercase()
+}
 
 /// Managed state for the event log writer.
 pub struct EventLogState {
@@ -10,45 +25,226 @@ pub struct EventLogState {
 }
 
 struct LogWriter {
-    current_date: String,
-    current_channel: String,
     log_dir: PathBuf,
+    streams: HashMap<StreamKey, ChannelStream>,
+    /// Monotonic counter used to find the least recently used stream.
+    tick: u64,
+}
+
+/// Identifies a single log file: one per channel per day.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct StreamKey {
+    channel: String,
+    date: String,
+}
+
+/// Pending lines and the (lazily opened) file for one channel and day.
+#[derive(Default)]
+struct ChannelStream {
     buffer: Vec<String>,
+    file: Option<File>,
+    last_used: u64,
 }
 
 impl EventLogState {
     pub fn new(log_dir: PathBuf) -> Self {
         Self {
             inner: Mutex::new(LogWriter {
-                current_date: String::new(),
-                current_channel: String::new(),
                 log_dir,
-                buffer: Vec::new(),
+                streams: HashMap::new(),
+                tick: 0,
             }),
         }
     }
 
-    fn flush_inner(writer: &mut LogWriter) -> Result<(), String> {
-        if writer.buffer.is_empty() || writer.current_channel.is_empty() {
-            return Ok(());
+    /// Set an invalid payload aside so it can be inspected without corrupting
+    /// the channel log.
+    fn quarantine(writer: &LogWriter, channel: &str, raw: &str, reason: &str) {
+        let date = Local::now().format("%Y-%m-%d");
+        let path = writer.log_dir.join(format!("quarantine-{date}.jsonl"));
+        let line = serde_json::json!({ "channel": channel, "reason": reason, "raw": raw });
+        let result = fs::create_dir_all(&writer.log_dir).and_then(|()| {
+            let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+            writeln!(file, "{line}")
+        });
+        if let Err(e) = result {
+            warn!("[event-log] failed to quarantine event: {e}");
         }
+    }
+
+    /// Buffer an event payload sent by the frontend. Invalid payloads are
+    /// quarantined rather than written to the channel log.
+    pub fn append_raw(&self, channel: &str, event: &str) -> Result<(), String> {
+        let mut writer = self.inner.lock().map_err(|e| e.to_string())?;
 
-        fs::create_dir_all(&writer.log_dir).map_err(|e| e.to_string())?;
+        let line = match ChannelEvent::parse(event).and_then(|e| e.to_log_line()) {
+            Ok(line) => line,
+            Err(reason) => {
+                warn!("[event-log] rejected event for {channel}: {reason}");
+                Self::quarantine(&writer, channel, event, &reason);
+                return Err(format!("Invalid event: {reason}"));
+            }
+        };
 
-        let filename = format!("{}-{}.jsonl", writer.current_channel, writer.current_date);
-        let path = writer.log_dir.join(filename);
+        let key = writer.push(channel, line)?;
+        if writer.streams.get(&key).map_or(0, |s| s.buffer.len()) >= FLUSH_THRESHOLD {
+            writer.flush_stream(&key)?;
+        }
+        Ok(())
+    }
 
-        let mut file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&path)
-            .map_err(|e| e.to_string())?;
+    /// Write out every buffered line, e.g. before the app exits.
+    pub fn flush(&self) -> Result<(), String> {
+        let mut writer = self.inner.lock().map_err(|e| e.to_string())?;
+        writer.flush_all()
+    }
 
-        for line in writer.buffer.drain(..) {
+    /// Record an event produced by the backend and write it out immediately.
+    pub fn append(&self, channel: &str, event: &ChannelEvent) -> Result<(), String> {
+        let line = event.to_log_line()?;
+        let mut writer = self.inner.lock().map_err(|e| e.to_string())?;
+        let key = writer.push(channel, line)?;
+        writer.flush_stream(&key)
+    }
+
+    /// Every logged event of `channel`, oldest file first. Unreadable lines are skipped.
+    pub fn read(&self, channel: &str) -> Result<Vec<ChannelEvent>, String> {
+        let mut writer = self.inner.lock().map_err(|e| e.to_string())?;
+        writer.flush_all()?;
+        let entries = match fs::read_dir(&writer.log_dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.to_string()),
+        };
+
+        // Files written before channels were normalised may be mixed-case
+        let prefix = format!("{}-", normalise_channel(channel));
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .map(str::to_lowercase)
+                    .as_deref()
+                    .and_then(|name| name.strip_prefix(&prefix))
+                    .and_then(|rest| rest.strip_suffix(".jsonl"))
+                    .is_some_and(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok())
+            })
+            .collect();
+        files.sort();
+
+        let mut events = Vec::new();
+        for path in files {
+            let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            events.extend(
+                content
+                    .lines()
+                    .filter_map(|line| ChannelEvent::from_log_line(line).ok()),
+            );
+        }
+        Ok(events)
+    }
+}
+
+impl LogWriter {
+    /// Write the buffered lines for one stream, opening its file if needed.
+    fn flush_stream(&mut self, key: &StreamKey) -> Result<(), String> {
+        let needs_open = match self.streams.get(key) {
+            Some(stream) if !stream.buffer.is_empty() => stream.file.is_none(),
+            _ => return Ok(()),
+        };
+
+        if needs_open {
+            self.evict_open_files(MAX_OPEN_FILES - 1);
+            fs::create_dir_all(&self.log_dir).map_err(|e| e.to_string())?;
+            let filename = format!("{}-{}.jsonl", key.channel, key.date);
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.log_dir.join(filename))
+                .map_err(|e| e.to_string())?;
+            if let Some(stream) = self.streams.get_mut(key) {
+                stream.file = Some(file);
+            }
+        }
+
+        let Some(stream) = self.streams.get_mut(key) else {
+            return Ok(());
+        };
+        let Some(file) = stream.file.as_mut() else {
+            return Ok(());
+        };
+        for line in stream.buffer.drain(..) {
             writeln!(file, "{line}").map_err(|e| e.to_string())?;
         }
+        file.flush().map_err(|e| e.to_string())
+    }
 
-        file.flush().map_err(|e| e.to_string())?;
+    /// Flush every stream with pending lines.
+    fn flush_all(&mut self) -> Result<(), String> {
+        let keys: Vec<StreamKey> = self.streams.keys().cloned().collect();
+        for key in keys {
+            self.flush_stream(&key)?;
+        }
+        Ok(())
+    }
+
+    /// Close least recently used files until at most `limit` remain open.
+    fn evict_open_files(&mut self, limit: usize) {
+        loop {
+            let open: Vec<(&StreamKey, u64)> = self
+                .streams
+                .iter()
+                .filter(|(_, s)| s.file.is_some())
+                .map(|(k, s)| (k, s.last_used))
+                .collect();
+            if open.len() <= limit {
+                return;
+            }
+            let Some(oldest) = open
+                .into_iter()
+                .min_by_key(|(_, used)| *used)
+                .map(|(k, _)| k.clone())
+            else {
+                return;
+            };
+            if let Some(stream) = self.streams.get_mut(&oldest) {
+                stream.file = None;
+            }
+        }
+    }
+
+    /// Buffer a line for today's file of `channel`.
+    fn push(&mut self, channel: &str, line: String) -> Result<StreamKey, String> {
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        // Detect date change — flush and close the previous day's files
+        self.rotate(&today)?;
+
+        self.tick += 1;
+        let tick = self.tick;
+        let key = StreamKey {
+            channel: normalise_channel(channel),
+            date: today,
+        };
+        let stream = self.streams.entry(key.clone()).or_default();
+        stream.buffer.push(line);
+        stream.last_used = tick;
+        Ok(key)
+    }
+
+    /// Flush and drop streams from previous days.
+    fn rotate(&mut self, today: &str) -> Result<(), String> {
+        let stale: Vec<StreamKey> = self
+            .streams
+            .keys()
+            .filter(|k| k.date != today)
+            .cloned()
+            .collect();
+        for key in stale {
+            self.flush_stream(&key)?;
+            self.streams.remove(&key);
+        }
         Ok(())
     }
 }
@@ -59,28 +255,92 @@ pub fn append_event_log(
     event: String,
     state: tauri::State<'_, EventLogState>,
 ) -> Result<(), String> {
-    let today = Local::now().format("%Y-%m-%d").to_string();
-    let mut writer = state.inner.lock().map_err(|e| e.to_string())?;
+    state.append_raw(&channel, &event)
+}
+
+#[tauri::command]
+pub fn flush_event_log(state: tauri::State<'_, EventLogState>) -> Result<(), String> {
+    state.flush()
+}
 
-    // Detect date or channel change — flush and rotate
-    if writer.current_date != today || writer.current_channel != channel {
-        EventLogState::flush_inner(&mut writer)?;
-        writer.current_date = today;
-        writer.current_channel = channel;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_log_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("event-log-test-{}", uuid::Uuid::new_v4()))
     }
 
-    writer.buffer.push(event);
+    fn event(n: usize) -> String {
+        serde_json::json!({ "type": "chat", "timestamp": 1_700_000_000_000i64 + n as i64 })
+            .to_string()
+    }
 
-    // Flush every 20 events to balance I/O and data safety
-    if writer.buffer.len() >= 20 {
-        EventLogState::flush_inner(&mut writer)?;
+    fn line_count(dir: &std::path::Path, channel: &str) -> usize {
+        let date = Local::now().format("%Y-%m-%d");
+        fs::read_to_string(dir.join(format!("{channel}-{date}.jsonl")))
+            .map(|s| s.lines().count())
+            .unwrap_or(0)
     }
 
-    Ok(())
-}
+    #[test]
+    fn buffers_until_threshold_and_flushes_on_demand() {
+        let dir = temp_log_dir();
+        let log = EventLogState::new(dir.clone());
+        for n in 0..FLUSH_THRESHOLD - 1 {
+            log.append_raw("Streamer", &event(n)).unwrap();
+        }
+        assert_eq!(line_count(&dir, "streamer"), 0);
 
-#[tauri::command]
-pub fn flush_event_log(state: tauri::State<'_, EventLogState>) -> Result<(), String> {
-    let mut writer = state.inner.lock().map_err(|e| e.to_string())?;
-    EventLogState::flush_inner(&mut writer)
+        log.append_raw("streamer", &event(FLUSH_THRESHOLD)).unwrap();
+        assert_eq!(line_count(&dir, "streamer"), FLUSH_THRESHOLD);
+
+        log.append_raw("STREAMER", &event(0)).unwrap();
+        log.flush().unwrap();
+        assert_eq!(line_count(&dir, "streamer"), FLUSH_THRESHOLD + 1);
+        assert_eq!(log.read("Streamer").unwrap().len(), FLUSH_THRESHOLD + 1);
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn evicts_least_recently_used_files() {
+        let dir = temp_log_dir();
+        let log = EventLogState::new(dir.clone());
+        let parsed = ChannelEvent::parse(&event(0)).unwrap();
+        for n in 0..MAX_OPEN_FILES + 2 {
+            log.append(&format!("channel{n}"), &parsed).unwrap();
+        }
+        let writer = log.inner.lock().unwrap();
+        let open: Vec<&str> = writer
+            .streams
+            .iter()
+            .filter(|(_, s)| s.file.is_some())
+            .map(|(k, _)| k.channel.as_str())
+            .collect();
+        assert_eq!(open.len(), MAX_OPEN_FILES);
+        assert!(!open.contains(&"channel0") && !open.contains(&"channel1"));
+        drop(writer);
+
+        // Evicted files are reopened on demand
+        log.append("channel0", &parsed).unwrap();
+        assert_eq!(line_count(&dir, "channel0"), 2);
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn quarantines_invalid_events() {
+        let dir = temp_log_dir();
+        let log = EventLogState::new(dir.clone());
+        let err = log
+            .append_raw("streamer", r#"{"type":"nope"}"#)
+            .unwrap_err();
+        assert!(err.starts_with("Invalid event"));
+        log.flush().unwrap();
+        assert_eq!(line_count(&dir, "streamer"), 0);
+
+        let date = Local::now().format("%Y-%m-%d");
+        let quarantined = fs::read_to_string(dir.join(format!("quarantine-{date}.jsonl"))).unwrap();
+        assert!(quarantined.contains("nope"));
+        fs::remove_dir_all(dir).ok();
+    }
 }
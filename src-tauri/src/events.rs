@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Schema version stamped onto every persisted event line.
+///
+/// Version 0 is the implicit version of lines written before stamping
+/// existed; they share the same shape and are read without conversion.
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// Tauri event name used to deliver backend-originated channel events to
+/// every window's event bus.
+pub const CHANNEL_EVENT: &str = "channel-event";
+
+/// Channel event types emitted by all event sources.
+/// Mirrors `ChannelEventType` in `src/events/bus.ts`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelEventType {
+    Chat,
+    Join,
+    Part,
+    Follow,
+    Raid,
+    Subscribe,
+    GiftSub,
+    Cheer,
+    Ban,
+    Unban,
+    StreamOnline,
+    StreamOffline,
+    ChannelUpdate,
+    FollowerCountUpdate,
+    ViewerCountUpdate,
+    ChannelPointsRedemption,
+    StreamMarker,
+    ClipCreated,
+}
+
+/// A single channel event. Mirrors `ChannelEvent` in `src/events/bus.ts`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelEvent {
+    #[serde(rename = "type")]
+    pub kind: ChannelEventType,
+    /// Unix timestamp in milliseconds.
+    pub timestamp: i64,
+    #[serde(default)]
+    pub data: Map<String, Value>,
+}
+
+/// On-disk representation of an event: the event plus its schema version.
+#[derive(Serialize, Deserialize)]
+struct VersionedEvent {
+    #[serde(rename = "v", default)]
+    version: u32,
+    #[serde(flatten)]
+    event: ChannelEvent,
+}
+
+/// Timestamps below this are assumed to be in seconds rather than milliseconds.
+const SECONDS_THRESHOLD: f64 = 1e11;
+
+impl ChannelEvent {
+    /// Parse and normalise an event payload sent by the frontend.
+    ///
+    /// Accepts fractional or second-based timestamps and a missing or null
+    /// `data` field; rejects unknown types and non-object payloads.
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let value: Value = serde_json::from_str(raw).map_err(|e| format!("invalid JSON: {e}"))?;
+        let mut obj = match value {
+            Value::Object(obj) => obj,
+            _ => return Err("event must be a JSON object".into()),
+        };
+
+        let kind_value = obj.remove("type").ok_or("missing \"type\"")?;
+        let kind: ChannelEventType = serde_json::from_value(kind_value.clone())
+            .map_err(|_| format!("unknown event type: {kind_value}"))?;
+
+        let timestamp = obj
+            .get("timestamp")
+            .and_then(Value::as_f64)
+            .filter(|t| t.is_finite() && *t > 0.0)
+            .ok_or("missing or invalid \"timestamp\"")?;
+        let timestamp = if timestamp < SECONDS_THRESHOLD {
+            (timestamp * 1000.0).round() as i64
+        } else {
+            timestamp.round() as i64
+        };
+
+        let data = match obj.remove("data") {
+            None | Some(Value::Null) => Map::new(),
+            Some(Value::Object(data)) => data,
+            Some(_) => return Err("\"data\" must be an object".into()),
+        };
+
+        Ok(Self {
+            kind,
+            timestamp,
+            data,
+        })
+    }
+
+    /// Serialise the event as a single versioned JSONL line (without newline).
+    pub fn to_log_line(&self) -> Result<String, String> {
+        serde_json::to_string(&VersionedEvent {
+            version: EVENT_SCHEMA_VERSION,
+            event: self.clone(),
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    /// Read a line from an event log, migrating older schema versions.
+    pub fn from_log_line(line: &str) -> Result<Self, String> {
+        let versioned: VersionedEvent = serde_json::from_str(line).map_err(|e| e.to_string())?;
+        match versioned.version {
+            0 | EVENT_SCHEMA_VERSION => Ok(versioned.event),
+            v => Err(format!("unsupported event schema version: {v}")),
+        }
+    }
+
+    /// Whether the event was replayed or synthesised rather than received
+    /// from Twitch. Simulated events must never act on the real channel.
+    pub fn is_simulated(&self) -> bool {
+        self.data.get("simulated").and_then(Value::as_bool) == Some(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn log_lines_round_trip_with_schema_version() {
+        let event = ChannelEvent::parse(
+            &json!({ "type": "cheer", "timestamp": 1_700_000_000, "data": { "bits": 100 } })
+                .to_string(),
+        )
+        .unwrap();
+        assert_eq!(event.timestamp, 1_700_000_000_000);
+
+        let line = event.to_log_line().unwrap();
+        let stamped: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(stamped["v"], json!(EVENT_SCHEMA_VERSION));
+        assert_eq!(ChannelEvent::from_log_line(&line).unwrap(), event);
+    }
+
+    #[test]
+    fn reads_unversioned_lines_and_rejects_future_versions() {
+        let v0 = r#"{"type":"follow","timestamp":1700000000000,"data":{"user_name":"a"}}"#;
+        let event = ChannelEvent::from_log_line(v0).unwrap();
+        assert_eq!(event.kind, ChannelEventType::Follow);
+        assert_eq!(event.data["user_name"], json!("a"));
+
+        let future = r#"{"v":99,"type":"follow","timestamp":1700000000000,"data":{}}"#;
+        assert!(ChannelEvent::from_log_line(future)
+            .unwrap_err()
+            .contains("unsupported"));
+        assert!(ChannelEvent::parse(r#"{"type":"nope","timestamp":1}"#).is_err());
+    }
+}
@@ -1,6 +1,9 @@
 use crate::auth::{get_valid_token, AuthState};
-use serde::Deserialize;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::sync::Arc;
+use tracing::info;
 
 const CLIENT_ID: &str = "yu2txwsc619qgqaghrv1xzf66swhad";
 
@@ -158,3 +161,134 @@ pub async fn eventsub_subscribe(
 
     resp.text().await.map_err(|e| e.to_string())
 }
+
+/// A failed [`helix_request`]. Converts into the `String` errors used by commands.
+#[derive(Debug)]
+pub struct HelixError {
+    /// HTTP status Twitch answered with; `None` if the request never got an answer.
+    pub status: Option<u16>,
+    pub message: String,
+}
+
+impl HelixError {
+    /// Whether Twitch reported the resource as missing.
+    pub fn is_not_found(&self) -> bool {
+        self.status == Some(404)
+    }
+}
+
+impl std::fmt::Display for HelixError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<String> for HelixError {
+    fn from(message: String) -> Self {
+        Self {
+            status: None,
+            message,
+        }
+    }
+}
+
+impl From<HelixError> for String {
+    fn from(e: HelixError) -> Self {
+        e.message
+    }
+}
+
+/// Authenticated Helix request for use by other backend modules, with the
+/// same refresh-and-retry-once handling on 401 as the commands above.
+/// Returns the raw response body.
+pub async fn helix_request(
+    state: &AuthState,
+    method: Method,
+    path: &str,
+    body: Option<&Value>,
+) -> Result<String, HelixError> {
+    let url = format!("https://api.twitch.tv/helix{path}");
+    let send = |token: String| {
+        let mut req = state
+            .http()
+            .request(method.clone(), &url)
+            .header("Client-Id", CLIENT_ID)
+            .header("Authorization", format!("Bearer {token}"));
+        if let Some(body) = body {
+            req = req.json(body);
+        }
+        req.send()
+    };
+
+    let data = get_valid_token(state).await?;
+    let mut resp = send(data.access_token).await.map_err(|e| e.to_string())?;
+    if resp.status().as_u16() == 401 {
+        // Token might have just expired — refresh and retry once
+        let data = get_valid_token(state).await?;
+        resp = send(data.access_token).await.map_err(|e| e.to_string())?;
+    }
+
+    let status = resp.status();
+    let text = resp.text().await.map_err(|e| e.to_string())?;
+    if !status.is_success() {
+        let message = serde_json::from_str::<Value>(&text)
+            .ok()
+            .and_then(|v| v.get("message")?.as_str().map(String::from))
+            .unwrap_or(text);
+        return Err(HelixError {
+            status: Some(status.as_u16()),
+            message: format!("Helix {method} {path} failed ({status}): {message}"),
+        });
+    }
+    Ok(text)
+}
+
+/// Twitch user id of the logged-in account, which owns the channel.
+pub async fn broadcaster_id(state: &AuthState) -> Result<String, String> {
+    let user_id = get_valid_token(state).await?.user_id;
+    if user_id.is_empty() {
+        return Err("Unknown broadcaster id; please log in again".into());
+    }
+    Ok(user_id)
+}
+
+/// Final state of a channel-point redemption.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RedemptionStatus {
+    /// Keep the points spent.
+    Fulfilled,
+    /// Refund the points.
+    Canceled,
+}
+
+/// Move an unfulfilled redemption to `status`.
+///
+/// Twitch only allows this for rewards created with this app's client id,
+/// and for redemptions still in the queue.
+pub async fn update_redemption(
+    state: &AuthState,
+    reward_id: &str,
+    redemption_id: &str,
+    status: RedemptionStatus,
+) -> Result<(), String> {
+    let broadcaster_id = broadcaster_id(state).await?;
+    let path = format!(
+        "/channel_points/custom_rewards/redemptions?broadcaster_id={broadcaster_id}&reward_id={reward_id}&id={redemption_id}"
+    );
+    let body = serde_json::json!({ "status": status });
+    helix_request(state, Method::PATCH, &path, Some(&body)).await?;
+    info!("[helix] redemption {redemption_id} marked {status:?}");
+    Ok(())
+}
+
+/// Fulfil or cancel (refund) a channel-point redemption.
+#[tauri::command]
+pub async fn update_redemption_status(
+    reward_id: String,
+    redemption_id: String,
+    status: RedemptionStatus,
+    state: tauri::State<'_, Arc<AuthState>>,
+) -> Result<(), String> {
+    update_redemption(&state, &reward_id, &redemption_id, status).await
+}
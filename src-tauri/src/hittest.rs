@@ -0,0 +1,465 @@
+//! Click-through hit-testing for overlay windows.
+//!
+//! Overlay windows ignore the mouse so clicks reach the game underneath.
+//! The frontend registers the rectangles of interactive widgets (chat input,
+//! suggestion box) and this module polls the OS cursor, turning click-through
+//! off while the cursor rests over a region and back on when it leaves.
+//! Windows are told which region is active through [`HIT_REGION_EVENT`].
+//!
+//! Polling needs a global cursor position, which tao provides on Windows,
+//! macOS and X11. Wayland doesn't expose one, so there regions never activate.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::warn;
+
+/// Emitted to a window with a [`HitRegionEvent`] when a region activates or deactivates.
+pub const HIT_REGION_EVENT: &str = "hit-region";
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// An interactive rectangle in window-relative logical pixels.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HitRegion {
+    pub id: String,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    /// How long the cursor must rest inside before the region activates.
+    #[serde(default)]
+    pub hover_ms: u64,
+}
+
+impl HitRegion {
+    fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && x <= self.x + self.width && y >= self.y && y <= self.y + self.height
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HitRegionEvent {
+    pub region_id: String,
+    pub active: bool,
+}
+
+/// Hit-test state for one window.
+#[derive(Debug, Default)]
+struct WindowRegions {
+    regions: Vec<HitRegion>,
+    /// Regions that stay active after the cursor leaves, e.g. a focused input.
+    held: HashSet<String>,
+    /// Edit mode: the window is fully interactive and left alone.
+    paused: bool,
+    hovering: Option<(String, Instant)>,
+    active: Option<String>,
+    /// Click-through state last applied to the window, if known.
+    ignoring: Option<bool>,
+}
+
+impl WindowRegions {
+    /// Advance hover tracking with the cursor position (window-relative, or
+    /// `None` when unknown) and return activation changes.
+    fn update(&mut self, cursor: Option<(f64, f64)>, now: Instant) -> Vec<HitRegionEvent> {
+        let over = cursor.and_then(|(x, y)| {
+            self.regions
+                .iter()
+                .find(|r| r.contains(x, y))
+                .map(|r| r.id.clone())
+        });
+        if over.as_ref() != self.hovering.as_ref().map(|(id, _)| id) {
+            self.hovering = over.map(|id| (id, now));
+        }
+
+        let due = self.hovering.as_ref().and_then(|(id, since)| {
+            let region = self.regions.iter().find(|r| &r.id == id)?;
+            (now.duration_since(*since) >= Duration::from_millis(region.hover_ms))
+                .then(|| id.clone())
+        });
+        let next = due.or_else(|| {
+            self.active
+                .clone()
+                .filter(|id| self.held.contains(id) && self.regions.iter().any(|r| &r.id == id))
+        });
+
+        let mut events = Vec::new();
+        if next != self.active {
+            if let Some(old) = self.active.take() {
+                events.push(HitRegionEvent {
+                    region_id: old,
+                    active: false,
+                });
+            }
+            if let Some(new) = &next {
+                events.push(HitRegionEvent {
+                    region_id: new.clone(),
+                    active: true,
+                });
+            }
+            self.active = next;
+        }
+        events
+    }
+
+    fn wants_ignore(&self) -> bool {
+        self.active.is_none()
+    }
+}
+
+/// Changes a poll decided for one window, applied outside the state lock.
+#[derive(Debug, Clone)]
+struct Pending {
+    label: String,
+    events: Vec<HitRegionEvent>,
+    /// Click-through state to set, if it changed.
+    ignore: Option<bool>,
+}
+
+/// Registered regions, keyed by window label.
+#[derive(Default)]
+pub struct HitTestState {
+    windows: Mutex<HashMap<String, WindowRegions>>,
+}
+
+impl HitTestState {
+    fn with_window<T>(
+        &self,
+        label: &str,
+        f: impl FnOnce(&mut WindowRegions) -> T,
+    ) -> Result<T, String> {
+        let mut windows = self.windows.lock().map_err(|e| e.to_string())?;
+        Ok(f(windows.entry(label.to_string()).or_default()))
+    }
+
+    /// Advance hover tracking for every window. `exists`, `cursor` and
+    /// `apply` talk to the windows and run without the lock held: window
+    /// calls wait on the main thread, which may be waiting on the lock in a
+    /// command. `apply` returns whether the click-through change took.
+    fn poll(
+        &self,
+        now: Instant,
+        exists: impl Fn(&str) -> bool,
+        mut cursor: impl FnMut(&str) -> Option<(f64, f64)>,
+        mut apply: impl FnMut(&Pending) -> bool,
+    ) -> Result<(), String> {
+        let labels: Vec<(String, bool)> = {
+            let windows = self.windows.lock().map_err(|e| e.to_string())?;
+            windows
+                .iter()
+                .map(|(label, w)| {
+                    let polled = !w.paused && (!w.regions.is_empty() || w.active.is_some());
+                    (label.clone(), polled)
+                })
+                .collect()
+        };
+
+        let mut gone = Vec::new();
+        let mut cursors = Vec::new();
+        for (label, polled) in labels {
+            if !exists(&label) {
+                gone.push(label);
+            } else if polled {
+                let pos = cursor(&label);
+                cursors.push((label, pos));
+            }
+        }
+
+        let pending: Vec<Pending> = {
+            let mut windows = self.windows.lock().map_err(|e| e.to_string())?;
+            for label in &gone {
+                windows.remove(label);
+            }
+            cursors
+                .into_iter()
+                .filter_map(|(label, pos)| {
+                    // Paused or removed while the lock was released
+                    let w = windows.get_mut(&label).filter(|w| !w.paused)?;
+                    let events = w.update(pos, now);
+                    let ignore = Some(w.wants_ignore()).filter(|i| w.ignoring != Some(*i));
+                    (!events.is_empty() || ignore.is_some()).then_some(Pending {
+                        label,
+                        events,
+                        ignore,
+                    })
+                })
+                .collect()
+        };
+
+        for change in pending {
+            let applied = apply(&change);
+            let Some(ignore) = change.ignore.filter(|_| applied) else {
+                continue;
+            };
+            let mut windows = self.windows.lock().map_err(|e| e.to_string())?;
+            if let Some(w) = windows.get_mut(&change.label).filter(|w| !w.paused) {
+                w.ignoring = Some(ignore);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Cursor position relative to `window`, in logical pixels.
+fn window_cursor(window: &tauri::WebviewWindow) -> Result<(f64, f64), String> {
+    let cursor = window.cursor_position().map_err(|e| e.to_string())?;
+    let origin = window.inner_position().map_err(|e| e.to_string())?;
+    let scale = window.scale_factor().map_err(|e| e.to_string())?;
+    Ok((
+        (cursor.x - origin.x as f64) / scale,
+        (cursor.y - origin.y as f64) / scale,
+    ))
+}
+
+fn tick(app: &AppHandle, warned: &mut bool) {
+    let state = app.state::<HitTestState>();
+    let polled = state.poll(
+        Instant::now(),
+        |label| app.get_webview_window(label).is_some(),
+        |label| {
+            let window = app.get_webview_window(label)?;
+            match window_cursor(&window) {
+                Ok(pos) => Some(pos),
+                Err(e) => {
+                    if !*warned {
+                        warn!("[hittest] cursor position unavailable: {e}");
+                        *warned = true;
+                    }
+                    None
+                }
+            }
+        },
+        |change| {
+            let label = change.label.as_str();
+            for event in &change.events {
+                if let Err(e) = app.emit_to(label, HIT_REGION_EVENT, event) {
+                    warn!("[hittest] failed to emit to '{label}': {e}");
+                }
+            }
+            let (Some(ignore), Some(window)) = (change.ignore, app.get_webview_window(label))
+            else {
+                return false;
+            };
+            match window.set_ignore_cursor_events(ignore) {
+                Ok(()) => true,
+                Err(e) => {
+                    warn!("[hittest] failed to set click-through on '{label}': {e}");
+                    false
+                }
+            }
+        },
+    );
+    // Only fails on a poisoned lock, which every later poll would report too
+    polled.ok();
+}
+
+/// Start polling the cursor.
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut warned = false;
+        loop {
+            tick(&app, &mut warned);
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+}
+
+/// Cursor position relative to the calling window, in logical pixels.
+///
+/// Not available on Wayland, which has no global cursor position.
+#[tauri::command]
+pub fn get_cursor_position(window: tauri::WebviewWindow) -> Result<(f64, f64), String> {
+    window_cursor(&window)
+}
+
+/// Add or move an interactive region of the calling window.
+#[tauri::command]
+pub fn set_hit_region(
+    region: HitRegion,
+    window: tauri::WebviewWindow,
+    state: tauri::State<'_, HitTestState>,
+) -> Result<(), String> {
+    state.with_window(window.label(), |w| {
+        match w.regions.iter_mut().find(|r| r.id == region.id) {
+            Some(existing) => *existing = region,
+            None => w.regions.push(region),
+        }
+    })
+}
+
+/// Remove a region. It deactivates on the next poll if it was active.
+#[tauri::command]
+pub fn remove_hit_region(
+    id: String,
+    window: tauri::WebviewWindow,
+    state: tauri::State<'_, HitTestState>,
+) -> Result<(), String> {
+    state.with_window(window.label(), |w| {
+        w.regions.retain(|r| r.id != id);
+        w.held.remove(&id);
+    })
+}
+
+/// Keep an active region active after the cursor leaves, e.g. while its
+/// input has focus.
+#[tauri::command]
+pub fn hold_hit_region(
+    id: String,
+    held: bool,
+    window: tauri::WebviewWindow,
+    state: tauri::State<'_, HitTestState>,
+) -> Result<(), String> {
+    state.with_window(window.label(), |w| {
+        if held {
+            w.held.insert(id);
+        } else {
+            w.held.remove(&id);
+        }
+    })
+}
+
+/// Stop or resume hit-testing for the calling window. Paused windows keep
+/// whatever click-through state the frontend sets (edit mode).
+#[tauri::command]
+pub fn set_hit_test_paused(
+    paused: bool,
+    window: tauri::WebviewWindow,
+    state: tauri::State<'_, HitTestState>,
+) -> Result<(), String> {
+    state.with_window(window.label(), |w| {
+        w.paused = paused;
+        w.hovering = None;
+        w.active = None;
+        w.ignoring = None;
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(id: &str, hover_ms: u64) -> HitRegion {
+        HitRegion {
+            id: id.into(),
+            x: 10.0,
+            y: 10.0,
+            width: 100.0,
+            height: 50.0,
+            hover_ms,
+        }
+    }
+
+    #[test]
+    fn activates_after_hover_delay_and_releases_on_leave() {
+        let mut w = WindowRegions {
+            regions: vec![region("chat", 500)],
+            ..Default::default()
+        };
+        let t0 = Instant::now();
+        assert!(w.update(Some((50.0, 20.0)), t0).is_empty());
+        assert!(w.wants_ignore());
+
+        let events = w.update(Some((50.0, 20.0)), t0 + Duration::from_millis(500));
+        assert_eq!(events[0].region_id, "chat");
+        assert!(events[0].active);
+        assert!(!w.wants_ignore());
+
+        let events = w.update(Some((500.0, 500.0)), t0 + Duration::from_millis(600));
+        assert!(!events[0].active);
+        assert!(w.wants_ignore());
+    }
+
+    #[test]
+    fn held_region_survives_cursor_leaving() {
+        let mut w = WindowRegions {
+            regions: vec![region("chat", 0)],
+            ..Default::default()
+        };
+        let t0 = Instant::now();
+        w.update(Some((50.0, 20.0)), t0);
+        w.held.insert("chat".into());
+        assert!(w.update(None, t0).is_empty());
+        assert!(!w.wants_ignore());
+
+        w.held.clear();
+        let events = w.update(None, t0);
+        assert!(!events[0].active);
+    }
+
+    #[test]
+    fn commands_run_while_a_poll_waits_on_a_window() {
+        let state = std::sync::Arc::new(HitTestState::default());
+        state
+            .with_window("main", |w| w.regions.push(region("chat", 0)))
+            .unwrap();
+        // What a command does, from another thread as the main thread would
+        let command = || {
+            let state = state.clone();
+            let (tx, rx) = std::sync::mpsc::channel();
+            std::thread::spawn(move || {
+                tx.send(state.with_window("main", |w| w.held.insert("chat".into())))
+                    .ok();
+            });
+            rx.recv_timeout(Duration::from_secs(5)).is_ok()
+        };
+
+        let mut applied = Vec::new();
+        state
+            .poll(
+                Instant::now(),
+                |_| true,
+                |_| {
+                    assert!(command(), "command blocked while reading the cursor");
+                    Some((50.0, 20.0))
+                },
+                |change| {
+                    assert!(command(), "command blocked while applying changes");
+                    applied.push(change.clone());
+                    true
+                },
+            )
+            .unwrap();
+        assert_eq!(applied.len(), 1);
+        assert!(applied[0].events[0].active);
+        assert_eq!(applied[0].ignore, Some(false));
+        let ignoring = state.with_window("main", |w| w.ignoring).unwrap();
+        assert_eq!(ignoring, Some(false));
+
+        // Moving between regions changes activation but not click-through
+        state
+            .with_window("main", |w| {
+                w.held.clear();
+                w.regions.push(HitRegion {
+                    id: "box".into(),
+                    y: 100.0,
+                    ..region("box", 0)
+                });
+            })
+            .unwrap();
+        let mut changes = Vec::new();
+        state
+            .poll(
+                Instant::now(),
+                |_| true,
+                |_| Some((50.0, 120.0)),
+                |change| {
+                    changes.push(change.clone());
+                    true
+                },
+            )
+            .unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].events.len(), 2);
+        assert_eq!(changes[0].ignore, None);
+
+        state
+            .poll(Instant::now(), |_| false, |_| None, |_| true)
+            .unwrap();
+        assert!(state.windows.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,343 @@
+//! Global hotkeys bound to overlay actions.
+//!
+//! Bindings live under `hotkeys` in settings and are registered with the OS
+//! here rather than in the frontend. Actions the backend can carry out itself
+//! (loading a preset) run directly; the rest are sent to the frontend as
+//! [`HOTKEY_ACTION_EVENT`].
+
+use crate::clips;
+use crate::markers;
+use crate::scenes::{self, SceneState};
+use crate::settings::{HotkeyAction, HotkeyBinding, Settings, SettingsState};
+use crate::watcher::SETTINGS_CHANGED_EVENT;
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Listener, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use tracing::{info, warn};
+
+/// Emitted with the [`HotkeyAction`] when a frontend-handled hotkey fires.
+pub const HOTKEY_ACTION_EVENT: &str = "hotkey-action";
+
+/// Emitted with `Vec<HotkeyStatus>` whenever bindings are registered.
+pub const HOTKEY_STATUS_EVENT: &str = "hotkey-status";
+
+/// Bindings used until the user saves their own.
+pub fn default_bindings() -> Vec<HotkeyBinding> {
+    vec![
+        HotkeyBinding {
+            id: "toggle-edit-mode".into(),
+            shortcut: "Ctrl+Shift+I".into(),
+            action: HotkeyAction::ToggleEditMode,
+            enabled: true,
+        },
+        HotkeyBinding {
+            id: "toggle-overlay".into(),
+            shortcut: "Ctrl+Shift+O".into(),
+            action: HotkeyAction::ToggleOverlay,
+            enabled: true,
+        },
+    ]
+}
+
+/// Outcome of registering one binding.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum Registration {
+    Registered,
+    Disabled,
+    /// The shortcut string doesn't parse.
+    Invalid {
+        message: String,
+    },
+    /// Another binding, or a scene rule, uses the same keys.
+    Conflict {
+        with: String,
+    },
+    /// The OS refused it, usually because another app owns the keys.
+    Failed {
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HotkeyStatus {
+    pub id: String,
+    pub shortcut: String,
+    #[serde(flatten)]
+    pub registration: Registration,
+}
+
+/// Shortcuts registered for bindings, and the last registration report.
+#[derive(Default)]
+pub struct HotkeyRegistry {
+    registered: Mutex<Vec<Shortcut>>,
+    status: Mutex<Vec<HotkeyStatus>>,
+    /// Bindings the shortcuts were registered from.
+    bindings: Mutex<Vec<HotkeyBinding>>,
+}
+
+/// Parse each binding and find conflicts, before touching the OS. Bindings
+/// that pass come back with their parsed shortcut.
+fn check(
+    bindings: &[HotkeyBinding],
+    scene_keys: &[String],
+) -> Vec<(HotkeyBinding, Result<Shortcut, Registration>)> {
+    let scene_ids: Vec<u32> = scene_keys
+        .iter()
+        .filter_map(|k| k.parse::<Shortcut>().ok())
+        .map(|s| s.id())
+        .collect();
+    let mut seen: Vec<(u32, String)> = Vec::new();
+    bindings
+        .iter()
+        .map(|binding| {
+            let result = if !binding.enabled {
+                Err(Registration::Disabled)
+            } else {
+                match binding.shortcut.parse::<Shortcut>() {
+                    Err(e) => Err(Registration::Invalid {
+                        message: e.to_string(),
+                    }),
+                    Ok(shortcut) => {
+                        if let Some((_, other)) = seen.iter().find(|(id, _)| *id == shortcut.id()) {
+                            Err(Registration::Conflict {
+                                with: other.clone(),
+                            })
+                        } else if scene_ids.contains(&shortcut.id()) {
+                            Err(Registration::Conflict {
+                                with: "a scene rule".into(),
+                            })
+                        } else {
+                            seen.push((shortcut.id(), binding.id.clone()));
+                            Ok(shortcut)
+                        }
+                    }
+                }
+            };
+            (binding.clone(), result)
+        })
+        .collect()
+}
+
+/// Carry out a hotkey action.
+pub fn run_action(app: &AppHandle, action: &HotkeyAction) {
+    let result = match action {
+        HotkeyAction::LoadPreset { name } => scenes::switch_to(app, name, "hotkey"),
+        HotkeyAction::StreamMarker { description } => {
+            markers::spawn_create(app, description.clone().unwrap_or_default(), "hotkey");
+            Ok(())
+        }
+        HotkeyAction::CreateClip => {
+            clips::spawn_create(app, "hotkey");
+            Ok(())
+        }
+        _ => app
+            .emit(HOTKEY_ACTION_EVENT, action)
+            .map_err(|e| e.to_string()),
+    };
+    if let Err(e) = result {
+        warn!("[hotkeys] {action:?} failed: {e}");
+    }
+}
+
+/// The bindings in `settings`, or the defaults if none were ever saved.
+fn bindings_in(settings: Option<Settings>) -> Vec<HotkeyBinding> {
+    settings
+        .and_then(|s| s.hotkeys)
+        .unwrap_or_else(default_bindings)
+}
+
+/// Saved bindings, or the defaults if none were ever saved.
+fn bindings(app: &AppHandle) -> Vec<HotkeyBinding> {
+    bindings_in(app.state::<SettingsState>().load().ok().flatten())
+}
+
+/// Register the saved bindings, replacing any registered previously, and
+/// report the outcome per binding.
+pub fn register(app: &AppHandle) -> Vec<HotkeyStatus> {
+    let registry = app.state::<HotkeyRegistry>();
+    let shortcuts = app.global_shortcut();
+    if let Ok(mut registered) = registry.registered.lock() {
+        for shortcut in registered.drain(..) {
+            if let Err(e) = shortcuts.unregister(shortcut) {
+                warn!("[hotkeys] failed to unregister {shortcut}: {e}");
+            }
+        }
+    }
+
+    let bindings = bindings(app);
+    if let Ok(mut last) = registry.bindings.lock() {
+        *last = bindings.clone();
+    }
+    let scene_keys = app.state::<SceneState>().registered_hotkeys();
+    let mut report = Vec::new();
+    for (binding, checked) in check(&bindings, &scene_keys) {
+        let registration = match checked {
+            Err(registration) => registration,
+            Ok(shortcut) => {
+                let action = binding.action.clone();
+                let result = shortcuts.on_shortcut(shortcut, move |app, _, event| {
+                    if event.state == ShortcutState::Pressed {
+                        run_action(app, &action);
+                    }
+                });
+                match result {
+                    Ok(()) => {
+                        if let Ok(mut registered) = registry.registered.lock() {
+                            registered.push(shortcut);
+                        }
+                        Registration::Registered
+                    }
+                    Err(e) => Registration::Failed {
+                        message: e.to_string(),
+                    },
+                }
+            }
+        };
+        if !matches!(
+            registration,
+            Registration::Registered | Registration::Disabled
+        ) {
+            warn!(
+                "[hotkeys] {} ({}) not registered: {registration:?}",
+                binding.id, binding.shortcut
+            );
+        }
+        report.push(HotkeyStatus {
+            id: binding.id,
+            shortcut: binding.shortcut,
+            registration,
+        });
+    }
+
+    if let Ok(mut status) = registry.status.lock() {
+        *status = report.clone();
+    }
+    if let Err(e) = app.emit(HOTKEY_STATUS_EVENT, &report) {
+        warn!("[hotkeys] failed to emit status: {e}");
+    }
+    report
+}
+
+/// Re-register whenever the settings change with different bindings, e.g.
+/// after an external edit, a profile switch or a snapshot restore.
+pub fn listen_settings(app: &AppHandle) {
+    let handle = app.clone();
+    app.listen(SETTINGS_CHANGED_EVENT, move |event| {
+        let settings = serde_json::from_str(event.payload())
+            .and_then(|value| Settings::from_value(value).map_err(serde::de::Error::custom));
+        let settings = match settings {
+            Ok(settings) => settings,
+            Err(e) => {
+                warn!("[hotkeys] ignoring unreadable settings: {e}");
+                return;
+            }
+        };
+        let registry = handle.state::<HotkeyRegistry>();
+        let changed = registry
+            .bindings
+            .lock()
+            .map(|last| *last != bindings_in(Some(settings)))
+            .unwrap_or(true);
+        if changed {
+            info!("[hotkeys] bindings changed, registering again");
+            register(&handle);
+        }
+    });
+}
+
+/// Return the hotkey bindings (the defaults if none were saved).
+#[tauri::command]
+pub fn get_hotkeys(app: AppHandle) -> Vec<HotkeyBinding> {
+    bindings(&app)
+}
+
+/// Save and register hotkey bindings. Returns the outcome per binding.
+#[tauri::command]
+pub fn set_hotkeys(
+    bindings: Vec<HotkeyBinding>,
+    app: AppHandle,
+    state: tauri::State<'_, SettingsState>,
+) -> Result<Vec<HotkeyStatus>, String> {
+    let mut ids: Vec<&str> = bindings.iter().map(|b| b.id.as_str()).collect();
+    ids.sort_unstable();
+    if let Some(w) = ids.windows(2).find(|w| w[0] == w[1]) {
+        return Err(format!("Duplicate hotkey id '{}'", w[0]));
+    }
+
+    let ((), settings) = state.update(|s| {
+        s.hotkeys = Some(bindings);
+        Ok(())
+    })?;
+    // Register before announcing, so the settings listener finds nothing to do
+    let report = register(&app);
+    app.emit(SETTINGS_CHANGED_EVENT, &settings)
+        .map_err(|e| e.to_string())?;
+    info!("[hotkeys] saved {} bindings", report.len());
+    Ok(report)
+}
+
+/// The outcome of the last registration.
+#[tauri::command]
+pub fn hotkey_status(registry: tauri::State<'_, HotkeyRegistry>) -> Vec<HotkeyStatus> {
+    registry
+        .status
+        .lock()
+        .map(|s| s.clone())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binding(id: &str, shortcut: &str) -> HotkeyBinding {
+        HotkeyBinding {
+            id: id.into(),
+            shortcut: shortcut.into(),
+            action: HotkeyAction::ClearChat,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn reports_invalid_conflicting_and_disabled_bindings() {
+        let mut off = binding("off", "Ctrl+Shift+I");
+        off.enabled = false;
+        let bindings = [
+            binding("edit", "Ctrl+Shift+I"),
+            binding("dup", "shift+ctrl+i"),
+            binding("bad", "Ctrl+Nope"),
+            binding("scene", "Alt+F1"),
+            off,
+            binding("ok", "Ctrl+Shift+M"),
+        ];
+        let results: Vec<_> = check(&bindings, &["Alt+F1".into()])
+            .into_iter()
+            .map(|(b, r)| (b.id, r.err()))
+            .collect();
+
+        assert_eq!(results[0], ("edit".into(), None));
+        assert_eq!(
+            results[1].1,
+            Some(Registration::Conflict {
+                with: "edit".into()
+            })
+        );
+        assert!(matches!(results[2].1, Some(Registration::Invalid { .. })));
+        assert!(matches!(results[3].1, Some(Registration::Conflict { .. })));
+        assert_eq!(results[4].1, Some(Registration::Disabled));
+        assert_eq!(results[5], ("ok".into(), None));
+    }
+
+    #[test]
+    fn falls_back_to_default_bindings() {
+        assert_eq!(bindings_in(None), default_bindings());
+        let mut settings = Settings::default();
+        assert_eq!(bindings_in(Some(settings.clone())), default_bindings());
+        settings.hotkeys = Some(vec![binding("ok", "Ctrl+Shift+M")]);
+        assert_eq!(bindings_in(Some(settings))[0].id, "ok");
+    }
+}
@@ -3,47 +3,26 @@ use tauri::Manager;
 use tracing_subscriber::{fmt, layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter};
 
 mod auth;
+mod bundle;
+mod clips;
+mod control;
 mod event_log;
+mod events;
 mod helix;
+mod hittest;
+mod hotkeys;
+mod markers;
+mod monitors;
+mod persist;
 mod presets;
+mod replay;
+mod rewards;
+mod scenes;
 mod settings;
+mod share_code;
 mod suggestions;
-
-/// Information about a connected display monitor.
-#[derive(serde::Serialize)]
-struct MonitorInfo {
-    id: String,
-    name: String,
-    x: i32,
-    y: i32,
-    width: u32,
-    height: u32,
-}
-
-/// Return a list of all available monitors with their position and size.
-#[tauri::command]
-fn list_monitors(app: tauri::AppHandle) -> Result<Vec<MonitorInfo>, String> {
-    let monitors = app.available_monitors().map_err(|e| e.to_string())?;
-    Ok(monitors
-        .iter()
-        .enumerate()
-        .map(|(i, m)| {
-            let pos = m.position();
-            let size = m.size();
-            MonitorInfo {
-                id: format!("monitor-{i}"),
-                name: m
-                    .name()
-                    .unwrap_or(&format!("Monitor {}", i + 1))
-                    .to_string(),
-                x: pos.x,
-                y: pos.y,
-                width: size.width,
-                height: size.height,
-            }
-        })
-        .collect())
-}
+mod tray;
+mod watcher;
 
 #[tauri::command]
 fn set_ignore_cursor(window: tauri::WebviewWindow, ignore: bool) -> Result<(), String> {
@@ -52,34 +31,6 @@ fn set_ignore_cursor(window: tauri::WebviewWindow, ignore: bool) -> Result<(), S
         .map_err(|e| e.to_string())
 }
 
-#[tauri::command]
-#[allow(unused_variables)]
-fn get_cursor_position(window: tauri::WebviewWindow) -> Result<(f64, f64), String> {
-    #[cfg(target_os = "windows")]
-    {
-        #[repr(C)]
-        struct Point {
-            x: i32,
-            y: i32,
-        }
-        extern "system" {
-            fn GetCursorPos(lp_point: *mut Point) -> i32;
-        }
-        let mut point = Point { x: 0, y: 0 };
-        if unsafe { GetCursorPos(&mut point) } == 0 {
-            return Err("GetCursorPos failed".into());
-        }
-        let win_pos = window.outer_position().map_err(|e| e.to_string())?;
-        let scale = window.scale_factor().map_err(|e| e.to_string())?;
-        Ok((
-            (point.x - win_pos.x) as f64 / scale,
-            (point.y - win_pos.y) as f64 / scale,
-        ))
-    }
-    #[cfg(not(target_os = "windows"))]
-    Err("get_cursor_position is only supported on Windows".into())
-}
-
 #[tauri::command]
 #[allow(unused_variables)]
 fn write_default_layout(data: String) -> Result<(), String> {
@@ -168,9 +119,25 @@ pub fn run() {
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .plugin(tauri_plugin_dialog::init())
         .invoke_handler(tauri::generate_handler![
-            list_monitors,
+            monitors::list_monitors,
+            monitors::sync_overlay_windows,
+            monitors::list_overlay_windows,
+            monitors::close_overlay_windows,
+            monitors::get_monitor_fallback,
+            monitors::set_monitor_fallback,
             set_ignore_cursor,
-            get_cursor_position,
+            hittest::get_cursor_position,
+            hittest::set_hit_region,
+            hittest::remove_hit_region,
+            hittest::hold_hit_region,
+            hittest::set_hit_test_paused,
+            hotkeys::get_hotkeys,
+            hotkeys::set_hotkeys,
+            hotkeys::hotkey_status,
+            clips::create_clip,
+            clips::list_clips,
+            markers::create_stream_marker,
+            markers::list_stream_markers,
             log_frontend,
             auth::auth_device_start,
             auth::auth_device_poll,
@@ -180,10 +147,19 @@ pub fn run() {
             helix::helix_get,
             helix::helix_patch,
             helix::eventsub_subscribe,
+            helix::update_redemption_status,
             event_log::append_event_log,
             event_log::flush_event_log,
             settings::read_settings,
             settings::write_settings,
+            settings::list_settings_snapshots,
+            settings::diff_settings_snapshots,
+            settings::restore_settings_snapshot,
+            settings::list_profiles,
+            settings::create_profile,
+            settings::clone_profile,
+            settings::switch_profile,
+            settings::delete_profile,
             settings::read_chat_history,
             settings::write_chat_history,
             write_default_layout,
@@ -195,8 +171,35 @@ pub fn run() {
             presets::delete_preset,
             presets::export_preset,
             presets::import_preset,
+            presets::encode_preset_share,
+            presets::decode_preset_share,
+            presets::import_preset_share,
+            presets::merge_preset,
+            rewards::list_managed_rewards,
+            rewards::create_managed_reward,
+            rewards::update_managed_reward,
+            rewards::set_managed_reward_paused,
+            rewards::set_managed_reward_enabled,
+            rewards::delete_managed_reward,
+            control::get_control_api,
             suggestions::read_suggestions,
-            suggestions::write_suggestions,
+            suggestions::add_suggestion,
+            suggestions::vote_suggestion,
+            suggestions::unvote_suggestion,
+            suggestions::complete_suggestion,
+            suggestions::delete_suggestion,
+            suggestions::archive_suggestions,
+            suggestions::search_suggestions,
+            suggestions::export_suggestions,
+            suggestions::suggestion_event,
+            replay::replay_start,
+            replay::replay_stop,
+            replay::simulate_event,
+            scenes::get_scene_rules,
+            scenes::set_scene_rules,
+            scenes::switch_scene,
+            scenes::active_scene,
+            scenes::scene_event,
         ])
         .setup(|app| {
             let data_dir = dirs::home_dir()
@@ -257,34 +260,56 @@ pub fn run() {
             // Fit the window exactly to the primary monitor instead of using
             // maximized (which adds invisible borders on Windows).
             if let Some(monitor) = window.primary_monitor().map_err(|e| e.to_string())? {
-                let scale = monitor.scale_factor();
-                let phys_pos = monitor.position();
-                let phys_size = monitor.size();
-                window
-                    .set_position(tauri::Position::Logical(tauri::LogicalPosition::new(
-                        phys_pos.x as f64 / scale,
-                        phys_pos.y as f64 / scale,
-                    )))
-                    .map_err(|e| e.to_string())?;
-                window
-                    .set_size(tauri::Size::Logical(tauri::LogicalSize::new(
-                        phys_size.width as f64 / scale,
-                        phys_size.height as f64 / scale,
-                    )))
-                    .map_err(|e| e.to_string())?;
+                monitors::fit_to_monitor(&window, &monitor);
             }
+            monitors::watch_scale(app.handle(), &window);
 
             window.set_ignore_cursor_events(true)?;
 
             app.manage(Arc::new(auth::AuthState::new(data_dir.clone())));
             app.manage(settings::SettingsState::new(data_dir.clone()));
+            let selected = monitors::saved_selection(&app.state::<settings::SettingsState>());
+            app.manage(monitors::OverlayWindows::new(&data_dir, selected));
+            monitors::start(app.handle());
+            monitors::listen_settings(app.handle());
+            app.manage(hittest::HitTestState::default());
+            hittest::start(app.handle());
             app.manage(suggestions::SuggestionsState::new(data_dir.clone()));
 
             let log_dir = data_dir.join("logs");
             app.manage(event_log::EventLogState::new(log_dir));
+            app.manage(replay::ReplayState::default());
+
+            app.manage(scenes::SceneState::new(data_dir.clone()));
+            scenes::start(app.handle());
+            app.manage(hotkeys::HotkeyRegistry::default());
+            hotkeys::register(app.handle());
+            hotkeys::listen_settings(app.handle());
+
+            tray::start(app.handle())?;
+            if let Err(e) = control::start(app.handle(), &data_dir) {
+                tracing::warn!("[control] failed to start: {e}");
+            }
+
+            match watcher::DataWatcher::start(app.handle().clone(), &data_dir) {
+                Ok(data_watcher) => {
+                    app.manage(data_watcher);
+                }
+                Err(e) => tracing::warn!("[watcher] failed to start: {e}"),
+            }
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Buffered event log lines would otherwise be lost, e.g. on quit from the tray
+                if let Some(log) = app.try_state::<event_log::EventLogState>() {
+                    if let Err(e) = log.flush() {
+                        tracing::warn!("[event-log] failed to flush on exit: {e}");
+                    }
+                }
+            }
+        });
 }
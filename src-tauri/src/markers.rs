@@ -0,0 +1,216 @@
+//! Stream markers for highlighting moments in the VOD.
+//!
+//! Markers are created through Helix and recorded in the broadcaster's event
+//! log as `stream_marker` events, so they can be listed with their stream
+//! offsets after the stream; the same event is announced on [`CHANNEL_EVENT`].
+//! They can be created from a hotkey, the control API or with the `!marker`
+//! chat command by a moderator or the broadcaster.
+
+use crate::auth::AuthState;
+use crate::event_log::EventLogState;
+use crate::events::{ChannelEvent, ChannelEventType, CHANNEL_EVENT};
+use crate::helix::{broadcaster_id, helix_request};
+use crate::scenes;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+/// Chat command that creates a marker; the rest of the message is its description.
+pub const MARKER_COMMAND: &str = "!marker";
+
+/// Helix rejects descriptions longer than this.
+const MAX_DESCRIPTION_CHARS: usize = 140;
+
+/// Minimum time between markers requested with [`MARKER_COMMAND`].
+const CHAT_COOLDOWN: Duration = Duration::from_secs(5);
+
+/// Ignores [`MARKER_COMMAND`] while a marker is being created or cooling down.
+static CHAT_GATE: scenes::CommandGate = scenes::CommandGate::new(CHAT_COOLDOWN);
+
+/// A marker as returned by Helix and recorded in the event log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamMarker {
+    pub id: String,
+    /// Offset into the stream, in seconds.
+    pub position_seconds: u64,
+    #[serde(default)]
+    pub description: String,
+    /// Unix timestamp in milliseconds. Only set for markers read from the log.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct HelixMarker {
+    id: String,
+    position_seconds: u64,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Deserialize)]
+struct MarkersResponse {
+    data: Vec<HelixMarker>,
+}
+
+fn parse_marker(body: &str) -> Result<StreamMarker, String> {
+    let resp: MarkersResponse = serde_json::from_str(body).map_err(|e| e.to_string())?;
+    let marker = resp
+        .data
+        .into_iter()
+        .next()
+        .ok_or("Twitch returned no marker")?;
+    Ok(StreamMarker {
+        id: marker.id,
+        position_seconds: marker.position_seconds,
+        description: marker.description,
+        timestamp: None,
+    })
+}
+
+fn to_event(marker: &StreamMarker, source: &str) -> Result<ChannelEvent, String> {
+    let Value::Object(mut data) = serde_json::to_value(marker).map_err(|e| e.to_string())? else {
+        return Err("marker is not an object".into());
+    };
+    data.insert("source".into(), json!(source));
+    Ok(ChannelEvent {
+        kind: ChannelEventType::StreamMarker,
+        timestamp: chrono::Utc::now().timestamp_millis(),
+        data,
+    })
+}
+
+fn from_event(event: ChannelEvent) -> Option<StreamMarker> {
+    if event.kind != ChannelEventType::StreamMarker {
+        return None;
+    }
+    let marker: StreamMarker = serde_json::from_value(Value::Object(event.data)).ok()?;
+    Some(StreamMarker {
+        timestamp: Some(event.timestamp),
+        ..marker
+    })
+}
+
+/// Create a marker at the current point of the live stream and log it.
+/// `source` records what triggered it (hotkey, chat, api, manual).
+pub async fn create(
+    app: &AppHandle,
+    description: &str,
+    source: &str,
+) -> Result<StreamMarker, String> {
+    let auth = app.state::<Arc<AuthState>>();
+    let user_id = broadcaster_id(&auth).await?;
+    let mut body = Map::new();
+    body.insert("user_id".into(), json!(user_id));
+    let description: String = description.chars().take(MAX_DESCRIPTION_CHARS).collect();
+    if !description.is_empty() {
+        body.insert("description".into(), json!(description));
+    }
+
+    let resp = helix_request(
+        &auth,
+        Method::POST,
+        "/streams/markers",
+        Some(&Value::Object(body)),
+    )
+    .await?;
+    let marker = parse_marker(&resp)?;
+    info!(
+        "[markers] created marker at {}s ({source})",
+        marker.position_seconds
+    );
+
+    let channel = auth
+        .stored_username()
+        .ok_or("Unknown broadcaster; please log in again")?;
+    let event = to_event(&marker, source)?;
+    if let Err(e) = app.state::<EventLogState>().append(&channel, &event) {
+        warn!("[markers] failed to record marker: {e}");
+    }
+    if let Err(e) = app.emit(CHANNEL_EVENT, &event) {
+        warn!("[markers] failed to emit marker: {e}");
+    }
+    Ok(marker)
+}
+
+/// Create a marker without waiting for the result, logging failures.
+pub fn spawn_create(app: &AppHandle, description: String, source: &'static str) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = create(&app, &description, source).await {
+            warn!("[markers] failed to create marker ({source}): {e}");
+        }
+    });
+}
+
+/// Handle a chat message from a moderator or the broadcaster. Returns whether
+/// it was a marker command. Repeats are ignored while a marker is being
+/// created and for [`CHAT_COOLDOWN`] after one was requested.
+pub fn chat_command(app: &AppHandle, text: &str) -> bool {
+    let Some(description) = scenes::command_args(text, MARKER_COMMAND) else {
+        return false;
+    };
+    if !CHAT_GATE.try_start(Instant::now()) {
+        info!("[markers] ignoring {MARKER_COMMAND}: a marker was requested moments ago");
+        return true;
+    }
+    let app = app.clone();
+    let description = description.to_string();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = create(&app, &description, "chat").await {
+            warn!("[markers] failed to create marker (chat): {e}");
+        }
+        CHAT_GATE.finish();
+    });
+    true
+}
+
+/// Create a stream marker. The stream must be live.
+#[tauri::command]
+pub async fn create_stream_marker(
+    description: Option<String>,
+    app: AppHandle,
+) -> Result<StreamMarker, String> {
+    create(&app, description.as_deref().unwrap_or(""), "manual").await
+}
+
+/// Markers recorded in the logged-in broadcaster's event log, oldest first.
+#[tauri::command]
+pub fn list_stream_markers(
+    auth: tauri::State<'_, Arc<AuthState>>,
+    log: tauri::State<'_, EventLogState>,
+) -> Result<Vec<StreamMarker>, String> {
+    let Some(channel) = auth.stored_username() else {
+        return Ok(Vec::new());
+    };
+    Ok(log
+        .read(&channel)?
+        .into_iter()
+        .filter_map(from_event)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marker_round_trips_through_event_log() {
+        let marker = parse_marker(
+            r#"{"data":[{"id":"123","created_at":"2024-01-01T00:00:00Z","description":"clutch","position_seconds":244}]}"#,
+        )
+        .unwrap();
+        let event = to_event(&marker, "hotkey").unwrap();
+        let line = event.to_log_line().unwrap();
+        let read = from_event(ChannelEvent::from_log_line(&line).unwrap()).unwrap();
+        assert_eq!(read.id, "123");
+        assert_eq!(read.position_seconds, 244);
+        assert_eq!(read.description, "clutch");
+        assert_eq!(read.timestamp, Some(event.timestamp));
+    }
+}
@@ -0,0 +1,768 @@
+//! Monitor enumeration and the per-monitor overlay windows.
+//!
+//! The main window always covers the primary monitor. Each additional
+//! monitor selected in settings (`overlay.selectedMonitors`) gets a
+//! display-only `overlay-{id}` window. Windows are re-fitted when their scale
+//! factor changes and when monitors are plugged, unplugged or rearranged.
+//!
+//! Monitor ids are stable across reboots and reconnects. Every monitor seen is
+//! remembered in `monitors.json` with its name, resolution and position, and a
+//! connected monitor takes the id of the best-matching remembered one, so a
+//! monitor keeps its id when moved, re-ordered or switched to another mode.
+
+use crate::persist;
+use crate::settings::{Settings, SettingsState};
+use crate::watcher::SETTINGS_CHANGED_EVENT;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{
+    AppHandle, Emitter, Listener, LogicalPosition, LogicalSize, Manager, Monitor, Position, Size,
+    WebviewUrl, WebviewWindow, WebviewWindowBuilder, WindowEvent,
+};
+use tracing::{info, warn};
+
+/// Emitted with the current `Vec<MonitorInfo>` when the monitor layout changes.
+pub const MONITORS_CHANGED_EVENT: &str = "monitors-changed";
+
+/// Emitted with a [`MissingMonitor`] when a selected monitor is not connected.
+pub const MONITOR_MISSING_EVENT: &str = "monitor-missing";
+
+const WINDOW_PREFIX: &str = "overlay-";
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Lowest [`match_score`] accepted as the same monitor: the name alone, or
+/// both resolution and position.
+const MIN_MATCH_SCORE: u32 = 3;
+
+/// Information about a connected display monitor.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorInfo {
+    /// Stable id that survives reconnects, re-ordering and mode changes.
+    pub id: String,
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f64,
+    pub primary: bool,
+}
+
+/// Lowercase `name` to the characters allowed in a window label.
+fn slug(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            out.push(c.to_ascii_lowercase());
+        } else if !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    out.trim_matches('-').to_string()
+}
+
+/// A monitor seen before, as remembered in `monitors.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KnownMonitor {
+    id: String,
+    name: String,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+impl KnownMonitor {
+    fn from_info(m: &MonitorInfo) -> Self {
+        Self {
+            id: m.id.clone(),
+            name: m.name.clone(),
+            x: m.x,
+            y: m.y,
+            width: m.width,
+            height: m.height,
+        }
+    }
+}
+
+/// What to do when a selected monitor is not connected.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MonitorFallback {
+    /// Leave it without a window until it comes back.
+    #[default]
+    Wait,
+    /// Show its overlay on a connected secondary monitor that isn't selected.
+    NextAvailable,
+}
+
+/// Contents of `monitors.json`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MonitorMap {
+    #[serde(default)]
+    fallback: MonitorFallback,
+    #[serde(default)]
+    known: Vec<KnownMonitor>,
+}
+
+/// Payload of [`MONITOR_MISSING_EVENT`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingMonitor {
+    pub id: String,
+    /// Name it had when last connected.
+    pub name: Option<String>,
+    /// Monitor showing its overlay instead, under [`MonitorFallback::NextAvailable`].
+    pub fallback: Option<String>,
+}
+
+/// How alike a remembered and a connected monitor are. The name weighs
+/// most, then resolution, then position.
+fn match_score(known: &KnownMonitor, m: &MonitorInfo) -> u32 {
+    let mut score = 0;
+    if !known.name.is_empty() && known.name == m.name {
+        score += 4;
+    }
+    if (known.width, known.height) == (m.width, m.height) {
+        score += 2;
+    }
+    if (known.x, known.y) == (m.x, m.y) {
+        score += 1;
+    }
+    score
+}
+
+/// Id for a monitor seen for the first time.
+fn fingerprint(m: &MonitorInfo) -> String {
+    let name = match slug(&m.name) {
+        s if s.is_empty() => "display".to_string(),
+        s => s,
+    };
+    format!("{name}-{}x{}", m.width, m.height)
+}
+
+/// Give each connected monitor the id of its best match in `known`, pairing
+/// the closest matches first. Unmatched monitors get a new id and are
+/// remembered; matched ones update their remembered geometry. Returns whether
+/// `known` changed.
+fn identify(known: &mut Vec<KnownMonitor>, list: &mut [MonitorInfo]) -> bool {
+    let mut pairs = Vec::new();
+    for (k, km) in known.iter().enumerate() {
+        for (i, m) in list.iter().enumerate() {
+            let score = match_score(km, m);
+            if score >= MIN_MATCH_SCORE {
+                pairs.push((score, k, i));
+            }
+        }
+    }
+    pairs.sort_by_key(|&(score, ..)| std::cmp::Reverse(score));
+    let mut used_known = vec![false; known.len()];
+    let mut matched = vec![false; list.len()];
+    for (_, k, i) in pairs {
+        if used_known[k] || matched[i] {
+            continue;
+        }
+        used_known[k] = true;
+        matched[i] = true;
+        list[i].id = known[k].id.clone();
+    }
+
+    let mut changed = false;
+    // New monitors in position order, so ids don't depend on enumeration
+    let mut fresh: Vec<usize> = (0..list.len()).filter(|&i| !matched[i]).collect();
+    fresh.sort_by_key(|&i| (list[i].x, list[i].y));
+    for i in fresh {
+        let base = fingerprint(&list[i]);
+        let mut id = base.clone();
+        let mut n = 2;
+        while known.iter().any(|k| k.id == id) {
+            id = format!("{base}-{n}");
+            n += 1;
+        }
+        list[i].id = id;
+        known.push(KnownMonitor::from_info(&list[i]));
+        changed = true;
+    }
+
+    for m in list.iter() {
+        let current = KnownMonitor::from_info(m);
+        if let Some(k) = known.iter_mut().find(|k| k.id == m.id) {
+            if *k != current {
+                *k = current;
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+/// Map saved selection ids to current ones. Handles ids from older versions:
+/// enumeration indexes (`monitor-{i}`) and bare name slugs. Unknown ids are
+/// kept so they can be reported missing.
+fn resolve_saved(selected: &[String], list: &[MonitorInfo], known: &[KnownMonitor]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for id in selected {
+        let current = if list.iter().any(|m| &m.id == id) || known.iter().any(|k| &k.id == id) {
+            None
+        } else if let Some(i) = id
+            .strip_prefix("monitor-")
+            .and_then(|i| i.parse::<usize>().ok())
+        {
+            list.get(i).map(|m| m.id.clone())
+        } else {
+            list.iter()
+                .find(|m| &slug(&m.name) == id)
+                .map(|m| m.id.clone())
+        };
+        let id = current.unwrap_or_else(|| id.clone());
+        if !out.contains(&id) {
+            out.push(id);
+        }
+    }
+    out
+}
+
+/// Enumerate connected monitors.
+pub fn monitors(app: &AppHandle) -> Result<Vec<MonitorInfo>, String> {
+    let primary = app
+        .primary_monitor()
+        .map_err(|e| e.to_string())?
+        .map(|m| *m.position());
+    let mut list: Vec<MonitorInfo> = app
+        .available_monitors()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|m| {
+            let pos = m.position();
+            let size = m.size();
+            MonitorInfo {
+                id: String::new(),
+                name: m.name().cloned().unwrap_or_default(),
+                x: pos.x,
+                y: pos.y,
+                width: size.width,
+                height: size.height,
+                scale_factor: m.scale_factor(),
+                primary: primary == Some(*pos),
+            }
+        })
+        .collect();
+    app.state::<OverlayWindows>().identify(&mut list);
+    for (i, m) in list.iter_mut().enumerate() {
+        if m.name.is_empty() {
+            m.name = format!("Monitor {}", i + 1);
+        }
+    }
+    Ok(list)
+}
+
+/// Place `window` exactly over a monitor. Used instead of maximising, which
+/// adds invisible borders on Windows.
+fn fit(window: &WebviewWindow, x: i32, y: i32, width: u32, height: u32, scale: f64) {
+    let result = window
+        .set_position(Position::Logical(LogicalPosition::new(
+            x as f64 / scale,
+            y as f64 / scale,
+        )))
+        .and_then(|_| {
+            window.set_size(Size::Logical(LogicalSize::new(
+                width as f64 / scale,
+                height as f64 / scale,
+            )))
+        });
+    if let Err(e) = result {
+        warn!("[monitors] failed to fit '{}': {e}", window.label());
+    }
+}
+
+/// Fit `window` to `monitor`.
+pub fn fit_to_monitor(window: &WebviewWindow, monitor: &Monitor) {
+    let pos = monitor.position();
+    let size = monitor.size();
+    fit(
+        window,
+        pos.x,
+        pos.y,
+        size.width,
+        size.height,
+        monitor.scale_factor(),
+    );
+}
+
+fn fit_to_info(window: &WebviewWindow, m: &MonitorInfo) {
+    fit(window, m.x, m.y, m.width, m.height, m.scale_factor);
+}
+
+/// Monitor id an overlay window was opened for.
+fn window_monitor_id(window: &WebviewWindow) -> Option<String> {
+    window
+        .label()
+        .strip_prefix(WINDOW_PREFIX)
+        .map(str::to_string)
+}
+
+/// Remembered monitors, the user's selection and the last layout seen by
+/// the poller.
+pub struct OverlayWindows {
+    path: PathBuf,
+    map: Mutex<MonitorMap>,
+    selected: Mutex<Vec<String>>,
+    layout: Mutex<Vec<MonitorInfo>>,
+    /// Selected ids already reported missing, so each disconnect warns once.
+    missing: Mutex<Vec<String>>,
+}
+
+impl OverlayWindows {
+    pub fn new(data_dir: &Path, selected: Vec<String>) -> Self {
+        let path = data_dir.join("monitors.json");
+        let map = persist::read_json(&path)
+            .ok()
+            .flatten()
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            map: Mutex::new(map),
+            selected: Mutex::new(selected),
+            layout: Mutex::new(Vec::new()),
+            missing: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn selected(&self) -> Vec<String> {
+        self.selected.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    fn fallback(&self) -> MonitorFallback {
+        self.map.lock().map(|m| m.fallback).unwrap_or_default()
+    }
+
+    fn known_name(&self, id: &str) -> Option<String> {
+        let map = self.map.lock().ok()?;
+        let known = map.known.iter().find(|k| k.id == id)?;
+        (!known.name.is_empty()).then(|| known.name.clone())
+    }
+
+    /// Assign stable ids and persist anything newly learned.
+    fn identify(&self, list: &mut [MonitorInfo]) {
+        let Ok(mut map) = self.map.lock() else { return };
+        if identify(&mut map.known, list) {
+            if let Err(e) = persist::write_json(&self.path, &*map) {
+                warn!("[monitors] failed to save monitor map: {e}");
+            }
+        }
+    }
+}
+
+/// Refit a window to its monitor after a DPI or scale change.
+fn refit(app: &AppHandle, window: &WebviewWindow) {
+    let Ok(list) = monitors(app) else { return };
+    let monitor = match window_monitor_id(window) {
+        Some(id) => list.iter().find(|m| m.id == id),
+        None => list.iter().find(|m| m.primary),
+    };
+    if let Some(m) = monitor {
+        fit_to_info(window, m);
+    }
+}
+
+/// Re-fit windows on scale-factor changes.
+pub fn watch_scale(app: &AppHandle, window: &WebviewWindow) {
+    let app = app.clone();
+    let handle = window.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::ScaleFactorChanged { .. } = event {
+            refit(&app, &handle);
+        }
+    });
+}
+
+fn open_window(app: &AppHandle, monitor: &MonitorInfo) -> Result<(), String> {
+    let label = format!("{WINDOW_PREFIX}{}", monitor.id);
+    let url = format!("index.html?monitor=true&monitorId={}", monitor.id);
+    let builder = WebviewWindowBuilder::new(app, &label, WebviewUrl::App(url.into()))
+        .title(format!("Streamer – {}", monitor.name))
+        .decorations(false)
+        .shadow(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .resizable(false)
+        .focused(false);
+    #[cfg(not(target_os = "macos"))]
+    let builder = builder.transparent(true);
+    let window = builder.build().map_err(|e| e.to_string())?;
+    fit_to_info(&window, monitor);
+    window
+        .set_ignore_cursor_events(true)
+        .map_err(|e| e.to_string())?;
+    watch_scale(app, &window);
+    info!("[monitors] opened overlay on '{}'", monitor.id);
+    Ok(())
+}
+
+/// Bring overlay windows in line with the selection and the current layout:
+/// close windows for deselected or disconnected monitors, refit the rest and
+/// open missing ones. Returns the ids that have a window.
+fn reconcile(app: &AppHandle, list: &[MonitorInfo]) -> Vec<String> {
+    let state = app.state::<OverlayWindows>();
+    let (wanted, missing) = plan_windows(&state.selected(), list, state.fallback());
+    report_missing(app, &state, missing);
+
+    for (label, window) in app.webview_windows() {
+        let Some(id) = window_monitor_id(&window) else {
+            if label == "main" {
+                if let Some(primary) = list.iter().find(|m| m.primary) {
+                    fit_to_info(&window, primary);
+                }
+            }
+            continue;
+        };
+        match wanted.iter().find(|m| m.id == id) {
+            Some(m) => fit_to_info(&window, m),
+            None => {
+                if let Err(e) = window.destroy() {
+                    warn!("[monitors] failed to close '{label}': {e}");
+                }
+                info!("[monitors] closed overlay on '{id}'");
+            }
+        }
+    }
+
+    let mut open = Vec::new();
+    for m in wanted {
+        let label = format!("{WINDOW_PREFIX}{}", m.id);
+        if app.get_webview_window(&label).is_some() {
+            open.push(m.id.clone());
+            continue;
+        }
+        match open_window(app, m) {
+            Ok(()) => open.push(m.id.clone()),
+            Err(e) => warn!("[monitors] failed to open overlay on '{}': {e}", m.id),
+        }
+    }
+    open
+}
+
+/// Monitors that should have an overlay window, and the selected monitors
+/// that aren't connected.
+fn plan_windows<'a>(
+    selected: &[String],
+    list: &'a [MonitorInfo],
+    fallback: MonitorFallback,
+) -> (Vec<&'a MonitorInfo>, Vec<MissingMonitor>) {
+    let mut wanted: Vec<&MonitorInfo> = list
+        .iter()
+        .filter(|m| !m.primary && selected.contains(&m.id))
+        .collect();
+    let mut missing = Vec::new();
+    for id in selected {
+        if list.iter().any(|m| &m.id == id) {
+            continue;
+        }
+        let substitute = match fallback {
+            MonitorFallback::Wait => None,
+            MonitorFallback::NextAvailable => list
+                .iter()
+                .find(|m| !m.primary && !wanted.iter().any(|w| w.id == m.id)),
+        };
+        if let Some(m) = substitute {
+            wanted.push(m);
+        }
+        missing.push(MissingMonitor {
+            id: id.clone(),
+            name: None,
+            fallback: substitute.map(|m| m.id.clone()),
+        });
+    }
+    (wanted, missing)
+}
+
+/// Warn about monitors that have gone missing since the last check.
+fn report_missing(app: &AppHandle, state: &OverlayWindows, missing: Vec<MissingMonitor>) {
+    let Ok(mut reported) = state.missing.lock() else {
+        return;
+    };
+    let ids: Vec<String> = missing.iter().map(|m| m.id.clone()).collect();
+    for mut m in missing {
+        if reported.contains(&m.id) {
+            continue;
+        }
+        m.name = state.known_name(&m.id);
+        warn!(
+            "[monitors] selected monitor '{}' is not connected (fallback: {:?})",
+            m.id, m.fallback
+        );
+        if let Err(e) = app.emit(MONITOR_MISSING_EVENT, &m) {
+            warn!("[monitors] failed to emit missing monitor: {e}");
+        }
+    }
+    *reported = ids;
+}
+
+/// Rewrite the saved selection in terms of current ids, see [`resolve_saved`].
+fn migrate_selection(app: &AppHandle) -> Result<(), String> {
+    let list = monitors(app)?;
+    let state = app.state::<OverlayWindows>();
+    let selected = state.selected();
+    let resolved = {
+        let map = state.map.lock().map_err(|e| e.to_string())?;
+        resolve_saved(&selected, &list, &map.known)
+    };
+    if resolved == selected {
+        return Ok(());
+    }
+    *state.selected.lock().map_err(|e| e.to_string())? = resolved.clone();
+
+    let settings = app.state::<SettingsState>();
+    let _writing = settings.lock_writes()?;
+    if let Some(mut s) = settings.load()? {
+        s.overlay.get_or_insert_default().selected_monitors = Some(resolved.clone());
+        settings.save(&s)?;
+        app.emit(SETTINGS_CHANGED_EVENT, &s)
+            .map_err(|e| e.to_string())?;
+    }
+    info!("[monitors] resolved saved monitors {selected:?} to {resolved:?}");
+    Ok(())
+}
+
+/// Open overlays for the saved selection and watch for layout changes.
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = migrate_selection(&app) {
+            warn!("[monitors] failed to resolve saved monitors: {e}");
+        }
+        loop {
+            match monitors(&app) {
+                Ok(list) => {
+                    let state = app.state::<OverlayWindows>();
+                    let changed = state
+                        .layout
+                        .lock()
+                        .map(|mut last| {
+                            let changed = *last != list;
+                            *last = list.clone();
+                            changed
+                        })
+                        .unwrap_or(false);
+                    if changed {
+                        reconcile(&app, &list);
+                        if let Err(e) = app.emit(MONITORS_CHANGED_EVENT, &list) {
+                            warn!("[monitors] failed to emit change: {e}");
+                        }
+                    }
+                }
+                Err(e) => warn!("[monitors] failed to enumerate: {e}"),
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+}
+
+fn selection_in(settings: Option<Settings>) -> Vec<String> {
+    settings
+        .and_then(|s| s.overlay?.selected_monitors)
+        .unwrap_or_default()
+}
+
+/// Selected monitors from saved settings, for the initial [`OverlayWindows`].
+pub fn saved_selection(settings: &SettingsState) -> Vec<String> {
+    selection_in(settings.load().ok().flatten())
+}
+
+/// Follow selection changes made outside the monitor picker, e.g. a profile
+/// switch, a snapshot restore or an external edit.
+pub fn listen_settings(app: &AppHandle) {
+    let handle = app.clone();
+    app.listen(SETTINGS_CHANGED_EVENT, move |event| {
+        let settings = serde_json::from_str(event.payload())
+            .and_then(|value| Settings::from_value(value).map_err(serde::de::Error::custom));
+        let selected = match settings {
+            Ok(settings) => selection_in(Some(settings)),
+            Err(e) => {
+                warn!("[monitors] ignoring unreadable settings: {e}");
+                return;
+            }
+        };
+        let state = handle.state::<OverlayWindows>();
+        let Ok(mut current) = state.selected.lock() else {
+            return;
+        };
+        if *current == selected {
+            return;
+        }
+        info!("[monitors] selection changed to {selected:?}");
+        *current = selected;
+        drop(current);
+        // Windows are built off the emitting thread
+        let app = handle.clone();
+        tauri::async_runtime::spawn(async move {
+            match monitors(&app) {
+                Ok(list) => {
+                    reconcile(&app, &list);
+                }
+                Err(e) => warn!("[monitors] failed to enumerate: {e}"),
+            }
+        });
+    });
+}
+
+/// Return a list of all available monitors with their position and size.
+#[tauri::command]
+pub fn list_monitors(app: AppHandle) -> Result<Vec<MonitorInfo>, String> {
+    monitors(&app)
+}
+
+/// Set which monitors get an overlay window and open or close windows to
+/// match. Selected monitors that are disconnected keep their selection and
+/// get a window when they come back. Returns the ids that have a window.
+#[tauri::command]
+pub async fn sync_overlay_windows(
+    monitor_ids: Vec<String>,
+    app: AppHandle,
+    state: tauri::State<'_, OverlayWindows>,
+) -> Result<Vec<String>, String> {
+    *state.selected.lock().map_err(|e| e.to_string())? = monitor_ids;
+    let list = monitors(&app)?;
+    Ok(reconcile(&app, &list))
+}
+
+/// How overlays for disconnected monitors are handled.
+#[tauri::command]
+pub fn get_monitor_fallback(state: tauri::State<'_, OverlayWindows>) -> MonitorFallback {
+    state.fallback()
+}
+
+/// Change the fallback policy and apply it to the current layout.
+#[tauri::command]
+pub async fn set_monitor_fallback(
+    fallback: MonitorFallback,
+    app: AppHandle,
+    state: tauri::State<'_, OverlayWindows>,
+) -> Result<(), String> {
+    {
+        let mut map = state.map.lock().map_err(|e| e.to_string())?;
+        map.fallback = fallback;
+        persist::write_json(&state.path, &*map)?;
+    }
+    let list = monitors(&app)?;
+    reconcile(&app, &list);
+    info!("[monitors] fallback set to {fallback:?}");
+    Ok(())
+}
+
+/// Ids of monitors that currently have an overlay window.
+#[tauri::command]
+pub fn list_overlay_windows(app: AppHandle) -> Vec<String> {
+    app.webview_windows()
+        .values()
+        .filter_map(window_monitor_id)
+        .collect()
+}
+
+/// Close every overlay window and clear the selection.
+#[tauri::command]
+pub async fn close_overlay_windows(
+    app: AppHandle,
+    state: tauri::State<'_, OverlayWindows>,
+) -> Result<(), String> {
+    state.selected.lock().map_err(|e| e.to_string())?.clear();
+    let list = monitors(&app)?;
+    reconcile(&app, &list);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(name: &str, x: i32) -> MonitorInfo {
+        MonitorInfo {
+            id: String::new(),
+            name: name.into(),
+            x,
+            y: 0,
+            width: 1920,
+            height: 1080,
+            scale_factor: 1.0,
+            primary: x == 0,
+        }
+    }
+
+    fn ids(list: &[MonitorInfo]) -> Vec<&str> {
+        list.iter().map(|m| m.id.as_str()).collect()
+    }
+
+    #[test]
+    fn ids_survive_reordering_and_mode_changes() {
+        let mut known = Vec::new();
+        let mut list = vec![
+            monitor("DELL U2720Q", 1920),
+            monitor("\\\\.\\DISPLAY1", 0),
+            monitor("DELL U2720Q", -1920),
+            monitor("", 3840),
+        ];
+        assert!(identify(&mut known, &mut list));
+        assert_eq!(
+            ids(&list),
+            [
+                "dell-u2720q-1920x1080-2",
+                "display1-1920x1080",
+                "dell-u2720q-1920x1080",
+                "display-1920x1080"
+            ]
+        );
+
+        // Enumerated in another order, one DELL at a new resolution
+        let mut again = vec![
+            list[3].clone(),
+            list[2].clone(),
+            list[1].clone(),
+            list[0].clone(),
+        ];
+        again[1].width = 2560;
+        again[1].height = 1440;
+        assert!(identify(&mut known, &mut again));
+        assert_eq!(
+            ids(&again),
+            [
+                "display-1920x1080",
+                "dell-u2720q-1920x1080",
+                "display1-1920x1080",
+                "dell-u2720q-1920x1080-2"
+            ]
+        );
+        assert!(!identify(&mut known, &mut again));
+    }
+
+    #[test]
+    fn resolves_legacy_ids_and_plans_fallback() {
+        let mut known = Vec::new();
+        let mut list = vec![monitor("Main", 0), monitor("Side", 1920)];
+        identify(&mut known, &mut list);
+
+        let saved = [
+            "monitor-1".to_string(),
+            "side".to_string(),
+            "gone".to_string(),
+        ];
+        assert_eq!(
+            resolve_saved(&saved, &list, &known),
+            ["side-1920x1080", "gone"]
+        );
+
+        let selected = ["gone".to_string()];
+        let (wanted, missing) = plan_windows(&selected, &list, MonitorFallback::Wait);
+        assert!(wanted.is_empty());
+        assert_eq!(missing[0].fallback, None);
+
+        let (wanted, missing) = plan_windows(&selected, &list, MonitorFallback::NextAvailable);
+        assert_eq!(wanted[0].id, "side-1920x1080");
+        assert_eq!(missing[0].fallback.as_deref(), Some("side-1920x1080"));
+    }
+}
@@ -0,0 +1,194 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use tracing::{error, warn};
+
+/// Content hash of the last write made by this process to each path, so file
+/// watchers can tell our own writes apart from external edits.
+static LAST_WRITTEN: LazyLock<Mutex<HashMap<PathBuf, u64>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Hash file contents for change detection.
+pub fn content_hash(contents: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Whether `contents` is exactly what this process last wrote to `path`.
+pub fn is_own_write(path: &Path, contents: &[u8]) -> bool {
+    LAST_WRITTEN
+        .lock()
+        .is_ok_and(|map| map.get(path) == Some(&content_hash(contents)))
+}
+
+/// Path of the backup copy kept alongside `path`.
+fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+/// Path of the temporary file a new version is staged in before the rename.
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// Whether the current file at `path` is good enough to become the backup.
+/// A JSON file that no longer parses must not replace the last good `.bak`,
+/// which is exactly what [`read_string`] falls back to.
+fn backup_worthy(path: &Path) -> bool {
+    if path.extension().is_some_and(|ext| ext == "json") {
+        fs::read(path).is_ok_and(|bytes| serde_json::from_slice::<Value>(&bytes).is_ok())
+    } else {
+        path.exists()
+    }
+}
+
+/// Write `contents` to `path` without ever leaving a truncated file behind.
+///
+/// The data is staged in a temp file and fsynced, the current file is copied
+/// to `.bak` if it is valid, then the temp file is renamed over the target.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let parent = path.parent().ok_or("Path has no parent directory")?;
+    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+
+    let tmp = temp_path(path);
+    let result = (|| -> std::io::Result<()> {
+        let mut file = File::create(&tmp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        if backup_worthy(path) {
+            fs::copy(path, backup_path(path))?;
+        } else if path.exists() {
+            warn!(
+                "[persist] {} is corrupt, keeping the previous backup",
+                path.display()
+            );
+        }
+        if let Ok(mut map) = LAST_WRITTEN.lock() {
+            map.insert(path.to_path_buf(), content_hash(contents));
+        }
+        fs::rename(&tmp, path)?;
+        // Persist the rename itself; directories can't be opened for sync on Windows.
+        #[cfg(unix)]
+        File::open(parent)?.sync_all()?;
+        Ok(())
+    })();
+
+    result.map_err(|e| {
+        error!("[persist] write error for {}: {e}", path.display());
+        let _ = fs::remove_file(&tmp);
+        e.to_string()
+    })
+}
+
+/// Remove a file together with its backup copy. Missing files are ignored.
+pub fn remove(path: &Path) -> Result<(), String> {
+    for target in [path.to_path_buf(), backup_path(path)] {
+        match fs::remove_file(&target) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+    Ok(())
+}
+
+/// Serialise `data` as pretty JSON and write it atomically.
+pub fn write_json<T: Serialize + ?Sized>(path: &Path, data: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(data).map_err(|e| e.to_string())?;
+    write_atomic(path, json.as_bytes())
+}
+
+/// Read the raw contents of a JSON file, falling back to its `.bak` copy when
+/// the primary file is missing or does not parse. Returns `None` if neither exists.
+pub fn read_string(path: &Path) -> Result<Option<String>, String> {
+    let primary_err = match fs::read_to_string(path) {
+        Ok(json) => match serde_json::from_str::<Value>(&json) {
+            Ok(_) => return Ok(Some(json)),
+            Err(e) => format!("corrupt file: {e}"),
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => e.to_string(),
+    };
+
+    let backup = backup_path(path);
+    match fs::read_to_string(&backup) {
+        Ok(json) if serde_json::from_str::<Value>(&json).is_ok() => {
+            if !primary_err.is_empty() {
+                warn!(
+                    "[persist] {} unreadable ({primary_err}), restored from {}",
+                    path.display(),
+                    backup.display()
+                );
+            }
+            Ok(Some(json))
+        }
+        _ if primary_err.is_empty() => Ok(None),
+        _ => Err(primary_err),
+    }
+}
+
+/// Read and parse a JSON file with the same backup fallback as [`read_string`].
+pub fn read_json(path: &Path) -> Result<Option<Value>, String> {
+    read_string(path)?
+        .map(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("persist-test-{}", uuid::Uuid::new_v4()))
+            .join(name)
+    }
+
+    #[test]
+    fn failed_write_leaves_primary_intact() {
+        let path = temp_file("settings.json");
+        write_atomic(&path, b"{\"v\":1}").unwrap();
+        // A directory in the temp file's place makes staging fail part-way
+        fs::create_dir(temp_path(&path)).unwrap();
+        assert!(write_atomic(&path, b"{\"v\":2}").is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"v\":1}");
+        fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn corrupt_primary_falls_back_to_and_keeps_backup() {
+        let path = temp_file("settings.json");
+        write_atomic(&path, b"{\"v\":1}").unwrap();
+        write_atomic(&path, b"{\"v\":2}").unwrap();
+        fs::write(&path, b"{\"v\":").unwrap();
+        assert_eq!(read_string(&path).unwrap().unwrap(), "{\"v\":1}");
+
+        // Writing over the corrupt primary must not rotate it into the backup
+        write_atomic(&path, b"{\"v\":3}").unwrap();
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "{\"v\":1}");
+        fs::write(&path, b"garbage").unwrap();
+        assert_eq!(read_string(&path).unwrap().unwrap(), "{\"v\":1}");
+        fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn remembers_own_writes() {
+        let path = temp_file("suggestions.json");
+        write_atomic(&path, b"[]").unwrap();
+        assert!(is_own_write(&path, b"[]"));
+        assert!(!is_own_write(&path, b"[1]"));
+        assert!(!is_own_write(&temp_file("other.json"), b"[]"));
+        fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+}
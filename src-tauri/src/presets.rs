@@ -1,11 +1,101 @@
-use std::path::PathBuf;
-use tracing::info;
+use crate::bundle;
+use crate::persist;
+use crate::settings::{
+    self, OverlaySettings, Settings, SettingsDiff, SettingsState, WidgetInstance,
+};
+use crate::share_code;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tauri::Emitter;
+use tracing::{info, warn};
+
+/// Emitted after a preset is saved, imported or deleted.
+pub const PRESETS_CHANGED_EVENT: &str = "presets-changed";
+
+/// Longest slug accepted for a preset file name.
+const MAX_SLUG_LEN: usize = 64;
+
+/// Largest accepted thumbnail, after base64 decoding.
+const MAX_THUMBNAIL_BYTES: usize = 2 * 1024 * 1024;
 
 /// Metadata about a saved preset file.
-#[derive(serde::Serialize)]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct PresetInfo {
-    name: String,
+    /// Slug used to load, delete and export the preset.
+    pub name: String,
+    pub display_name: String,
+    description: String,
     path: String,
+    /// Unix millis.
+    created_at: i64,
+    /// Unix millis.
+    updated_at: i64,
+    app_version: Option<String>,
+    widget_count: usize,
+    /// Distinct widget type ids, sorted.
+    widget_types: Vec<String>,
+    /// Path to the PNG thumbnail, if one was saved.
+    thumbnail: Option<String>,
+}
+
+/// Metadata envelope stored under `meta` in a preset file. Presets saved
+/// before metadata existed have none; their fields are derived on listing.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct PresetMeta {
+    display_name: String,
+    description: String,
+    created_at: i64,
+    updated_at: i64,
+    app_version: Option<String>,
+}
+
+/// What to do when saving a preset whose slug is already taken.
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictPolicy {
+    /// Refuse to save, so the UI can ask for confirmation.
+    #[default]
+    Error,
+    /// Replace the existing preset.
+    Overwrite,
+    /// Append `-2`, `-3`, … until the slug is free.
+    Rename,
+}
+
+/// Widgets to take from the source preset in a merge. An instance is
+/// selected if it matches either list.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MergeSelection {
+    instance_ids: Vec<String>,
+    type_ids: Vec<String>,
+}
+
+/// A merged instance that was given a new id to avoid a collision.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RenamedInstance {
+    from: String,
+    to: String,
+}
+
+/// Outcome of [`merge_preset`].
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeResult {
+    /// The merged layout.
+    instances: Vec<WidgetInstance>,
+    /// Changes relative to the target layout.
+    diff: SettingsDiff,
+    renamed: Vec<RenamedInstance>,
+    /// Whether the merged layout was written; false for dry runs.
+    applied: bool,
 }
 
 /// Resolve the presets directory under the app config folder.
@@ -17,7 +107,190 @@ fn presets_dir() -> Result<PathBuf, String> {
         .join("presets"))
 }
 
-/// List all preset files in the presets directory.
+/// Derive a file-safe slug from a user-facing preset name.
+///
+/// Lowercases, turns whitespace and underscores into hyphens and drops every
+/// other character outside `[a-z0-9-]`, so the result can never contain path
+/// separators or dot segments.
+fn slugify(name: &str) -> Result<String, String> {
+    let mut slug = String::new();
+    for c in name.trim().chars().flat_map(char::to_lowercase) {
+        let c = if c.is_whitespace() || c == '_' {
+            '-'
+        } else {
+            c
+        };
+        if c == '-' && (slug.is_empty() || slug.ends_with('-')) {
+            continue;
+        }
+        if c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' {
+            slug.push(c);
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    validate_slug(slug)?;
+    Ok(slug.to_string())
+}
+
+/// Check that `slug` is a well-formed preset slug before it is joined into a path.
+fn validate_slug(slug: &str) -> Result<(), String> {
+    let valid = !slug.is_empty()
+        && slug.len() <= MAX_SLUG_LEN
+        && !slug.starts_with('-')
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid preset name '{slug}': use 1-{MAX_SLUG_LEN} letters, digits or hyphens"
+        ))
+    }
+}
+
+/// Path of the preset file for an already validated slug.
+fn preset_path(slug: &str) -> Result<PathBuf, String> {
+    validate_slug(slug)?;
+    Ok(presets_dir()?.join(format!("{slug}.json")))
+}
+
+/// Path of the thumbnail image stored next to a preset.
+fn thumbnail_path(preset_path: &Path) -> PathBuf {
+    preset_path.with_extension("png")
+}
+
+/// Decode a `data:image/png;base64,…` URL (or bare base64) into PNG bytes.
+fn decode_thumbnail(data: &str) -> Result<Vec<u8>, String> {
+    let encoded = match data.split_once(',') {
+        Some((header, body)) if header.starts_with("data:image/png") => body,
+        Some(_) => return Err("Thumbnail must be a PNG image".into()),
+        None => data,
+    };
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| format!("Invalid thumbnail: {e}"))?;
+    if bytes.len() > MAX_THUMBNAIL_BYTES {
+        return Err("Thumbnail is too large".into());
+    }
+    if !bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Err("Thumbnail must be a PNG image".into());
+    }
+    Ok(bytes)
+}
+
+fn to_millis(time: std::io::Result<SystemTime>) -> i64 {
+    time.ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_millis() as i64)
+}
+
+/// Build the listing entry for a preset file.
+fn preset_info(path: &Path) -> Result<PresetInfo, String> {
+    let slug = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+    let contents = persist::read_string(path)?.ok_or("Preset file vanished")?;
+    let parsed: Value = serde_json::from_str(&contents).map_err(|e| e.to_string())?;
+
+    let mut meta: PresetMeta = parsed
+        .get("meta")
+        .and_then(|m| serde_json::from_value(m.clone()).ok())
+        .unwrap_or_default();
+    if meta.display_name.is_empty() {
+        meta.display_name = slug.clone();
+    }
+    if meta.updated_at == 0 || meta.created_at == 0 {
+        let fs_meta = std::fs::metadata(path).map_err(|e| e.to_string())?;
+        let modified = to_millis(fs_meta.modified());
+        if meta.updated_at == 0 {
+            meta.updated_at = modified;
+        }
+        if meta.created_at == 0 {
+            meta.created_at = match to_millis(fs_meta.created()) {
+                0 => modified,
+                created => created,
+            };
+        }
+    }
+
+    let instances = parsed
+        .get("instances")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let mut widget_types: Vec<String> = instances
+        .iter()
+        .filter_map(|i| i.get("typeId")?.as_str().map(String::from))
+        .collect();
+    widget_types.sort();
+    widget_types.dedup();
+
+    let thumbnail = thumbnail_path(path);
+    Ok(PresetInfo {
+        name: slug,
+        display_name: meta.display_name,
+        description: meta.description,
+        path: path.to_string_lossy().to_string(),
+        created_at: meta.created_at,
+        updated_at: meta.updated_at,
+        app_version: meta.app_version,
+        widget_count: instances.len(),
+        widget_types,
+        thumbnail: thumbnail
+            .exists()
+            .then(|| thumbnail.to_string_lossy().to_string()),
+    })
+}
+
+/// Pick the slug to write to according to `policy`.
+fn resolve_conflict(slug: String, policy: ConflictPolicy) -> Result<String, String> {
+    if !preset_path(&slug)?.exists() || policy == ConflictPolicy::Overwrite {
+        return Ok(slug);
+    }
+    match policy {
+        ConflictPolicy::Error => Err(format!("Preset '{slug}' already exists")),
+        _ => (2..)
+            .map(|n| {
+                let suffix = format!("-{n}");
+                let base = &slug[..slug.len().min(MAX_SLUG_LEN - suffix.len())];
+                format!("{}{suffix}", base.trim_end_matches('-'))
+            })
+            .find(|candidate| preset_path(candidate).is_ok_and(|p| !p.exists()))
+            .ok_or_else(|| "Could not find a free preset name".into()),
+    }
+}
+
+/// Check that preset JSON is an object with an `instances` array of widget
+/// instances, each carrying string `instanceId` and `typeId` fields.
+pub fn validate_preset(contents: &str) -> Result<Value, String> {
+    let parsed: Value = serde_json::from_str(contents).map_err(|e| format!("Invalid JSON: {e}"))?;
+    validate_preset_value(&parsed)?;
+    Ok(parsed)
+}
+
+/// Shape check shared by [`validate_preset`], share code decoding and bundle import.
+pub(crate) fn validate_preset_value(parsed: &Value) -> Result<(), String> {
+    let instances = parsed
+        .get("instances")
+        .and_then(|v| v.as_array())
+        .ok_or("Preset file must contain an \"instances\" array")?;
+    for (i, inst) in instances.iter().enumerate() {
+        let has_ids = ["instanceId", "typeId"]
+            .iter()
+            .all(|key| inst.get(key).is_some_and(|v| v.is_string()));
+        if !has_ids {
+            return Err(format!(
+                "Preset instance {i} must be an object with \"instanceId\" and \"typeId\" strings"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// List all presets with their metadata, most recently modified first.
 #[tauri::command]
 pub fn list_presets() -> Result<Vec<PresetInfo>, String> {
     let presets_dir = presets_dir()?;
@@ -29,84 +302,549 @@ pub fn list_presets() -> Result<Vec<PresetInfo>, String> {
         let entry = entry.map_err(|e| e.to_string())?;
         let path = entry.path();
         if path.extension().is_some_and(|ext| ext == "json") {
-            let name = path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("unknown")
-                .to_string();
-            presets.push(PresetInfo {
-                name,
-                path: path.to_string_lossy().to_string(),
-            });
+            match preset_info(&path) {
+                Ok(info) => presets.push(info),
+                Err(e) => warn!("Skipping unreadable preset {}: {e}", path.display()),
+            }
         }
     }
+    presets.sort_by_key(|p| std::cmp::Reverse(p.updated_at));
     Ok(presets)
 }
 
-/// Save preset data to a named file.
+fn announce_presets(app: &tauri::AppHandle) {
+    if let Err(e) = app.emit(PRESETS_CHANGED_EVENT, ()) {
+        warn!("[presets] emit failed: {e}");
+    }
+}
+
+/// Save preset data to a named file. Returns the slug it was saved under.
+///
+/// `name` is kept as the display name; `thumbnail` is an optional PNG data URL.
+/// When overwriting, the original creation time is preserved.
 #[tauri::command]
-pub fn save_preset(name: String, data: String) -> Result<(), String> {
-    let presets_dir = presets_dir()?;
-    std::fs::create_dir_all(&presets_dir).map_err(|e| e.to_string())?;
-    let slug = name.to_lowercase().replace(' ', "-");
-    let path = presets_dir.join(format!("{slug}.json"));
-    std::fs::write(&path, data).map_err(|e| e.to_string())?;
+pub fn save_preset(
+    name: String,
+    data: String,
+    description: Option<String>,
+    thumbnail: Option<String>,
+    on_conflict: Option<ConflictPolicy>,
+    app: tauri::AppHandle,
+) -> Result<String, String> {
+    let mut parsed = validate_preset(&data)?;
+    let thumbnail = thumbnail.as_deref().map(decode_thumbnail).transpose()?;
+    let slug = resolve_conflict(slugify(&name)?, on_conflict.unwrap_or_default())?;
+    let path = preset_path(&slug)?;
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let created_at = persist::read_json(&path)
+        .ok()
+        .flatten()
+        .and_then(|existing| existing.get("meta")?.get("createdAt")?.as_i64())
+        .unwrap_or(now);
+    let meta = PresetMeta {
+        display_name: name.trim().to_string(),
+        description: description.unwrap_or_default(),
+        created_at,
+        updated_at: now,
+        app_version: Some(app.package_info().version.to_string()),
+    };
+    parsed
+        .as_object_mut()
+        .ok_or("Preset data must be an object")?
+        .insert(
+            "meta".into(),
+            serde_json::to_value(meta).map_err(|e| e.to_string())?,
+        );
+
+    persist::write_json(&path, &parsed)?;
+    if let Some(png) = thumbnail {
+        persist::write_atomic(&thumbnail_path(&path), &png)?;
+    }
     info!("Saved preset: {}", path.display());
-    Ok(())
+    announce_presets(&app);
+    Ok(slug)
 }
 
 /// Load a preset by name.
 #[tauri::command]
 pub fn load_preset(name: String) -> Result<String, String> {
-    let path = presets_dir()?.join(format!("{name}.json"));
-    std::fs::read_to_string(&path).map_err(|e| e.to_string())
+    let path = preset_path(&name)?;
+    persist::read_string(&path)?.ok_or_else(|| format!("Preset '{name}' not found"))
 }
 
 /// Delete a preset by name.
 #[tauri::command]
-pub fn delete_preset(name: String) -> Result<(), String> {
-    let path = presets_dir()?.join(format!("{name}.json"));
+pub fn delete_preset(name: String, app: tauri::AppHandle) -> Result<(), String> {
+    let path = preset_path(&name)?;
     if path.exists() {
-        std::fs::remove_file(&path).map_err(|e| e.to_string())?;
+        persist::remove(&path)?;
+        persist::remove(&thumbnail_path(&path))?;
         info!("Deleted preset: {}", path.display());
+        announce_presets(&app);
     }
     Ok(())
 }
 
-/// Export preset data to a user-chosen file path.
+/// Export preset data to a user-chosen file path. A `.zip` path produces a
+/// portable bundle that also contains the sounds, images and fonts it uses.
 #[tauri::command]
-pub fn export_preset(path: String, data: String) -> Result<(), String> {
+pub fn export_preset(
+    path: String,
+    data: String,
+    name: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let dest = Path::new(&path);
+    if bundle::is_bundle(dest) {
+        validate_preset(&data)?;
+        let name = name.unwrap_or_else(|| {
+            dest.file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("preset")
+                .to_string()
+        });
+        return bundle::export(dest, &name, &data, &app.package_info().version.to_string());
+    }
     std::fs::write(&path, &data).map_err(|e| e.to_string())?;
     info!("Exported preset to: {path}");
     Ok(())
 }
 
 /// Import a preset from an arbitrary file path into the presets directory.
-/// Returns the slug name of the imported preset.
+/// Accepts plain JSON presets and `.zip` bundles, whose assets are unpacked
+/// into the app's assets directory. Returns the slug name of the imported
+/// preset, suffixed if the name was taken.
 #[tauri::command]
-pub fn import_preset(path: String) -> Result<String, String> {
-    let contents = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+pub fn import_preset(path: String, app: tauri::AppHandle) -> Result<String, String> {
+    let file_path = Path::new(&path);
+    let (name, contents) = if bundle::is_bundle(file_path) {
+        let imported = bundle::import(file_path)?;
+        (imported.name, imported.contents)
+    } else {
+        // Derive a name from the file stem
+        let name = file_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("imported")
+            .to_string();
+        let contents = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        (name, contents)
+    };
+    validate_preset(&contents)?;
 
-    // Validate that the JSON is parseable and contains an instances array
-    let parsed: serde_json::Value =
-        serde_json::from_str(&contents).map_err(|e| format!("Invalid JSON: {e}"))?;
-    if !parsed.get("instances").is_some_and(|v| v.is_array()) {
-        return Err("Preset file must contain an \"instances\" array".into());
+    let slug = slugify(&name).or_else(|_| slugify("imported"))?;
+    let slug = resolve_conflict(slug, ConflictPolicy::Rename)?;
+    let dest = preset_path(&slug)?;
+    persist::write_atomic(&dest, contents.as_bytes())?;
+    info!("Imported preset '{}' to: {}", slug, dest.display());
+    announce_presets(&app);
+    Ok(slug)
+}
+
+/// Encode a saved preset as a compact share code for copy/paste exchange.
+/// Local-only metadata such as timestamps is left out.
+#[tauri::command]
+pub fn encode_preset_share(name: String) -> Result<String, String> {
+    let path = preset_path(&name)?;
+    let mut preset =
+        persist::read_json(&path)?.ok_or_else(|| format!("Preset '{name}' not found"))?;
+    validate_preset_value(&preset)?;
+
+    let display_name = preset
+        .get("meta")
+        .and_then(|m| m.get("displayName"))
+        .cloned()
+        .unwrap_or_else(|| Value::String(name.clone()));
+    let description = preset
+        .get("meta")
+        .and_then(|m| m.get("description"))
+        .cloned();
+    if let Some(obj) = preset.as_object_mut() {
+        let mut meta = serde_json::Map::new();
+        meta.insert("displayName".into(), display_name);
+        if let Some(description) = description {
+            meta.insert("description".into(), description);
+        }
+        obj.insert("meta".into(), Value::Object(meta));
     }
+    share_code::encode(&preset)
+}
 
-    // Derive a name from the file stem
-    let file_path = std::path::Path::new(&path);
-    let name = file_path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("imported")
-        .to_string();
+/// Decode and validate a share code without saving it, for previewing.
+#[tauri::command]
+pub fn decode_preset_share(code: String) -> Result<Value, String> {
+    let preset = share_code::decode(&code)?;
+    validate_preset_value(&preset)?;
+    Ok(preset)
+}
 
-    let slug = name.to_lowercase().replace(' ', "-");
-    let presets_dir = presets_dir()?;
-    std::fs::create_dir_all(&presets_dir).map_err(|e| e.to_string())?;
-    let dest = presets_dir.join(format!("{slug}.json"));
-    std::fs::write(&dest, &contents).map_err(|e| e.to_string())?;
-    info!("Imported preset '{}' to: {}", slug, dest.display());
+/// Decode a share code and save it into the presets directory.
+/// Returns the slug it was saved under, suffixed if the name was taken.
+#[tauri::command]
+pub fn import_preset_share(code: String, app: tauri::AppHandle) -> Result<String, String> {
+    let preset = decode_preset_share(code)?;
+    let name = preset
+        .get("meta")
+        .and_then(|m| m.get("displayName"))
+        .and_then(Value::as_str)
+        .unwrap_or("shared");
+    let slug = slugify(name).or_else(|_| slugify("shared"))?;
+    let slug = resolve_conflict(slug, ConflictPolicy::Rename)?;
+    let dest = preset_path(&slug)?;
+    persist::write_json(&dest, &preset)?;
+    info!("Imported shared preset '{}' to: {}", slug, dest.display());
+    announce_presets(&app);
     Ok(slug)
 }
+
+/// Parse the `instances` array of a preset document. Presets saved before
+/// instances carried their geometry get the registry defaults, as they do
+/// when loaded.
+fn preset_instances(preset: &Value) -> Result<Vec<WidgetInstance>, String> {
+    let mut instances = preset.get("instances").cloned().unwrap_or_default();
+    if let Value::Array(items) = &mut instances {
+        for inst in items.iter_mut().filter_map(Value::as_object_mut) {
+            let type_id = inst.get("typeId").and_then(Value::as_str).unwrap_or("");
+            let (x, y, width, height) = settings::widget_defaults(type_id);
+            for (key, fallback) in [
+                ("x", Value::from(x)),
+                ("y", Value::from(y)),
+                ("width", Value::from(width)),
+                ("height", Value::from(height)),
+                ("visible", Value::Bool(true)),
+            ] {
+                inst.entry(key).or_insert(fallback);
+            }
+        }
+    }
+    serde_json::from_value(instances).map_err(|e| format!("Invalid widget instances: {e}"))
+}
+
+/// Next free `{typeId}-{n}` id, matching `nextInstanceId` in the overlay store.
+fn next_instance_id(type_id: &str, taken: &HashSet<String>) -> String {
+    let max = taken
+        .iter()
+        .filter_map(|id| {
+            id.strip_prefix(type_id)?
+                .strip_prefix('-')?
+                .parse::<u32>()
+                .ok()
+        })
+        .max()
+        .unwrap_or(0);
+    (max + 1..)
+        .map(|n| format!("{type_id}-{n}"))
+        .find(|id| !taken.contains(id))
+        .unwrap_or_else(|| format!("{type_id}-{max}"))
+}
+
+/// Add the selected `source` instances to `target`.
+///
+/// An id that already exists in `target` is handled per `policy`: `Rename`
+/// assigns a fresh id, `Overwrite` replaces the existing instance in place and
+/// `Error` rejects the merge.
+fn merge_instances(
+    mut target: Vec<WidgetInstance>,
+    source: Vec<WidgetInstance>,
+    selection: &MergeSelection,
+    policy: ConflictPolicy,
+) -> Result<(Vec<WidgetInstance>, Vec<RenamedInstance>), String> {
+    if let Some(missing) = selection
+        .instance_ids
+        .iter()
+        .find(|id| !source.iter().any(|i| &i.instance_id == *id))
+    {
+        return Err(format!("Widget '{missing}' is not in the source preset"));
+    }
+    let selected: Vec<WidgetInstance> = source
+        .into_iter()
+        .filter(|i| {
+            selection.instance_ids.contains(&i.instance_id)
+                || selection.type_ids.contains(&i.type_id)
+        })
+        .collect();
+    if selected.is_empty() {
+        return Err("No widgets in the source preset match the selection".into());
+    }
+
+    let mut taken: HashSet<String> = target.iter().map(|i| i.instance_id.clone()).collect();
+    if policy == ConflictPolicy::Error {
+        let collisions: Vec<&str> = selected
+            .iter()
+            .filter(|i| taken.contains(&i.instance_id))
+            .map(|i| i.instance_id.as_str())
+            .collect();
+        if !collisions.is_empty() {
+            return Err(format!(
+                "Widgets already exist in the layout: {}",
+                collisions.join(", ")
+            ));
+        }
+    }
+
+    // Reserve incoming ids too, so a rename never takes an id that a later
+    // selected instance keeps
+    taken.extend(selected.iter().map(|i| i.instance_id.clone()));
+    let mut renamed = Vec::new();
+    for mut inst in selected {
+        if let Some(pos) = target
+            .iter()
+            .position(|t| t.instance_id == inst.instance_id)
+        {
+            if policy == ConflictPolicy::Overwrite {
+                target[pos] = inst;
+                continue;
+            }
+            let id = next_instance_id(&inst.type_id, &taken);
+            renamed.push(RenamedInstance {
+                from: std::mem::replace(&mut inst.instance_id, id.clone()),
+                to: id,
+            });
+        }
+        taken.insert(inst.instance_id.clone());
+        target.push(inst);
+    }
+    Ok((target, renamed))
+}
+
+/// Wrap a bare instance list so it can be compared with [`settings::diff_settings`].
+fn as_layout(instances: &[WidgetInstance]) -> Settings {
+    Settings {
+        overlay: Some(OverlaySettings {
+            instances: instances.to_vec(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Merge selected widgets from the `source` preset into a target layout.
+///
+/// The target is the preset named by `target`, or the live settings when it
+/// is omitted. Id collisions are renamed unless `on_conflict` says otherwise.
+/// With `dry_run` nothing is written and only the result and diff are returned.
+#[tauri::command]
+pub fn merge_preset(
+    source: String,
+    selection: MergeSelection,
+    target: Option<String>,
+    on_conflict: Option<ConflictPolicy>,
+    dry_run: Option<bool>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+) -> Result<MergeResult, String> {
+    let source_instances = preset_instances(&validate_preset(&load_preset(source.clone())?)?)?;
+
+    let mut target_preset = None;
+    let mut live = None;
+    let current = match &target {
+        Some(name) => {
+            let preset = validate_preset(&load_preset(name.clone())?)?;
+            let instances = preset_instances(&preset)?;
+            target_preset = Some(preset);
+            instances
+        }
+        None => {
+            let settings = state.load()?.unwrap_or_default();
+            let instances = settings
+                .overlay
+                .as_ref()
+                .map(|o| o.instances.clone())
+                .unwrap_or_default();
+            live = Some(settings);
+            instances
+        }
+    };
+
+    let policy = on_conflict.unwrap_or(ConflictPolicy::Rename);
+    let (instances, renamed) =
+        merge_instances(current.clone(), source_instances, &selection, policy)?;
+    let diff = settings::diff_settings(&as_layout(&current), &as_layout(&instances));
+
+    let dry_run = dry_run.unwrap_or(false);
+    if !dry_run {
+        let instances_value = serde_json::to_value(&instances).map_err(|e| e.to_string())?;
+        if let (Some(name), Some(mut preset)) = (&target, target_preset) {
+            let doc = preset
+                .as_object_mut()
+                .ok_or("Preset data must be an object")?;
+            doc.insert("instances".into(), instances_value);
+            if let Some(meta) = doc.get_mut("meta").and_then(Value::as_object_mut) {
+                meta.insert(
+                    "updatedAt".into(),
+                    chrono::Utc::now().timestamp_millis().into(),
+                );
+            }
+            persist::write_json(&preset_path(name)?, &preset)?;
+            announce_presets(&app);
+        } else if live.is_some() {
+            let ((), settings) = state.update(|s| {
+                s.overlay.get_or_insert_default().instances = instances.clone();
+                Ok(())
+            })?;
+            app.emit(crate::watcher::SETTINGS_CHANGED_EVENT, &settings)
+                .map_err(|e| e.to_string())?;
+        }
+        info!(
+            "Merged preset '{source}' into {} ({} widgets, {} renamed)",
+            target.as_deref().unwrap_or("the live layout"),
+            instances.len(),
+            renamed.len()
+        );
+    }
+
+    Ok(MergeResult {
+        instances,
+        diff,
+        renamed,
+        applied: !dry_run,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slugify_normalises_display_names() {
+        assert_eq!(slugify("Just Chatting").unwrap(), "just-chatting");
+        assert_eq!(slugify("  BRB  screen ").unwrap(), "brb-screen");
+        assert_eq!(slugify("coding_stream 2").unwrap(), "coding-stream-2");
+        assert_eq!(slugify("Gaming!!").unwrap(), "gaming");
+    }
+
+    #[test]
+    fn slugify_strips_traversal_sequences() {
+        assert_eq!(slugify("../settings").unwrap(), "settings");
+        assert_eq!(slugify("..\\..\\tokens").unwrap(), "tokens");
+        assert_eq!(slugify("/etc/passwd").unwrap(), "etcpasswd");
+        assert_eq!(slugify("C:\\Windows\\win.ini").unwrap(), "cwindowswinini");
+    }
+
+    #[test]
+    fn slugify_rejects_names_without_usable_characters() {
+        for name in ["", "   ", "..", "../", "///", "💥", "-_-"] {
+            assert!(slugify(name).is_err(), "{name:?} should be rejected");
+        }
+        assert!(slugify(&"a".repeat(MAX_SLUG_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn validate_slug_rejects_hostile_names() {
+        for name in [
+            "../settings",
+            "..",
+            ".",
+            "",
+            "foo/bar",
+            "foo\\bar",
+            ".hidden",
+            "UPPER",
+            "nul\0byte",
+            "-leading",
+            "with space",
+            "settings.json",
+        ] {
+            assert!(validate_slug(name).is_err(), "{name:?} should be rejected");
+        }
+        assert!(validate_slug("just-chatting-2").is_ok());
+    }
+
+    #[test]
+    fn validate_preset_checks_instances_shape() {
+        assert!(validate_preset(r#"{"instances":[]}"#).is_ok());
+        assert!(validate_preset(
+            r#"{"version":1,"instances":[{"instanceId":"a","typeId":"chat"}]}"#
+        )
+        .is_ok());
+        assert!(validate_preset("not json").is_err());
+        assert!(validate_preset(r#"{"instances":{}}"#).is_err());
+        assert!(validate_preset(r#"{"instances":[1]}"#).is_err());
+        assert!(validate_preset(r#"{"instances":[{"instanceId":"a"}]}"#).is_err());
+    }
+
+    fn widget(instance_id: &str, type_id: &str) -> WidgetInstance {
+        serde_json::from_value(serde_json::json!({
+            "instanceId": instance_id,
+            "typeId": type_id,
+            "x": 0, "y": 0, "width": 100, "height": 100,
+            "visible": true,
+        }))
+        .unwrap()
+    }
+
+    fn ids(instances: &[WidgetInstance]) -> Vec<&str> {
+        instances.iter().map(|i| i.instance_id.as_str()).collect()
+    }
+
+    #[test]
+    fn preset_instances_default_missing_geometry() {
+        let preset = serde_json::json!({ "instances": [
+            { "instanceId": "chat-1", "typeId": "chat" },
+            { "instanceId": "text-1", "typeId": "custom-text", "x": 5, "visible": false },
+        ]});
+        let instances = preset_instances(&preset).unwrap();
+        let chat = &instances[0];
+        assert_eq!(
+            (chat.x, chat.y, chat.width, chat.height, chat.visible),
+            (8.0, 840.0, 416.0, 296.0, true)
+        );
+        let text = &instances[1];
+        assert_eq!((text.x, text.width, text.visible), (5.0, 544.0, false));
+    }
+
+    #[test]
+    fn merge_renames_colliding_ids() {
+        let target = vec![widget("chat-1", "chat"), widget("alerts-1", "alerts")];
+        let source = vec![
+            widget("alerts-1", "alerts"),
+            widget("alerts-2", "alerts"),
+            widget("chat-1", "chat"),
+        ];
+        let selection = MergeSelection {
+            type_ids: vec!["alerts".into()],
+            ..Default::default()
+        };
+        let (merged, renamed) =
+            merge_instances(target, source, &selection, ConflictPolicy::Rename).unwrap();
+        assert_eq!(ids(&merged), ["chat-1", "alerts-1", "alerts-3", "alerts-2"]);
+        assert_eq!(
+            renamed,
+            [RenamedInstance {
+                from: "alerts-1".into(),
+                to: "alerts-3".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn merge_overwrites_or_rejects_collisions_per_policy() {
+        let target = vec![widget("chat-1", "chat")];
+        let mut replacement = widget("chat-1", "chat");
+        replacement.x = 50.0;
+        let selection = MergeSelection {
+            instance_ids: vec!["chat-1".into()],
+            ..Default::default()
+        };
+
+        let (merged, renamed) = merge_instances(
+            target.clone(),
+            vec![replacement.clone()],
+            &selection,
+            ConflictPolicy::Overwrite,
+        )
+        .unwrap();
+        assert_eq!(merged, [replacement.clone()]);
+        assert!(renamed.is_empty());
+
+        assert!(merge_instances(
+            target.clone(),
+            vec![replacement],
+            &selection,
+            ConflictPolicy::Error
+        )
+        .is_err());
+        assert!(merge_instances(target, vec![], &selection, ConflictPolicy::Rename).is_err());
+    }
+}
@@ -0,0 +1,304 @@
+use crate::events::{ChannelEvent, ChannelEventType, CHANNEL_EVENT};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::Emitter;
+use tracing::{info, warn};
+
+/// Emitted when a replay runs to completion or is stopped.
+const REPLAY_FINISHED_EVENT: &str = "replay-finished";
+
+/// Managed state for the running replay, if any.
+#[derive(Default)]
+pub struct ReplayState {
+    task: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+}
+
+/// A single step in a scripted scenario file.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScenarioStep {
+    /// Delay before this step, relative to the previous one.
+    #[serde(default)]
+    delay_ms: u64,
+    #[serde(rename = "type")]
+    kind: ChannelEventType,
+    /// Payload fields; merged over the synthetic defaults for the type.
+    #[serde(default)]
+    data: Map<String, Value>,
+}
+
+/// A scripted scenario: an ordered list of events with relative delays.
+#[derive(Deserialize)]
+struct Scenario {
+    steps: Vec<ScenarioStep>,
+}
+
+/// Summary of a replay returned when it starts.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayInfo {
+    events: usize,
+    /// Total playback time at the requested speed.
+    duration_ms: u64,
+}
+
+/// Load a recorded JSONL event log as (delay, event) pairs.
+fn load_recording(contents: &str) -> Vec<(u64, ChannelEvent)> {
+    let mut previous: Option<i64> = None;
+    let mut timeline = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match ChannelEvent::from_log_line(line) {
+            Ok(event) => {
+                let delay = previous
+                    .map(|p| (event.timestamp - p).max(0) as u64)
+                    .unwrap_or(0);
+                previous = Some(event.timestamp);
+                timeline.push((delay, event));
+            }
+            Err(e) => warn!("[replay] skipping line {}: {e}", i + 1),
+        }
+    }
+    timeline
+}
+
+/// Load a scripted scenario file as (delay, event) pairs.
+fn load_scenario(contents: &str) -> Result<Vec<(u64, ChannelEvent)>, String> {
+    let scenario: Scenario =
+        serde_json::from_str(contents).map_err(|e| format!("Invalid scenario: {e}"))?;
+    Ok(scenario
+        .steps
+        .into_iter()
+        .map(|step| (step.delay_ms, synthesise(step.kind, step.data)))
+        .collect())
+}
+
+const SAMPLE_USERS: &[&str] = &[
+    "pixel_pirate",
+    "cosy_coder",
+    "lurkmaster3000",
+    "speedrun_sam",
+    "tea_enjoyer",
+    "night_owl_nina",
+];
+
+static SAMPLE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Generate a plausible EventSub-shaped payload for `kind`, with `overrides`
+/// merged on top. Marked as simulated so it is never written to the event log.
+fn synthesise(kind: ChannelEventType, overrides: Map<String, Value>) -> ChannelEvent {
+    let n = SAMPLE_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let user = SAMPLE_USERS[n as usize % SAMPLE_USERS.len()];
+    let user_id = format!("{}", 100_000 + n);
+
+    let defaults = match kind {
+        ChannelEventType::Follow => json!({
+            "user_id": user_id,
+            "user_login": user,
+            "user_name": user,
+            "followed_at": chrono::Utc::now().to_rfc3339(),
+        }),
+        ChannelEventType::Subscribe => json!({
+            "user_id": user_id,
+            "user_name": user,
+            "tier": "1000",
+            "is_gift": false,
+        }),
+        ChannelEventType::GiftSub => json!({
+            "user_id": user_id,
+            "user_name": user,
+            "gifter_user_name": user,
+            "tier": "1000",
+            "total": 5,
+            "is_anonymous": false,
+        }),
+        ChannelEventType::Raid => json!({
+            "from_broadcaster_user_id": user_id,
+            "from_broadcaster_user_name": user,
+            "viewers": 42,
+        }),
+        ChannelEventType::Cheer => json!({
+            "user_id": user_id,
+            "user_name": user,
+            "bits": 100,
+            "message": "Cheer100 great stream!",
+            "is_anonymous": false,
+        }),
+        ChannelEventType::ChannelPointsRedemption => json!({
+            "id": format!("sim-redemption-{n}"),
+            "user_id": user_id,
+            "user_name": user,
+            "user_input": "Play something chill",
+            "status": "unfulfilled",
+            "reward": { "id": "sim-reward", "title": "Suggestion", "cost": 500 },
+        }),
+        ChannelEventType::Chat => json!({
+            "username": user,
+            "userId": user_id,
+            "text": "Hello from the simulator!",
+        }),
+        ChannelEventType::Join | ChannelEventType::Part => json!({ "username": user }),
+        _ => json!({}),
+    };
+
+    let mut data = match defaults {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    };
+    data.extend(overrides);
+    data.insert("simulated".into(), Value::Bool(true));
+
+    ChannelEvent {
+        kind,
+        timestamp: chrono::Utc::now().timestamp_millis(),
+        data,
+    }
+}
+
+/// Start replaying a recorded event log (`.jsonl`) or scenario file (`.json`)
+/// to all windows. `speed` scales playback, e.g. 2.0 plays twice as fast.
+#[tauri::command]
+pub fn replay_start(
+    path: String,
+    speed: Option<f64>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, ReplayState>,
+) -> Result<ReplayInfo, String> {
+    let speed = speed.unwrap_or(1.0);
+    if !speed.is_finite() || speed <= 0.0 {
+        return Err("Replay speed must be greater than zero".into());
+    }
+
+    let contents = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let timeline = if Path::new(&path)
+        .extension()
+        .is_some_and(|ext| ext == "jsonl")
+    {
+        load_recording(&contents)
+    } else {
+        load_scenario(&contents)?
+    };
+    if timeline.is_empty() {
+        return Err("No events to replay".into());
+    }
+
+    let timeline: Vec<(u64, ChannelEvent)> = timeline
+        .into_iter()
+        .map(|(delay, event)| ((delay as f64 / speed) as u64, event))
+        .collect();
+    let info = ReplayInfo {
+        events: timeline.len(),
+        duration_ms: timeline.iter().map(|(delay, _)| delay).sum(),
+    };
+
+    let mut task = state.task.lock().map_err(|e| e.to_string())?;
+    if let Some(previous) = task.take() {
+        previous.abort();
+    }
+
+    info!(
+        "[replay] starting {} events from {path} at {speed}x",
+        info.events
+    );
+    *task = Some(tauri::async_runtime::spawn(async move {
+        for (delay, mut event) in timeline {
+            if delay > 0 {
+                tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+            }
+            event.timestamp = chrono::Utc::now().timestamp_millis();
+            event.data.insert("simulated".into(), Value::Bool(true));
+            if let Err(e) = app.emit(CHANNEL_EVENT, &event) {
+                warn!("[replay] emit failed: {e}");
+            }
+        }
+        info!("[replay] finished");
+        let _ = app.emit(REPLAY_FINISHED_EVENT, ());
+    }));
+
+    Ok(info)
+}
+
+/// Stop the running replay, if any.
+#[tauri::command]
+pub fn replay_stop(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, ReplayState>,
+) -> Result<(), String> {
+    if let Some(task) = state.task.lock().map_err(|e| e.to_string())?.take() {
+        task.abort();
+        info!("[replay] stopped");
+        let _ = app.emit(REPLAY_FINISHED_EVENT, ());
+    }
+    Ok(())
+}
+
+/// Emit a single synthetic event to all windows. `data` overrides fields of
+/// the generated payload.
+#[tauri::command]
+pub fn simulate_event(
+    kind: ChannelEventType,
+    data: Option<Map<String, Value>>,
+    app: tauri::AppHandle,
+) -> Result<ChannelEvent, String> {
+    let event = synthesise(kind, data.unwrap_or_default());
+    app.emit(CHANNEL_EVENT, &event).map_err(|e| e.to_string())?;
+    Ok(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recording_keeps_relative_delays_and_skips_bad_lines() {
+        let contents = [
+            r#"{"v":1,"type":"follow","timestamp":1000,"data":{}}"#,
+            "",
+            "not json",
+            r#"{"v":1,"type":"cheer","timestamp":1750,"data":{"bits":5}}"#,
+            r#"{"v":99,"type":"raid","timestamp":2000,"data":{}}"#,
+            r#"{"type":"raid","timestamp":1500,"data":{}}"#,
+        ]
+        .join("\n");
+        let timeline = load_recording(&contents);
+        let delays: Vec<u64> = timeline.iter().map(|(d, _)| *d).collect();
+        assert_eq!(delays, [0, 750, 0]);
+        assert_eq!(timeline[1].1.kind, ChannelEventType::Cheer);
+        assert_eq!(timeline[2].1.kind, ChannelEventType::Raid);
+    }
+
+    #[test]
+    fn scenario_steps_are_synthesised_with_overrides() {
+        let timeline = load_scenario(
+            r#"{"steps":[
+                {"type":"raid","data":{"viewers":7}},
+                {"delayMs":250,"type":"chat","data":{"text":"!marker hi"}}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(timeline.len(), 2);
+        assert_eq!(timeline[0].0, 0);
+        assert_eq!(timeline[0].1.data["viewers"], 7);
+        assert_eq!(timeline[1].0, 250);
+        assert_eq!(timeline[1].1.data["text"], "!marker hi");
+        assert!(timeline.iter().all(|(_, e)| e.is_simulated()));
+
+        assert!(load_scenario(r#"{"steps":[{"type":"nope"}]}"#).is_err());
+    }
+
+    #[test]
+    fn synthesised_events_are_always_simulated() {
+        let mut overrides = Map::new();
+        overrides.insert("simulated".into(), Value::Bool(false));
+        overrides.insert("bits".into(), json!(500));
+        let event = synthesise(ChannelEventType::Cheer, overrides);
+        assert!(event.is_simulated());
+        assert_eq!(event.data["bits"], 500);
+        assert!(event.data.contains_key("user_name"));
+    }
+}
@@ -0,0 +1,504 @@
+//! Custom channel-point rewards created and managed by the app.
+//!
+//! Each reward is tied to an overlay feature ([`RewardFeature`]) and recorded
+//! under `rewards` in settings. Creating a reward also wires it up: a
+//! suggestion box instance gets the reward id as its `rewardId`, and a sound
+//! reward adds a `reward:{id}` entry to the sound mappings.
+//!
+//! Twitch only lets an app edit rewards it created itself, which is why these
+//! are kept separate from rewards made on the dashboard.
+
+use crate::auth::AuthState;
+use crate::helix::{broadcaster_id, helix_request};
+use crate::settings::{ManagedReward, RewardFeature, Settings, SettingsState, SoundMapping};
+use crate::watcher::SETTINGS_CHANGED_EVENT;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+use tracing::{info, warn};
+
+const SUGGESTION_BOX_TYPE: &str = "suggestion-box";
+
+/// Sound mapping key a sound reward plays under.
+pub fn sound_mapping_key(reward_id: &str) -> String {
+    format!("reward:{reward_id}")
+}
+
+/// Reward fields sent to Helix. Everything is optional so the same shape
+/// serves partial updates; `title` and `cost` are required on create.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RewardSpec {
+    pub title: Option<String>,
+    pub cost: Option<u64>,
+    pub prompt: Option<String>,
+    pub background_color: Option<String>,
+    pub is_user_input_required: Option<bool>,
+    /// `0` removes the limit.
+    pub max_per_stream: Option<u64>,
+    /// `0` removes the cooldown.
+    pub global_cooldown_seconds: Option<u64>,
+    pub should_redemptions_skip_request_queue: Option<bool>,
+}
+
+impl RewardSpec {
+    /// Helix request body. Limits also toggle their `*_enabled` flag.
+    fn to_body(&self) -> Value {
+        let mut body = Map::new();
+        let mut put = |key: &str, value: Value| {
+            body.insert(key.into(), value);
+        };
+        if let Some(title) = &self.title {
+            put("title", title.trim().into());
+        }
+        if let Some(cost) = self.cost {
+            put("cost", cost.into());
+        }
+        if let Some(prompt) = &self.prompt {
+            put("prompt", prompt.clone().into());
+        }
+        if let Some(colour) = &self.background_color {
+            put("background_color", colour.clone().into());
+        }
+        if let Some(required) = self.is_user_input_required {
+            put("is_user_input_required", required.into());
+        }
+        if let Some(max) = self.max_per_stream {
+            put("is_max_per_stream_enabled", (max > 0).into());
+            put("max_per_stream", max.into());
+        }
+        if let Some(cooldown) = self.global_cooldown_seconds {
+            put("is_global_cooldown_enabled", (cooldown > 0).into());
+            put("global_cooldown_seconds", cooldown.into());
+        }
+        if let Some(skip) = self.should_redemptions_skip_request_queue {
+            put("should_redemptions_skip_request_queue", skip.into());
+        }
+        Value::Object(body)
+    }
+}
+
+/// The subset of a Helix custom reward the app cares about.
+#[derive(Debug, Clone, Deserialize)]
+struct HelixReward {
+    id: String,
+    title: String,
+    cost: u64,
+    #[serde(default)]
+    prompt: String,
+    is_enabled: bool,
+    is_paused: bool,
+}
+
+#[derive(Deserialize)]
+struct HelixRewards {
+    data: Vec<HelixReward>,
+}
+
+/// A managed reward joined with its live state on Twitch.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RewardInfo {
+    #[serde(flatten)]
+    pub reward: ManagedReward,
+    pub cost: Option<u64>,
+    pub prompt: Option<String>,
+    pub is_enabled: bool,
+    pub is_paused: bool,
+    /// The reward no longer exists on Twitch (deleted on the dashboard).
+    pub missing: bool,
+}
+
+impl RewardInfo {
+    fn new(reward: ManagedReward, live: Option<&HelixReward>) -> Self {
+        Self {
+            reward,
+            cost: live.map(|r| r.cost),
+            prompt: live.map(|r| r.prompt.clone()),
+            is_enabled: live.is_some_and(|r| r.is_enabled),
+            is_paused: live.is_some_and(|r| r.is_paused),
+            missing: live.is_none(),
+        }
+    }
+}
+
+fn rewards_path(broadcaster_id: &str, reward_id: Option<&str>) -> String {
+    let mut path = format!("/channel_points/custom_rewards?broadcaster_id={broadcaster_id}");
+    if let Some(id) = reward_id {
+        path.push_str(&format!("&id={id}"));
+    }
+    path
+}
+
+/// Parse the single reward returned by a create or update.
+fn parse_one(body: &str) -> Result<HelixReward, String> {
+    serde_json::from_str::<HelixRewards>(body)
+        .map_err(|e| e.to_string())?
+        .data
+        .into_iter()
+        .next()
+        .ok_or_else(|| "Helix returned no reward".to_string())
+}
+
+/// Point the feature at its new reward.
+fn link(settings: &mut Settings, reward: &ManagedReward) -> Result<(), String> {
+    let target = reward.target.as_deref().unwrap_or_default();
+    match reward.feature {
+        RewardFeature::SuggestionBox => {
+            let instance = settings
+                .overlay
+                .get_or_insert_default()
+                .instances
+                .iter_mut()
+                .find(|i| i.instance_id == target && i.type_id == SUGGESTION_BOX_TYPE)
+                .ok_or_else(|| format!("No suggestion box widget '{target}'"))?;
+            instance
+                .config
+                .get_or_insert_default()
+                .insert("rewardId".into(), reward.reward_id.clone().into());
+        }
+        RewardFeature::Sound => {
+            settings
+                .overlay
+                .get_or_insert_default()
+                .sound_mappings
+                .get_or_insert_default()
+                .insert(
+                    sound_mapping_key(&reward.reward_id),
+                    SoundMapping {
+                        enabled: true,
+                        sound: target.to_string(),
+                    },
+                );
+        }
+        RewardFeature::Tts => {}
+    }
+    Ok(())
+}
+
+/// Undo [`link`], leaving anything the user has since repointed alone.
+fn unlink(settings: &mut Settings, reward: &ManagedReward) {
+    let Some(overlay) = settings.overlay.as_mut() else {
+        return;
+    };
+    match reward.feature {
+        RewardFeature::SuggestionBox => {
+            for instance in &mut overlay.instances {
+                if let Some(config) = instance.config.as_mut() {
+                    if config.get("rewardId").and_then(Value::as_str) == Some(&reward.reward_id) {
+                        config.insert("rewardId".into(), "".into());
+                    }
+                }
+            }
+        }
+        RewardFeature::Sound => {
+            if let Some(mappings) = overlay.sound_mappings.as_mut() {
+                mappings.remove(&sound_mapping_key(&reward.reward_id));
+            }
+        }
+        RewardFeature::Tts => {}
+    }
+}
+
+/// Check the feature has a usable target before anything is created on Twitch.
+fn validate_target(
+    settings: &Settings,
+    feature: RewardFeature,
+    target: Option<&str>,
+) -> Result<(), String> {
+    let target = target.unwrap_or_default();
+    match feature {
+        RewardFeature::SuggestionBox => {
+            let exists = settings.overlay.as_ref().is_some_and(|o| {
+                o.instances
+                    .iter()
+                    .any(|i| i.instance_id == target && i.type_id == SUGGESTION_BOX_TYPE)
+            });
+            if !exists {
+                return Err(format!("No suggestion box widget '{target}'"));
+            }
+        }
+        RewardFeature::Sound if target.is_empty() => {
+            return Err("A sound reward needs a sound to play".into());
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Apply `f` to the settings under the settings write lock, then notify the frontend.
+fn update_settings<T>(
+    app: &AppHandle,
+    state: &SettingsState,
+    f: impl FnOnce(&mut Settings) -> Result<T, String>,
+) -> Result<T, String> {
+    let (out, settings) = state.update(f)?;
+    app.emit(SETTINGS_CHANGED_EVENT, &settings)
+        .map_err(|e| e.to_string())?;
+    Ok(out)
+}
+
+fn managed(state: &SettingsState, reward_id: &str) -> Result<ManagedReward, String> {
+    state
+        .load()?
+        .unwrap_or_default()
+        .rewards
+        .into_iter()
+        .find(|r| r.reward_id == reward_id)
+        .ok_or_else(|| format!("Reward {reward_id} is not managed by this app"))
+}
+
+async fn patch_reward(
+    auth: &AuthState,
+    reward_id: &str,
+    body: &Value,
+) -> Result<HelixReward, String> {
+    let broadcaster = broadcaster_id(auth).await?;
+    let resp = helix_request(
+        auth,
+        Method::PATCH,
+        &rewards_path(&broadcaster, Some(reward_id)),
+        Some(body),
+    )
+    .await?;
+    parse_one(&resp)
+}
+
+/// List the app's rewards with their current state on Twitch.
+#[tauri::command]
+pub async fn list_managed_rewards(
+    auth: tauri::State<'_, Arc<AuthState>>,
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<Vec<RewardInfo>, String> {
+    let broadcaster = broadcaster_id(&auth).await?;
+    let path = format!(
+        "{}&only_manageable_rewards=true",
+        rewards_path(&broadcaster, None)
+    );
+    let resp = helix_request(&auth, Method::GET, &path, None).await?;
+    let live = serde_json::from_str::<HelixRewards>(&resp)
+        .map_err(|e| e.to_string())?
+        .data;
+    let rewards = settings.load()?.unwrap_or_default().rewards;
+    Ok(rewards
+        .into_iter()
+        .map(|r| {
+            let found = live.iter().find(|l| l.id == r.reward_id);
+            RewardInfo::new(r, found)
+        })
+        .collect())
+}
+
+/// Create a reward on Twitch, record it and tie it to `feature`.
+///
+/// `target` is the suggestion box instance id or the sound to play.
+/// Suggestion box rewards ask for viewer input unless the spec says otherwise.
+#[tauri::command]
+pub async fn create_managed_reward(
+    feature: RewardFeature,
+    target: Option<String>,
+    mut spec: RewardSpec,
+    app: AppHandle,
+    auth: tauri::State<'_, Arc<AuthState>>,
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<RewardInfo, String> {
+    if spec.title.as_deref().is_none_or(|t| t.trim().is_empty()) {
+        return Err("A reward needs a title".into());
+    }
+    if spec.cost.is_none_or(|c| c == 0) {
+        return Err("A reward needs a cost of at least 1".into());
+    }
+    validate_target(
+        &settings.load()?.unwrap_or_default(),
+        feature,
+        target.as_deref(),
+    )?;
+    if feature == RewardFeature::SuggestionBox {
+        spec.is_user_input_required.get_or_insert(true);
+    }
+
+    let broadcaster = broadcaster_id(&auth).await?;
+    let resp = helix_request(
+        &auth,
+        Method::POST,
+        &rewards_path(&broadcaster, None),
+        Some(&spec.to_body()),
+    )
+    .await?;
+    let live = parse_one(&resp)?;
+    let reward = ManagedReward {
+        reward_id: live.id.clone(),
+        feature,
+        target,
+        title: live.title.clone(),
+    };
+
+    let linked = update_settings(&app, &settings, |s| {
+        link(s, &reward)?;
+        s.rewards.push(reward.clone());
+        Ok(())
+    });
+    if let Err(e) = linked {
+        // Don't leave an orphaned reward on the channel
+        let path = rewards_path(&broadcaster, Some(&reward.reward_id));
+        if let Err(del) = helix_request(&auth, Method::DELETE, &path, None).await {
+            warn!(
+                "[rewards] failed to remove orphaned reward {}: {del}",
+                reward.reward_id
+            );
+        }
+        return Err(e);
+    }
+    info!(
+        "[rewards] created '{}' ({}) for {:?}",
+        reward.title, reward.reward_id, feature
+    );
+    Ok(RewardInfo::new(reward, Some(&live)))
+}
+
+/// Change a managed reward's title, cost, prompt or limits.
+#[tauri::command]
+pub async fn update_managed_reward(
+    reward_id: String,
+    spec: RewardSpec,
+    app: AppHandle,
+    auth: tauri::State<'_, Arc<AuthState>>,
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<RewardInfo, String> {
+    let mut reward = managed(&settings, &reward_id)?;
+    let live = patch_reward(&auth, &reward_id, &spec.to_body()).await?;
+    if live.title != reward.title {
+        reward.title = live.title.clone();
+        update_settings(&app, &settings, |s| {
+            if let Some(r) = s.rewards.iter_mut().find(|r| r.reward_id == reward_id) {
+                r.title = live.title.clone();
+            }
+            Ok(())
+        })?;
+    }
+    info!("[rewards] updated {reward_id}");
+    Ok(RewardInfo::new(reward, Some(&live)))
+}
+
+/// Pause or resume redemptions. A paused reward stays visible to viewers.
+#[tauri::command]
+pub async fn set_managed_reward_paused(
+    reward_id: String,
+    paused: bool,
+    auth: tauri::State<'_, Arc<AuthState>>,
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<RewardInfo, String> {
+    let reward = managed(&settings, &reward_id)?;
+    let body = serde_json::json!({ "is_paused": paused });
+    let live = patch_reward(&auth, &reward_id, &body).await?;
+    info!("[rewards] {reward_id} paused={paused}");
+    Ok(RewardInfo::new(reward, Some(&live)))
+}
+
+/// Show or hide the reward on the channel.
+#[tauri::command]
+pub async fn set_managed_reward_enabled(
+    reward_id: String,
+    enabled: bool,
+    auth: tauri::State<'_, Arc<AuthState>>,
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<RewardInfo, String> {
+    let reward = managed(&settings, &reward_id)?;
+    let body = serde_json::json!({ "is_enabled": enabled });
+    let live = patch_reward(&auth, &reward_id, &body).await?;
+    info!("[rewards] {reward_id} enabled={enabled}");
+    Ok(RewardInfo::new(reward, Some(&live)))
+}
+
+/// Delete the reward on Twitch and unlink it from its feature. A reward that
+/// is already gone on Twitch is still removed locally.
+#[tauri::command]
+pub async fn delete_managed_reward(
+    reward_id: String,
+    app: AppHandle,
+    auth: tauri::State<'_, Arc<AuthState>>,
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<(), String> {
+    let reward = managed(&settings, &reward_id)?;
+    let broadcaster = broadcaster_id(&auth).await?;
+    let path = rewards_path(&broadcaster, Some(&reward_id));
+    if let Err(e) = helix_request(&auth, Method::DELETE, &path, None).await {
+        if !e.is_not_found() {
+            return Err(e.into());
+        }
+        warn!("[rewards] {reward_id} was already deleted on Twitch");
+    }
+    update_settings(&app, &settings, |s| {
+        unlink(s, &reward);
+        s.rewards.retain(|r| r.reward_id != reward_id);
+        Ok(())
+    })?;
+    info!("[rewards] deleted '{}' ({reward_id})", reward.title);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn settings_with_box() -> Settings {
+        Settings::from_value(json!({
+            "_v": crate::settings::SETTINGS_VERSION,
+            "overlay": { "instances": [{
+                "instanceId": "suggestion-box-1", "typeId": "suggestion-box",
+                "x": 0, "y": 0, "width": 100, "height": 100, "visible": true,
+                "config": { "rewardId": "" }
+            }]}
+        }))
+        .unwrap()
+    }
+
+    fn reward(feature: RewardFeature, target: &str) -> ManagedReward {
+        ManagedReward {
+            reward_id: "r1".into(),
+            feature,
+            target: Some(target.into()),
+            title: "Suggest".into(),
+        }
+    }
+
+    #[test]
+    fn links_and_unlinks_suggestion_box() {
+        let mut s = settings_with_box();
+        let r = reward(RewardFeature::SuggestionBox, "suggestion-box-1");
+        link(&mut s, &r).unwrap();
+        let config = |s: &Settings| s.overlay.as_ref().unwrap().instances[0].config.clone();
+        assert_eq!(config(&s).unwrap()["rewardId"], "r1");
+
+        unlink(&mut s, &r);
+        assert_eq!(config(&s).unwrap()["rewardId"], "");
+
+        let missing = reward(RewardFeature::SuggestionBox, "nope");
+        assert!(validate_target(&s, missing.feature, missing.target.as_deref()).is_err());
+    }
+
+    #[test]
+    fn links_sound_mapping_and_builds_body() {
+        let mut s = Settings::default();
+        let r = reward(RewardFeature::Sound, "chime");
+        link(&mut s, &r).unwrap();
+        let mappings = s.overlay.as_ref().unwrap().sound_mappings.clone().unwrap();
+        assert_eq!(mappings["reward:r1"].sound, "chime");
+        unlink(&mut s, &r);
+        assert!(s.overlay.unwrap().sound_mappings.unwrap().is_empty());
+
+        let body = RewardSpec {
+            title: Some(" Hydrate ".into()),
+            max_per_stream: Some(0),
+            ..Default::default()
+        }
+        .to_body();
+        assert_eq!(
+            body,
+            json!({ "title": "Hydrate", "is_max_per_stream_enabled": false, "max_per_stream": 0 })
+        );
+    }
+}
@@ -0,0 +1,500 @@
+use crate::clips;
+use crate::events::{ChannelEvent, ChannelEventType};
+use crate::markers;
+use crate::persist;
+use crate::presets;
+use chrono::{Datelike, Local, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use tracing::{info, warn};
+
+/// Emitted to every window when automation (or a manual request) switches preset.
+pub const SCENE_SWITCHED_EVENT: &str = "scene-switched";
+
+/// How often scheduled rules are checked.
+const SCHEDULE_TICK: Duration = Duration::from_secs(15);
+
+/// Badge set ids allowed to trigger chat command rules.
+const PRIVILEGED_BADGES: &[&str] = &["broadcaster", "moderator"];
+
+/// What causes a scene rule to fire.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum SceneTrigger {
+    StreamOnline,
+    StreamOffline,
+    /// Local wall-clock time, `HH:MM`. An empty `days` list means every day.
+    #[serde(rename_all = "camelCase")]
+    Schedule {
+        at: String,
+        #[serde(default)]
+        days: Vec<Weekday>,
+    },
+    /// A chat message from a moderator or the broadcaster starting with `command`.
+    ChatCommand {
+        command: String,
+    },
+    /// A global shortcut such as `Ctrl+Shift+1`.
+    Hotkey {
+        shortcut: String,
+    },
+}
+
+/// Switches to `preset` whenever `trigger` fires.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SceneRule {
+    pub id: String,
+    #[serde(default = "enabled_default")]
+    pub enabled: bool,
+    pub trigger: SceneTrigger,
+    /// Slug of the preset to load.
+    pub preset: String,
+}
+
+fn enabled_default() -> bool {
+    true
+}
+
+/// Payload of [`SCENE_SWITCHED_EVENT`].
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SceneSwitched {
+    preset: String,
+    /// Human-readable cause, e.g. `streamOnline` or `hotkey:Ctrl+Shift+1`.
+    trigger: String,
+    data: Value,
+}
+
+/// Managed state for the scene automation engine.
+pub struct SceneState {
+    path: PathBuf,
+    rules: Mutex<Vec<SceneRule>>,
+    /// Shortcuts currently registered on behalf of hotkey rules.
+    hotkeys: Mutex<Vec<String>>,
+    /// Date each schedule rule last fired, so it fires once per day.
+    fired: Mutex<HashMap<String, chrono::NaiveDate>>,
+    /// Slug of the preset most recently switched to.
+    active: Mutex<Option<String>>,
+}
+
+impl SceneState {
+    pub fn new(data_dir: PathBuf) -> Self {
+        let path = data_dir.join("scenes.json");
+        let rules = persist::read_json(&path).and_then(|value| {
+            value
+                .map(|v| serde_json::from_value(v).map_err(|e| e.to_string()))
+                .transpose()
+        });
+        let rules = match rules {
+            Ok(rules) => rules.unwrap_or_default(),
+            Err(e) => {
+                warn!("[scenes] ignoring unreadable rules: {e}");
+                Vec::new()
+            }
+        };
+        Self {
+            path,
+            rules: Mutex::new(rules),
+            hotkeys: Mutex::new(Vec::new()),
+            fired: Mutex::new(HashMap::new()),
+            active: Mutex::new(None),
+        }
+    }
+
+    /// Slug of the preset most recently switched to.
+    pub fn active(&self) -> Option<String> {
+        self.active.lock().map(|a| a.clone()).unwrap_or_default()
+    }
+
+    /// Shortcuts registered for hotkey rules, for conflict checks elsewhere.
+    pub fn registered_hotkeys(&self) -> Vec<String> {
+        self.hotkeys.lock().map(|h| h.clone()).unwrap_or_default()
+    }
+
+    fn rules(&self) -> Vec<SceneRule> {
+        self.rules.lock().map(|r| r.clone()).unwrap_or_default()
+    }
+}
+
+/// Check a rule set for problems that would stop it from ever firing.
+fn validate_rules(rules: &[SceneRule]) -> Result<(), String> {
+    let mut ids = HashSet::new();
+    let mut shortcuts = HashSet::new();
+    for rule in rules {
+        if rule.id.trim().is_empty() {
+            return Err("Scene rule id must not be empty".into());
+        }
+        if !ids.insert(rule.id.as_str()) {
+            return Err(format!("Duplicate scene rule id '{}'", rule.id));
+        }
+        presets::load_preset(rule.preset.clone())
+            .map_err(|e| format!("Scene rule '{}': {e}", rule.id))?;
+        match &rule.trigger {
+            SceneTrigger::Schedule { at, .. } => {
+                NaiveTime::parse_from_str(at, "%H:%M").map_err(|_| {
+                    format!("Scene rule '{}': time must be HH:MM, got '{at}'", rule.id)
+                })?;
+            }
+            SceneTrigger::ChatCommand { command } => {
+                if command.trim().is_empty() || command.contains(char::is_whitespace) {
+                    return Err(format!(
+                        "Scene rule '{}': chat command must be a single word",
+                        rule.id
+                    ));
+                }
+            }
+            SceneTrigger::Hotkey { shortcut } => {
+                let parsed: Shortcut = shortcut
+                    .parse()
+                    .map_err(|e| format!("Scene rule '{}': {e}", rule.id))?;
+                if !shortcuts.insert(parsed.id()) {
+                    return Err(format!(
+                        "Scene rule '{}': shortcut '{shortcut}' is used by another rule",
+                        rule.id
+                    ));
+                }
+            }
+            SceneTrigger::StreamOnline | SceneTrigger::StreamOffline => {}
+        }
+    }
+    Ok(())
+}
+
+/// Load `preset` and broadcast it to every window.
+pub fn switch_to(app: &tauri::AppHandle, preset: &str, trigger: &str) -> Result<(), String> {
+    let contents = presets::load_preset(preset.to_string())?;
+    let data = presets::validate_preset(&contents)?;
+    // Set before announcing, so listeners such as the tray see the new preset
+    if let Ok(mut active) = app.state::<SceneState>().active.lock() {
+        *active = Some(preset.to_string());
+    }
+    app.emit(
+        SCENE_SWITCHED_EVENT,
+        SceneSwitched {
+            preset: preset.to_string(),
+            trigger: trigger.to_string(),
+            data,
+        },
+    )
+    .map_err(|e| e.to_string())?;
+    info!("[scenes] switched to '{preset}' ({trigger})");
+    Ok(())
+}
+
+/// Fire the first enabled rule matching `matches`, if any.
+fn fire_first(app: &tauri::AppHandle, trigger: &str, matches: impl Fn(&SceneTrigger) -> bool) {
+    let rule = app
+        .state::<SceneState>()
+        .rules()
+        .into_iter()
+        .find(|r| r.enabled && matches(&r.trigger));
+    if let Some(rule) = rule {
+        if let Err(e) = switch_to(app, &rule.preset, trigger) {
+            warn!("[scenes] rule '{}' failed: {e}", rule.id);
+        }
+    }
+}
+
+/// Whether a chat event was sent by a moderator or the broadcaster.
+fn is_privileged(event: &ChannelEvent) -> bool {
+    event
+        .data
+        .get("badges")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|b| b.get("setId").and_then(Value::as_str))
+        .any(|set| PRIVILEGED_BADGES.contains(&set))
+}
+
+/// The arguments of a chat message starting with `command`, or `None` if it doesn't.
+pub fn command_args<'a>(text: &'a str, command: &str) -> Option<&'a str> {
+    let text = text.trim();
+    let rest = match text.split_once(char::is_whitespace) {
+        Some((word, rest)) => word.eq_ignore_ascii_case(command).then_some(rest),
+        None => text.eq_ignore_ascii_case(command).then_some(""),
+    }?;
+    Some(rest.trim())
+}
+
+/// Rate limit for chat commands that start slow Twitch work: one runs at a
+/// time, and the next may not start until `cooldown` after the last began.
+pub struct CommandGate {
+    cooldown: Duration,
+    state: Mutex<GateState>,
+}
+
+struct GateState {
+    running: bool,
+    last: Option<Instant>,
+}
+
+impl CommandGate {
+    pub const fn new(cooldown: Duration) -> Self {
+        Self {
+            cooldown,
+            state: Mutex::new(GateState {
+                running: false,
+                last: None,
+            }),
+        }
+    }
+
+    /// Claim the gate at `now`. Returns `false` while a command runs or cools down.
+    pub fn try_start(&self, now: Instant) -> bool {
+        let Ok(mut state) = self.state.lock() else {
+            return false;
+        };
+        let cooling = state
+            .last
+            .is_some_and(|last| now.saturating_duration_since(last) < self.cooldown);
+        if state.running || cooling {
+            return false;
+        }
+        state.running = true;
+        state.last = Some(now);
+        true
+    }
+
+    /// Release the gate once the command has finished, successfully or not.
+    pub fn finish(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.running = false;
+        }
+    }
+}
+
+/// Register global shortcuts for the enabled hotkey rules, replacing any
+/// registered previously. Returns a message per shortcut that failed.
+fn register_hotkeys(app: &tauri::AppHandle) -> Vec<String> {
+    let state = app.state::<SceneState>();
+    let shortcuts = app.global_shortcut();
+    let Ok(mut registered) = state.hotkeys.lock() else {
+        return vec!["Scene hotkey state is unavailable".into()];
+    };
+
+    for shortcut in registered.drain(..) {
+        if let Err(e) = shortcuts.unregister(shortcut.as_str()) {
+            warn!("[scenes] failed to unregister {shortcut}: {e}");
+        }
+    }
+
+    let mut failures = Vec::new();
+    for rule in state.rules().into_iter().filter(|r| r.enabled) {
+        let SceneTrigger::Hotkey { shortcut } = &rule.trigger else {
+            continue;
+        };
+        let preset = rule.preset.clone();
+        let trigger = format!("hotkey:{shortcut}");
+        let result = shortcuts.on_shortcut(shortcut.as_str(), move |app, _, event| {
+            if event.state == ShortcutState::Pressed {
+                if let Err(e) = switch_to(app, &preset, &trigger) {
+                    warn!("[scenes] hotkey switch failed: {e}");
+                }
+            }
+        });
+        match result {
+            Ok(()) => registered.push(shortcut.clone()),
+            Err(e) => {
+                warn!("[scenes] failed to register {shortcut}: {e}");
+                failures.push(format!("{shortcut}: {e}"));
+            }
+        }
+    }
+    failures
+}
+
+/// Fire schedule rules whose time has come. Each rule fires at most once a day.
+fn check_schedule(app: &tauri::AppHandle) {
+    let now = Local::now();
+    let today = now.date_naive();
+    let current = now.format("%H:%M").to_string();
+    let state = app.state::<SceneState>();
+
+    let due: Vec<SceneRule> = {
+        let Ok(mut fired) = state.fired.lock() else {
+            return;
+        };
+        state
+            .rules()
+            .into_iter()
+            .filter(|rule| {
+                let SceneTrigger::Schedule { at, days } = &rule.trigger else {
+                    return false;
+                };
+                rule.enabled
+                    && *at == current
+                    && (days.is_empty() || days.contains(&now.weekday()))
+                    && fired.insert(rule.id.clone(), today) != Some(today)
+            })
+            .collect()
+    };
+    for rule in due {
+        if let Err(e) = switch_to(app, &rule.preset, "schedule") {
+            warn!("[scenes] rule '{}' failed: {e}", rule.id);
+        }
+    }
+}
+
+/// Register hotkeys and start the schedule loop. Called once during setup.
+pub fn start(app: &tauri::AppHandle) {
+    for failure in register_hotkeys(app) {
+        warn!("[scenes] {failure}");
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            check_schedule(&app);
+            tokio::time::sleep(SCHEDULE_TICK).await;
+        }
+    });
+}
+
+/// Return the configured scene rules.
+#[tauri::command]
+pub fn get_scene_rules(state: tauri::State<'_, SceneState>) -> Result<Vec<SceneRule>, String> {
+    Ok(state.rules())
+}
+
+/// Replace the scene rules. Returns a message per hotkey that could not be
+/// registered (for example because another app already owns it).
+#[tauri::command]
+pub fn set_scene_rules(
+    rules: Vec<SceneRule>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SceneState>,
+) -> Result<Vec<String>, String> {
+    validate_rules(&rules)?;
+    persist::write_json(&state.path, &rules)?;
+    *state.rules.lock().map_err(|e| e.to_string())? = rules;
+    info!("[scenes] saved {} rules", state.rules().len());
+    Ok(register_hotkeys(&app))
+}
+
+/// Switch to a preset immediately, as if a rule had fired.
+#[tauri::command]
+pub fn switch_scene(preset: String, app: tauri::AppHandle) -> Result<(), String> {
+    switch_to(&app, &preset, "manual")
+}
+
+/// Return the slug of the preset most recently switched to, if any.
+#[tauri::command]
+pub fn active_scene(state: tauri::State<'_, SceneState>) -> Result<Option<String>, String> {
+    Ok(state.active.lock().map_err(|e| e.to_string())?.clone())
+}
+
+/// Feed a channel event from the frontend bus into the automation engine.
+#[tauri::command]
+pub fn scene_event(event: String, app: tauri::AppHandle) -> Result<(), String> {
+    let event = ChannelEvent::parse(&event)?;
+    // Replayed and simulated events must not switch scenes or hit Helix
+    if event.is_simulated() {
+        return Ok(());
+    }
+    match event.kind {
+        ChannelEventType::StreamOnline => {
+            fire_first(&app, "streamOnline", |t| *t == SceneTrigger::StreamOnline)
+        }
+        ChannelEventType::StreamOffline => {
+            fire_first(&app, "streamOffline", |t| *t == SceneTrigger::StreamOffline)
+        }
+        ChannelEventType::Chat if is_privileged(&event) => {
+            let text = event.data.get("text").and_then(Value::as_str).unwrap_or("");
+            if markers::chat_command(&app, text) || clips::chat_command(&app, text) {
+                return Ok(());
+            }
+            let Some(word) = text.split_whitespace().next() else {
+                return Ok(());
+            };
+            let trigger = format!("chat:{word}");
+            fire_first(
+                &app,
+                &trigger,
+                |t| matches!(t, SceneTrigger::ChatCommand { command } if command.eq_ignore_ascii_case(word)),
+            );
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn command_gate_allows_one_command_per_cooldown() {
+        let gate = CommandGate::new(Duration::from_secs(10));
+        let start = Instant::now();
+        assert!(gate.try_start(start));
+        // Still running, even once the cooldown is over
+        assert!(!gate.try_start(start + Duration::from_secs(1)));
+        assert!(!gate.try_start(start + Duration::from_secs(20)));
+        gate.finish();
+        assert!(!gate.try_start(start + Duration::from_secs(9)));
+        assert!(gate.try_start(start + Duration::from_secs(20)));
+    }
+
+    fn chat(badges: Value) -> ChannelEvent {
+        ChannelEvent::parse(
+            &json!({
+                "type": "chat",
+                "timestamp": 1_700_000_000_000i64,
+                "data": { "text": "!brb now", "badges": badges },
+            })
+            .to_string(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn chat_commands_require_mod_or_broadcaster() {
+        assert!(is_privileged(&chat(
+            json!([{ "setId": "moderator", "versionId": "1" }])
+        )));
+        assert!(is_privileged(&chat(
+            json!([{ "setId": "broadcaster", "versionId": "1" }])
+        )));
+        assert!(!is_privileged(&chat(
+            json!([{ "setId": "subscriber", "versionId": "12" }])
+        )));
+        assert!(!is_privileged(&chat(Value::Null)));
+    }
+
+    #[test]
+    fn command_args_match_whole_first_word() {
+        assert_eq!(command_args("!marker", "!marker"), Some(""));
+        assert_eq!(
+            command_args("  !MARKER  big play ", "!marker"),
+            Some("big play")
+        );
+        assert_eq!(command_args("!markers", "!marker"), None);
+        assert_eq!(command_args("nice !marker", "!marker"), None);
+    }
+
+    #[test]
+    fn triggers_round_trip_through_json() {
+        let trigger: SceneTrigger = serde_json::from_value(
+            json!({ "kind": "schedule", "at": "19:55", "days": ["Mon", "Fri"] }),
+        )
+        .unwrap();
+        assert_eq!(
+            trigger,
+            SceneTrigger::Schedule {
+                at: "19:55".into(),
+                days: vec![Weekday::Mon, Weekday::Fri],
+            }
+        );
+        let online: SceneTrigger =
+            serde_json::from_value(json!({ "kind": "streamOnline" })).unwrap();
+        assert_eq!(online, SceneTrigger::StreamOnline);
+    }
+}
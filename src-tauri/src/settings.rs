@@ -1,11 +1,431 @@
-use serde_json::Value;
+use crate::persist;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
-use tracing::{error, info};
+use std::sync::{Mutex, MutexGuard};
+use tauri::{Emitter, EventTarget};
+use tracing::{error, info, warn};
+
+/// Current settings schema version, stored as `_v` in `settings.json`.
+pub const SETTINGS_VERSION: u32 = 3;
+
+/// Number of settings snapshots kept in the rolling history.
+const MAX_SNAPSHOTS: usize = 30;
+
+/// Minimum time between automatic snapshots. Saves are debounced to every
+/// change in edit mode, so snapshotting each one would flood the history.
+const SNAPSHOT_INTERVAL_MS: i64 = 60_000;
+
+/// Ordered migrations; `MIGRATIONS[n]` upgrades a document from version `n` to `n + 1`.
+const MIGRATIONS: [fn(&mut Map<String, Value>); SETTINGS_VERSION as usize] =
+    [migrate_v0_to_v1, migrate_v1_to_v2, migrate_v2_to_v3];
+
+/// Typed contents of `settings.json`. Unknown fields are kept in `extra` so
+/// that newer frontends can add settings without the backend dropping them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Settings {
+    #[serde(rename = "_v", default)]
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overlay: Option<OverlaySettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub twitch: Option<TwitchSettings>,
+    /// Channel-point rewards created by the app. Owned by the backend: the
+    /// frontend never sends this key and writes without it keep the stored list.
+    #[serde(
+        default,
+        deserialize_with = "lenient_vec",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub rewards: Vec<ManagedReward>,
+    /// Global hotkey bindings, also backend-owned. `None` means the defaults.
+    #[serde(
+        default,
+        deserialize_with = "lenient_opt_vec",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub hotkeys: Option<Vec<HotkeyBinding>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Overlay layout and styling. Mirrors `PersistedSettings.overlay` in
+/// `src/stores/persistence.ts`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlaySettings {
+    #[serde(default, deserialize_with = "lenient_vec")]
+    pub instances: Vec<WidgetInstance>,
+    #[serde(default)]
+    pub file_logging: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub twitch_colours: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub presence_threshold: Option<f64>,
+    #[serde(
+        default,
+        deserialize_with = "lenient_opt_vec",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub commands: Option<Vec<ChatCommand>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sound_enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sound_volume: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sound_mappings: Option<HashMap<String, SoundMapping>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_monitors: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub border_radius: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub panel_width: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub panel_bg_colour: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub panel_align_h: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub panel_align_v: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub global_font: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub widget_bg_colour: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub widget_bg_opacity: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_bg_opacity: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub widget_text_colour: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub widget_live_bg: Option<bool>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A placed widget. Mirrors `WidgetInstance` in `src/stores/overlay.ts`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WidgetInstance {
+    pub instance_id: String,
+    pub type_id: String,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub visible: bool,
+    #[serde(default)]
+    pub locked: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<Map<String, Value>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatCommand {
+    pub trigger: String,
+    pub response: String,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SoundMapping {
+    pub enabled: bool,
+    /// A built-in sound key (e.g. "chime") or an absolute file path for custom sounds.
+    pub sound: String,
+}
+
+/// Overlay feature a managed channel-point reward drives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RewardFeature {
+    /// Redemptions become suggestions in the target suggestion box instance.
+    SuggestionBox,
+    /// Redemptions play the target sound.
+    Sound,
+    /// Redemptions are read out by text-to-speech.
+    Tts,
+}
+
+/// A custom channel-point reward created and owned by the app.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManagedReward {
+    pub reward_id: String,
+    pub feature: RewardFeature,
+    /// Suggestion box instance id, or sound key/path, the reward is tied to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    pub title: String,
+}
+
+/// What a global hotkey does.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum HotkeyAction {
+    ToggleEditMode,
+    ToggleOverlay,
+    #[serde(rename_all = "camelCase")]
+    LoadPreset {
+        name: String,
+    },
+    /// A built-in sound key or an absolute file path.
+    #[serde(rename_all = "camelCase")]
+    PlaySound {
+        sound: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    StreamMarker {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+    },
+    ClearChat,
+    CreateClip,
+}
+
+/// A key combination bound to an action.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HotkeyBinding {
+    pub id: String,
+    /// Accelerator string, e.g. `Ctrl+Shift+I`.
+    pub shortcut: String,
+    pub action: HotkeyAction,
+    #[serde(default = "enabled_default")]
+    pub enabled: bool,
+}
+
+fn enabled_default() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TwitchSettings {
+    #[serde(default)]
+    pub channel: String,
+}
+
+/// Deserialise a list, dropping (and logging) entries that don't parse so a
+/// single bad widget or command doesn't make the whole file unreadable.
+fn lenient_vec<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    Ok(lenient_opt_vec(deserializer)?.unwrap_or_default())
+}
+
+/// [`lenient_vec`] for optional lists; `null` stays `None`.
+fn lenient_opt_vec<'de, D, T>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let what = std::any::type_name::<T>()
+        .rsplit("::")
+        .next()
+        .unwrap_or("entry");
+    let items = match Value::deserialize(deserializer)? {
+        Value::Null => return Ok(None),
+        Value::Array(items) => items,
+        other => {
+            warn!("[settings] expected a list of {what}, got {other}; ignoring it");
+            return Ok(None);
+        }
+    };
+    let parsed = items
+        .into_iter()
+        .filter_map(|item| match serde_json::from_value(item.clone()) {
+            Ok(parsed) => Some(parsed),
+            Err(e) => {
+                warn!("[settings] dropping invalid {what} {item}: {e}");
+                None
+            }
+        })
+        .collect();
+    Ok(Some(parsed))
+}
+
+/// Default position and size of a widget type when a v0 document has none.
+/// Mirrors `defaults` in `src/widgets/registry.ts`.
+pub(crate) fn widget_defaults(type_id: &str) -> (f64, f64, f64, f64) {
+    match type_id {
+        "chat" => (8.0, 840.0, 416.0, 296.0),
+        "viewer-count" => (1192.0, 24.0, 200.0, 64.0),
+        "follower-alerts" => (1112.0, 1000.0, 352.0, 120.0),
+        "event-feed" => (2256.0, 896.0, 296.0, 272.0),
+        "custom-text" => (1024.0, 96.0, 544.0, 48.0),
+        "chat-presence" => (2392.0, 48.0, 152.0, 568.0),
+        "follow-events" => (2256.0, 648.0, 296.0, 224.0),
+        "event-log" => (8.0, 1168.0, 416.0, 216.0),
+        "raid-alerts" => (1112.0, 880.0, 352.0, 120.0),
+        "subscription-alerts" => (1112.0, 760.0, 352.0, 120.0),
+        "stream-info" => (1192.0, 96.0, 304.0, 152.0),
+        "stream-title" => (8.0, 8.0, 400.0, 48.0),
+        "suggestion-box" => (8.0, 560.0, 400.0, 260.0),
+        _ => (0.0, 0.0, 300.0, 200.0),
+    }
+}
+
+impl Settings {
+    /// Upgrade a raw settings document to [`SETTINGS_VERSION`] and parse it.
+    pub fn from_value(value: Value) -> Result<Self, String> {
+        let Value::Object(mut doc) = value else {
+            return Err("Settings must be a JSON object".into());
+        };
+
+        let version = doc.get("_v").and_then(Value::as_u64).unwrap_or(0) as u32;
+        if version > SETTINGS_VERSION {
+            return Err(format!(
+                "Settings version {version} is newer than supported version {SETTINGS_VERSION}"
+            ));
+        }
+        for (from, migrate) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+            migrate(&mut doc);
+            info!("[settings] migrated v{from} -> v{}", from + 1);
+        }
+        doc.insert("_v".into(), SETTINGS_VERSION.into());
+
+        let mut settings: Settings = serde_json::from_value(Value::Object(doc))
+            .map_err(|e| format!("Invalid settings: {e}"))?;
+        settings.normalise();
+        Ok(settings)
+    }
+
+    /// Replace the backend-owned sections with the live ones. Their state
+    /// lives outside the document (rewards on Twitch, registered shortcuts),
+    /// so an older copy must never win.
+    fn keep_backend_owned(&mut self, live: &Settings) {
+        self.rewards = live.rewards.clone();
+        self.hotkeys = live.hotkeys.clone();
+    }
+
+    /// Drop widget instances that would break the layout: empty or duplicate
+    /// ids and non-finite or negative sizes.
+    fn normalise(&mut self) {
+        let Some(overlay) = self.overlay.as_mut() else {
+            return;
+        };
+        let mut seen = HashSet::new();
+        overlay.instances.retain(|inst| {
+            let valid = !inst.instance_id.is_empty()
+                && [inst.x, inst.y, inst.width, inst.height]
+                    .iter()
+                    .all(|n| n.is_finite())
+                && inst.width >= 0.0
+                && inst.height >= 0.0
+                && seen.insert(inst.instance_id.clone());
+            if !valid {
+                warn!(
+                    "[settings] dropping invalid instance '{}'",
+                    inst.instance_id
+                );
+            }
+            valid
+        });
+    }
+}
+
+/// v0 → v1: fold the legacy split `widgetStates` record and global
+/// `customText` config into instances, and clear per-widget style overrides
+/// so they pick up the global defaults.
+fn migrate_v0_to_v1(doc: &mut Map<String, Value>) {
+    let custom_text = doc
+        .remove("customText")
+        .and_then(|c| c.get("config").cloned());
+    let Some(overlay) = doc.get_mut("overlay").and_then(Value::as_object_mut) else {
+        return;
+    };
+    let widget_states = overlay.remove("widgetStates");
+
+    for inst in instances_mut(overlay) {
+        if !inst.contains_key("visible") {
+            let old = inst
+                .get("instanceId")
+                .and_then(Value::as_str)
+                .and_then(|id| widget_states.as_ref()?.get(id))
+                .cloned()
+                .unwrap_or_default();
+            let type_id = inst.get("typeId").and_then(Value::as_str).unwrap_or("");
+            let (x, y, width, height) = widget_defaults(type_id);
+            for (key, fallback) in [
+                ("x", Value::from(x)),
+                ("y", Value::from(y)),
+                ("width", Value::from(width)),
+                ("height", Value::from(height)),
+                ("visible", Value::Bool(true)),
+            ] {
+                let value = old.get(key).cloned().unwrap_or(fallback);
+                inst.insert(key.into(), value);
+            }
+            inst.insert("locked".into(), Value::Bool(false));
+        }
+        inst.entry("locked").or_insert(Value::Bool(false));
+
+        for key in ["bgColour", "bgOpacity", "textColour"] {
+            inst.remove(key);
+        }
+
+        if let Some(config) = &custom_text {
+            let is_custom_text = inst.get("typeId").and_then(Value::as_str) == Some("custom-text");
+            if is_custom_text && !inst.contains_key("config") {
+                inst.insert("config".into(), config.clone());
+            }
+        }
+    }
+}
+
+/// v1 → v2: remove the dead per-instance `opacity`; carry `panelBgOpacity`
+/// forward as `widgetBgOpacity`.
+fn migrate_v1_to_v2(doc: &mut Map<String, Value>) {
+    let Some(overlay) = doc.get_mut("overlay").and_then(Value::as_object_mut) else {
+        return;
+    };
+    for inst in instances_mut(overlay) {
+        inst.remove("opacity");
+    }
+    if let Some(opacity) = overlay.get("panelBgOpacity").cloned() {
+        overlay.insert("widgetBgOpacity".into(), opacity);
+    }
+}
+
+/// v2 → v3: remove `liveBg` from instances.
+fn migrate_v2_to_v3(doc: &mut Map<String, Value>) {
+    let Some(overlay) = doc.get_mut("overlay").and_then(Value::as_object_mut) else {
+        return;
+    };
+    for inst in instances_mut(overlay) {
+        inst.remove("liveBg");
+    }
+}
+
+/// Iterate the instance objects of an overlay document.
+fn instances_mut(
+    overlay: &mut Map<String, Value>,
+) -> impl Iterator<Item = &mut Map<String, Value>> {
+    overlay
+        .get_mut("instances")
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object_mut)
+}
 
 /// Shared settings state managed by Tauri.
 pub struct SettingsState {
     path: PathBuf,
     chat_history_path: PathBuf,
+    history_dir: PathBuf,
+    profiles_dir: PathBuf,
+    /// Holds the name of the active profile.
+    active_profile_path: PathBuf,
+    /// Unix millis of the most recent automatic snapshot.
+    last_snapshot_at: Mutex<i64>,
+    /// Held across load-modify-save cycles so concurrent writers can't drop
+    /// each other's changes.
+    write_lock: Mutex<()>,
 }
 
 impl SettingsState {
@@ -13,55 +433,570 @@ impl SettingsState {
         Self {
             path: data_dir.join("settings.json"),
             chat_history_path: data_dir.join("ephemeral-chat-history.json"),
+            history_dir: data_dir.join("settings-history"),
+            profiles_dir: data_dir.join("profiles"),
+            active_profile_path: data_dir.join("active-profile.json"),
+            last_snapshot_at: Mutex::new(0),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Block other read-modify-write cycles until the guard is dropped.
+    pub fn lock_writes(&self) -> Result<MutexGuard<'_, ()>, String> {
+        self.write_lock.lock().map_err(|e| e.to_string())
+    }
+
+    /// Load the settings (defaults if none exist), apply `change` and save
+    /// them, all under the write lock. Returns `change`'s result and the saved
+    /// settings.
+    pub fn update<T>(
+        &self,
+        change: impl FnOnce(&mut Settings) -> Result<T, String>,
+    ) -> Result<(T, Settings), String> {
+        let _writing = self.lock_writes()?;
+        let mut settings = self.load()?.unwrap_or_default();
+        let out = change(&mut settings)?;
+        self.save(&settings)?;
+        Ok((out, settings))
+    }
+
+    /// Load and migrate the settings file. Returns `None` if none exists yet.
+    pub fn load(&self) -> Result<Option<Settings>, String> {
+        persist::read_json(&self.path)?
+            .map(Settings::from_value)
+            .transpose()
+    }
+
+    /// Persist validated settings, snapshotting the previous version first
+    /// if the last snapshot is older than [`SNAPSHOT_INTERVAL_MS`].
+    pub fn save(&self, settings: &Settings) -> Result<(), String> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let due = self
+            .last_snapshot_at
+            .lock()
+            .map(|last| now - *last >= SNAPSHOT_INTERVAL_MS)
+            .unwrap_or(true);
+        if due {
+            match self.load() {
+                Ok(Some(previous)) if previous != *settings => {
+                    if let Err(e) = self.snapshot(&previous) {
+                        warn!("[settings] snapshot failed: {e}");
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => warn!("[settings] could not read previous version: {e}"),
+            }
+        }
+        persist::write_json(&self.path, settings)
+    }
+
+    // -----------------------------------------------------------------------
+    // Snapshot history
+    // -----------------------------------------------------------------------
+
+    /// Write `settings` to the history and prune it to [`MAX_SNAPSHOTS`].
+    fn snapshot(&self, settings: &Settings) -> Result<String, String> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let id = now.to_string();
+        persist::write_json(&self.history_dir.join(format!("{id}.json")), settings)?;
+        if let Ok(mut last) = self.last_snapshot_at.lock() {
+            *last = now;
+        }
+
+        let ids = self.snapshot_ids()?;
+        for stale in ids.iter().skip(MAX_SNAPSHOTS) {
+            persist::remove(&self.history_dir.join(format!("{stale}.json")))?;
         }
+        info!("[settings] snapshot {id} saved");
+        Ok(id)
+    }
+
+    /// Snapshot ids (unix millis), newest first.
+    fn snapshot_ids(&self) -> Result<Vec<i64>, String> {
+        let entries = match std::fs::read_dir(&self.history_dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.to_string()),
+        };
+        let mut ids: Vec<i64> = entries
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? != "json" {
+                    return None;
+                }
+                path.file_stem()?.to_str()?.parse().ok()
+            })
+            .collect();
+        ids.sort_unstable_by(|a, b| b.cmp(a));
+        Ok(ids)
+    }
+
+    fn load_snapshot(&self, id: &str) -> Result<Settings, String> {
+        let id: i64 = id
+            .parse()
+            .map_err(|_| format!("Invalid snapshot id: {id}"))?;
+        let path = self.history_dir.join(format!("{id}.json"));
+        let value = persist::read_json(&path)?.ok_or(format!("Snapshot {id} not found"))?;
+        Settings::from_value(value)
+    }
+
+    /// Make snapshot `id` the current settings, keeping the live rewards and hotkeys.
+    fn restore_snapshot(&self, id: &str) -> Result<Settings, String> {
+        let _writing = self.lock_writes()?;
+        let mut restored = self.load_snapshot(id)?;
+        let current = self.load()?;
+        if let Some(current) = &current {
+            self.snapshot(current)?;
+        }
+        restored.keep_backend_owned(&current.unwrap_or_default());
+        persist::write_json(&self.path, &restored)?;
+        info!("[settings] restored snapshot {id}");
+        Ok(restored)
+    }
+
+    // -----------------------------------------------------------------------
+    // Profiles
+    //
+    // `settings.json` always holds the live document of the active profile.
+    // Inactive profiles are settings documents in `profiles/` without the
+    // backend-owned rewards and hotkeys, which are global and stay in
+    // `settings.json` whichever profile is active.
+    // -----------------------------------------------------------------------
+
+    fn profile_path(&self, name: &str) -> PathBuf {
+        self.profiles_dir.join(format!("{name}.json"))
+    }
+
+    fn active_profile(&self) -> Result<String, String> {
+        Ok(persist::read_json(&self.active_profile_path)?
+            .and_then(|v| v.get("active")?.as_str().map(String::from))
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_string()))
+    }
+
+    fn profile_names(&self) -> Result<Vec<String>, String> {
+        let mut names = vec![self.active_profile()?];
+        if let Ok(entries) = std::fs::read_dir(&self.profiles_dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().is_some_and(|ext| ext == "json") {
+                    if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                        names.push(stem.to_string());
+                    }
+                }
+            }
+        }
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+
+    fn load_profile(&self, name: &str) -> Result<Settings, String> {
+        validate_profile_name(name)?;
+        if name == self.active_profile()? {
+            return Ok(self.load()?.unwrap_or_default());
+        }
+        let value = persist::read_json(&self.profile_path(name))?
+            .ok_or(format!("Profile '{name}' not found"))?;
+        Settings::from_value(value)
+    }
+
+    fn ensure_new_profile(&self, name: &str) -> Result<(), String> {
+        validate_profile_name(name)?;
+        if self.profile_names()?.iter().any(|n| n == name) {
+            return Err(format!("Profile '{name}' already exists"));
+        }
+        Ok(())
+    }
+
+    /// Write an inactive profile, leaving out the global sections.
+    fn store_profile(&self, name: &str, settings: &Settings) -> Result<(), String> {
+        let mut profile = settings.clone();
+        profile.keep_backend_owned(&Settings::default());
+        persist::write_json(&self.profile_path(name), &profile)
+    }
+
+    /// Fold rewards stored in a profile written before rewards became global
+    /// into the live list, so they stay visible to the reward commands.
+    fn adopt_profile_rewards(live: &mut Settings, profile: &Settings) {
+        for reward in &profile.rewards {
+            if !live.rewards.iter().any(|r| r.reward_id == reward.reward_id) {
+                live.rewards.push(reward.clone());
+            }
+        }
+    }
+
+    fn create_profile(&self, name: &str) -> Result<(), String> {
+        self.ensure_new_profile(name)?;
+        let current = self.load()?.unwrap_or_default();
+        let profile = Settings {
+            version: SETTINGS_VERSION,
+            overlay: Some(OverlaySettings::default()),
+            twitch: current.twitch,
+            ..Settings::default()
+        };
+        self.store_profile(name, &profile)?;
+        info!("[settings] created profile '{name}'");
+        Ok(())
+    }
+
+    fn clone_profile(&self, source: &str, name: &str) -> Result<(), String> {
+        validate_profile_name(source)?;
+        self.ensure_new_profile(name)?;
+        let profile = self.load_profile(source)?;
+        self.store_profile(name, &profile)?;
+        info!("[settings] cloned profile '{source}' to '{name}'");
+        Ok(())
+    }
+
+    fn switch_profile(&self, name: &str) -> Result<Settings, String> {
+        validate_profile_name(name)?;
+        let active = self.active_profile()?;
+        if name == active {
+            return self.load_profile(name);
+        }
+        let mut target = self.load_profile(name)?;
+
+        let _writing = self.lock_writes()?;
+        let current = self.load()?.unwrap_or_default();
+        let mut live = current.clone();
+        Self::adopt_profile_rewards(&mut live, &target);
+        target.keep_backend_owned(&live);
+
+        self.store_profile(&active, &current)?;
+        self.save(&target)?;
+        persist::write_json(
+            &self.active_profile_path,
+            &serde_json::json!({ "active": name }),
+        )?;
+        persist::remove(&self.profile_path(name))?;
+        info!("[settings] switched profile '{active}' -> '{name}'");
+        Ok(target)
+    }
+
+    fn delete_profile(&self, name: &str) -> Result<(), String> {
+        validate_profile_name(name)?;
+        if name == self.active_profile()? {
+            return Err("Cannot delete the active profile; switch to another one first".into());
+        }
+        if let Ok(profile) = self.load_profile(name) {
+            if !profile.rewards.is_empty() {
+                self.update(|live| {
+                    Self::adopt_profile_rewards(live, &profile);
+                    Ok(())
+                })?;
+            }
+        }
+        persist::remove(&self.profile_path(name))?;
+        info!("[settings] deleted profile '{name}'");
+        Ok(())
     }
 }
 
+/// Profile used when no profile has been created or switched to yet.
+const DEFAULT_PROFILE: &str = "default";
+
+/// Profile names become file names, so only allow a safe slug alphabet.
+fn validate_profile_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid profile name '{name}': use 1-64 lowercase letters, digits, '-' or '_'"
+        ))
+    }
+}
+
+/// Available profiles and which one is live.
+#[derive(Serialize)]
+pub struct ProfilesInfo {
+    active: String,
+    profiles: Vec<String>,
+}
+
+/// A settings snapshot in the rolling history.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotInfo {
+    id: String,
+    /// Unix millis when the snapshot was taken.
+    timestamp: i64,
+    widget_count: usize,
+}
+
+/// Identifies a widget instance in a diff.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WidgetRef {
+    instance_id: String,
+    type_id: String,
+}
+
+/// A widget whose position or size differs between two snapshots.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WidgetChange {
+    instance_id: String,
+    type_id: String,
+    /// `[x, y, width, height]` before and after.
+    from: [f64; 4],
+    to: [f64; 4],
+}
+
+/// Differences between two settings documents.
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsDiff {
+    added: Vec<WidgetRef>,
+    removed: Vec<WidgetRef>,
+    moved: Vec<WidgetChange>,
+    resized: Vec<WidgetChange>,
+    /// Other instance properties (visibility, lock, config, styling) changed.
+    modified: Vec<WidgetRef>,
+    /// Top-level overlay settings whose value changed, by key.
+    changed_settings: Vec<String>,
+}
+
+pub fn diff_settings(from: &Settings, to: &Settings) -> SettingsDiff {
+    let empty = OverlaySettings::default();
+    let from = from.overlay.as_ref().unwrap_or(&empty);
+    let to = to.overlay.as_ref().unwrap_or(&empty);
+    let widget_ref = |inst: &WidgetInstance| WidgetRef {
+        instance_id: inst.instance_id.clone(),
+        type_id: inst.type_id.clone(),
+    };
+    let rect = |inst: &WidgetInstance| [inst.x, inst.y, inst.width, inst.height];
+
+    let before: HashMap<&str, &WidgetInstance> = from
+        .instances
+        .iter()
+        .map(|i| (i.instance_id.as_str(), i))
+        .collect();
+    let after: HashMap<&str, &WidgetInstance> = to
+        .instances
+        .iter()
+        .map(|i| (i.instance_id.as_str(), i))
+        .collect();
+
+    let mut diff = SettingsDiff::default();
+    for inst in &to.instances {
+        let Some(old) = before.get(inst.instance_id.as_str()) else {
+            diff.added.push(widget_ref(inst));
+            continue;
+        };
+        let change = || WidgetChange {
+            instance_id: inst.instance_id.clone(),
+            type_id: inst.type_id.clone(),
+            from: rect(old),
+            to: rect(inst),
+        };
+        if (old.x, old.y) != (inst.x, inst.y) {
+            diff.moved.push(change());
+        }
+        if (old.width, old.height) != (inst.width, inst.height) {
+            diff.resized.push(change());
+        }
+        if (old.visible, old.locked, &old.config, &old.extra)
+            != (inst.visible, inst.locked, &inst.config, &inst.extra)
+        {
+            diff.modified.push(widget_ref(inst));
+        }
+    }
+    diff.removed = from
+        .instances
+        .iter()
+        .filter(|i| !after.contains_key(i.instance_id.as_str()))
+        .map(widget_ref)
+        .collect();
+
+    // Compare the remaining overlay fields generically via their JSON form
+    let as_map = |overlay: &OverlaySettings| match serde_json::to_value(overlay) {
+        Ok(Value::Object(mut map)) => {
+            map.remove("instances");
+            map
+        }
+        _ => Map::new(),
+    };
+    let (old_map, new_map) = (as_map(from), as_map(to));
+    let keys: HashSet<&String> = old_map.keys().chain(new_map.keys()).collect();
+    diff.changed_settings = keys
+        .into_iter()
+        .filter(|k| old_map.get(*k) != new_map.get(*k))
+        .cloned()
+        .collect();
+    diff.changed_settings.sort();
+    diff
+}
+
 #[tauri::command]
-pub fn read_settings(state: tauri::State<'_, SettingsState>) -> Result<Option<Value>, String> {
-    match std::fs::read_to_string(&state.path) {
-        Ok(json) => {
+pub fn read_settings(state: tauri::State<'_, SettingsState>) -> Result<Option<Settings>, String> {
+    match state.load() {
+        Ok(Some(settings)) => {
             info!("[settings] loaded from {}", state.path.display());
-            let data: Value = serde_json::from_str(&json).map_err(|e| e.to_string())?;
-            Ok(Some(data))
+            Ok(Some(settings))
         }
-        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+        Ok(None) => {
             info!("[settings] no file at {}", state.path.display());
             Ok(None)
         }
         Err(e) => {
             error!("[settings] read error: {e}");
-            Err(e.to_string())
+            Err(e)
         }
     }
 }
 
+/// Save the settings sent by a window and announce them to the backend and
+/// every other window. The sender already has them, and re-applying its own
+/// write could undo edits made since.
 #[tauri::command]
-pub fn write_settings(data: Value, state: tauri::State<'_, SettingsState>) -> Result<(), String> {
-    if let Some(parent) = state.path.parent() {
-        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-    }
-    let json = serde_json::to_string_pretty(&data).map_err(|e| e.to_string())?;
-    std::fs::write(&state.path, &json).map_err(|e| {
-        error!("[settings] write error: {e}");
-        e.to_string()
+pub fn write_settings(
+    mut data: Value,
+    window: tauri::WebviewWindow,
+    state: tauri::State<'_, SettingsState>,
+) -> Result<(), String> {
+    // Windows only hold settings already migrated by `read_settings`, so an
+    // unversioned write is in the current shape
+    if let Some(doc) = data.as_object_mut() {
+        doc.entry("_v").or_insert(SETTINGS_VERSION.into());
+    }
+    let has_rewards = data.get("rewards").is_some();
+    let has_hotkeys = data.get("hotkeys").is_some();
+    let mut settings = Settings::from_value(data).inspect_err(|e| {
+        error!("[settings] rejected write: {e}");
+    })?;
+    let ((), saved) = state.update(|current| {
+        if !has_rewards {
+            settings.rewards = std::mem::take(&mut current.rewards);
+        }
+        if !has_hotkeys {
+            settings.hotkeys = current.hotkeys.take();
+        }
+        *current = settings;
+        Ok(())
     })?;
     info!("[settings] saved to {}", state.path.display());
-    Ok(())
+    let sender = window.label().to_string();
+    window
+        .emit_filter(
+            crate::watcher::SETTINGS_CHANGED_EVENT,
+            &saved,
+            |target| !matches!(target, EventTarget::WebviewWindow { label } if *label == sender),
+        )
+        .map_err(|e| e.to_string())
+}
+
+/// List settings snapshots, newest first.
+#[tauri::command]
+pub fn list_settings_snapshots(
+    state: tauri::State<'_, SettingsState>,
+) -> Result<Vec<SnapshotInfo>, String> {
+    let mut snapshots = Vec::new();
+    for id in state.snapshot_ids()? {
+        match state.load_snapshot(&id.to_string()) {
+            Ok(settings) => snapshots.push(SnapshotInfo {
+                id: id.to_string(),
+                timestamp: id,
+                widget_count: settings.overlay.map_or(0, |o| o.instances.len()),
+            }),
+            Err(e) => warn!("[settings] skipping snapshot {id}: {e}"),
+        }
+    }
+    Ok(snapshots)
+}
+
+/// Compare two snapshots. When `to` is omitted, compares against the current settings.
+#[tauri::command]
+pub fn diff_settings_snapshots(
+    from: String,
+    to: Option<String>,
+    state: tauri::State<'_, SettingsState>,
+) -> Result<SettingsDiff, String> {
+    let before = state.load_snapshot(&from)?;
+    let after = match to {
+        Some(id) => state.load_snapshot(&id)?,
+        None => state.load()?.unwrap_or_default(),
+    };
+    Ok(diff_settings(&before, &after))
+}
+
+/// Restore a snapshot as the current settings and notify all windows.
+/// The settings being replaced are snapshotted first, so a restore can be undone.
+/// Rewards and hotkeys are not part of the restore; the live ones are kept.
+#[tauri::command]
+pub fn restore_settings_snapshot(
+    id: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+) -> Result<Settings, String> {
+    let restored = state.restore_snapshot(&id)?;
+    app.emit(crate::watcher::SETTINGS_CHANGED_EVENT, &restored)
+        .map_err(|e| e.to_string())?;
+    Ok(restored)
+}
+
+/// List settings profiles and the active one.
+#[tauri::command]
+pub fn list_profiles(state: tauri::State<'_, SettingsState>) -> Result<ProfilesInfo, String> {
+    Ok(ProfilesInfo {
+        active: state.active_profile()?,
+        profiles: state.profile_names()?,
+    })
+}
+
+/// Create a profile with an empty layout. Connection settings are carried
+/// over from the active profile; everything else falls back to defaults.
+#[tauri::command]
+pub fn create_profile(name: String, state: tauri::State<'_, SettingsState>) -> Result<(), String> {
+    state.create_profile(&name)
+}
+
+/// Create a profile as a copy of an existing one.
+#[tauri::command]
+pub fn clone_profile(
+    source: String,
+    name: String,
+    state: tauri::State<'_, SettingsState>,
+) -> Result<(), String> {
+    state.clone_profile(&source, &name)
+}
+
+/// Make `name` the active profile: the live settings are stored back into the
+/// current profile, the target is loaded into `settings.json`, and every
+/// window is notified. Rewards and hotkeys are global and carry over.
+#[tauri::command]
+pub fn switch_profile(
+    name: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+) -> Result<Settings, String> {
+    let target = state.switch_profile(&name)?;
+    app.emit(crate::watcher::SETTINGS_CHANGED_EVENT, &target)
+        .map_err(|e| e.to_string())?;
+    Ok(target)
+}
+
+/// Delete an inactive profile.
+#[tauri::command]
+pub fn delete_profile(name: String, state: tauri::State<'_, SettingsState>) -> Result<(), String> {
+    state.delete_profile(&name)
 }
 
 #[tauri::command]
 pub fn read_chat_history(state: tauri::State<'_, SettingsState>) -> Result<Option<Value>, String> {
-    match std::fs::read_to_string(&state.chat_history_path) {
-        Ok(json) => {
+    match persist::read_json(&state.chat_history_path) {
+        Ok(Some(data)) => {
             info!(
                 "[chat-history] loaded from {}",
                 state.chat_history_path.display()
             );
-            let data: Value = serde_json::from_str(&json).map_err(|e| e.to_string())?;
             Ok(Some(data))
         }
-        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+        Ok(None) => {
             info!(
                 "[chat-history] no file at {}",
                 state.chat_history_path.display()
@@ -70,7 +1005,7 @@ pub fn read_chat_history(state: tauri::State<'_, SettingsState>) -> Result<Optio
         }
         Err(e) => {
             error!("[chat-history] read error: {e}");
-            Err(e.to_string())
+            Err(e)
         }
     }
 }
@@ -80,17 +1015,356 @@ pub fn write_chat_history(
     data: Value,
     state: tauri::State<'_, SettingsState>,
 ) -> Result<(), String> {
-    if let Some(parent) = state.chat_history_path.parent() {
-        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-    }
-    let json = serde_json::to_string_pretty(&data).map_err(|e| e.to_string())?;
-    std::fs::write(&state.chat_history_path, &json).map_err(|e| {
-        error!("[chat-history] write error: {e}");
-        e.to_string()
-    })?;
+    persist::write_json(&state.chat_history_path, &data)?;
     info!(
         "[chat-history] saved to {}",
         state.chat_history_path.display()
     );
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn instance<'a>(settings: &'a Settings, id: &str) -> &'a WidgetInstance {
+        settings
+            .overlay
+            .as_ref()
+            .unwrap()
+            .instances
+            .iter()
+            .find(|i| i.instance_id == id)
+            .unwrap()
+    }
+
+    #[test]
+    fn upgrades_unversioned_split_format() {
+        let fixture = json!({
+            "overlay": {
+                "instances": [
+                    { "instanceId": "chat-1", "typeId": "chat", "bgColour": "#ff0000" },
+                    { "instanceId": "text-1", "typeId": "custom-text" },
+                    { "instanceId": "orphan", "typeId": "viewer-count" }
+                ],
+                "widgetStates": {
+                    "chat-1": { "x": 10, "y": 20, "width": 400, "height": 500, "visible": false }
+                },
+                "fileLogging": true,
+                "presenceThreshold": 1000
+            },
+            "twitch": { "channel": "webbertakken" },
+            "customText": { "config": { "text": "Hello" } }
+        });
+
+        let settings = Settings::from_value(fixture).unwrap();
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert!(!settings.extra.contains_key("customText"));
+
+        let chat = instance(&settings, "chat-1");
+        assert_eq!(
+            (chat.x, chat.y, chat.width, chat.height),
+            (10.0, 20.0, 400.0, 500.0)
+        );
+        assert!(!chat.visible);
+        assert!(!chat.locked);
+        assert!(!chat.extra.contains_key("bgColour"));
+
+        // Widgets without saved state get their registry defaults
+        let orphan = instance(&settings, "orphan");
+        assert_eq!(
+            (orphan.x, orphan.y, orphan.width, orphan.height),
+            (1192.0, 24.0, 200.0, 64.0)
+        );
+        assert!(orphan.visible);
+
+        let text = instance(&settings, "text-1");
+        assert_eq!(text.config.as_ref().unwrap()["text"], "Hello");
+
+        let overlay = settings.overlay.as_ref().unwrap();
+        assert!(!overlay.extra.contains_key("widgetStates"));
+        assert_eq!(settings.twitch.unwrap().channel, "webbertakken");
+    }
+
+    #[test]
+    fn upgrades_v1_opacity_fields() {
+        let fixture = json!({
+            "_v": 1,
+            "overlay": {
+                "instances": [{
+                    "instanceId": "a", "typeId": "chat",
+                    "x": 0, "y": 0, "width": 1, "height": 1,
+                    "visible": true, "locked": true,
+                    "opacity": 50, "liveBg": true, "textColour": "#fff"
+                }],
+                "fileLogging": false,
+                "panelBgOpacity": 65
+            }
+        });
+
+        let settings = Settings::from_value(fixture).unwrap();
+        let overlay = settings.overlay.as_ref().unwrap();
+        assert_eq!(overlay.widget_bg_opacity, Some(65.0));
+
+        let a = instance(&settings, "a");
+        assert!(a.locked);
+        assert!(!a.extra.contains_key("opacity"));
+        assert!(!a.extra.contains_key("liveBg"));
+        // Style overrides set after v1 are preserved.
+        assert_eq!(a.extra["textColour"], "#fff");
+    }
+
+    #[test]
+    fn upgrades_v2_live_bg_and_defaults_unknown_types() {
+        let fixture = json!({
+            "_v": 2,
+            "overlay": {
+                "instances": [
+                    {
+                        "instanceId": "a", "typeId": "chat",
+                        "x": 5, "y": 6, "width": 7, "height": 8,
+                        "visible": true, "liveBg": false
+                    }
+                ],
+                "widgetBgOpacity": 40
+            }
+        });
+        let settings = Settings::from_value(fixture).unwrap();
+        let a = instance(&settings, "a");
+        assert_eq!((a.x, a.y, a.width, a.height), (5.0, 6.0, 7.0, 8.0));
+        assert!(!a.extra.contains_key("liveBg"));
+        assert_eq!(settings.overlay.unwrap().widget_bg_opacity, Some(40.0));
+
+        assert_eq!(widget_defaults("not-registered"), (0.0, 0.0, 300.0, 200.0));
+    }
+
+    #[test]
+    fn drops_malformed_entries_instead_of_the_document() {
+        let fixture = json!({
+            "_v": SETTINGS_VERSION,
+            "overlay": {
+                "instances": [
+                    {
+                        "instanceId": "good", "typeId": "chat",
+                        "x": 0, "y": 0, "width": 1, "height": 1, "visible": true
+                    },
+                    { "instanceId": "bad", "typeId": "chat", "x": "left" },
+                    42
+                ],
+                "commands": [
+                    { "trigger": "!hi", "response": "hello", "enabled": true },
+                    { "trigger": "!broken" }
+                ],
+                "fileLogging": true
+            },
+            "hotkeys": [
+                { "id": "h1", "shortcut": "Ctrl+K", "action": { "kind": "clearChat" } },
+                { "id": "h2", "shortcut": "Ctrl+L", "action": { "kind": "fromTheFuture" } }
+            ],
+            "rewards": "not a list"
+        });
+
+        let settings = Settings::from_value(fixture).unwrap();
+        let overlay = settings.overlay.as_ref().unwrap();
+        assert_eq!(overlay.instances.len(), 1);
+        assert_eq!(overlay.instances[0].instance_id, "good");
+        assert_eq!(overlay.commands.as_ref().unwrap().len(), 1);
+        assert!(overlay.file_logging);
+        assert_eq!(settings.hotkeys.as_ref().unwrap().len(), 1);
+        assert!(settings.rewards.is_empty());
+    }
+
+    #[test]
+    fn current_version_round_trips_unknown_fields() {
+        let fixture = json!({
+            "_v": SETTINGS_VERSION,
+            "overlay": {
+                "instances": [],
+                "fileLogging": false,
+                "someFutureSetting": [1, 2, 3]
+            },
+            "topLevelFuture": true
+        });
+
+        let settings = Settings::from_value(fixture).unwrap();
+        let value = serde_json::to_value(&settings).unwrap();
+        assert_eq!(value["overlay"]["someFutureSetting"], json!([1, 2, 3]));
+        assert_eq!(value["topLevelFuture"], true);
+        assert_eq!(value["_v"], SETTINGS_VERSION);
+    }
+
+    #[test]
+    fn rejects_newer_versions_and_non_objects() {
+        assert!(Settings::from_value(json!({ "_v": SETTINGS_VERSION + 1 })).is_err());
+        assert!(Settings::from_value(json!([])).is_err());
+    }
+
+    #[test]
+    fn drops_duplicate_and_empty_instance_ids() {
+        let inst = |id: &str| {
+            json!({
+                "instanceId": id, "typeId": "chat",
+                "x": 0, "y": 0, "width": 1, "height": 1, "visible": true
+            })
+        };
+        let fixture = json!({
+            "_v": SETTINGS_VERSION,
+            "overlay": { "instances": [inst("a"), inst("a"), inst("")] }
+        });
+
+        let settings = Settings::from_value(fixture).unwrap();
+        assert_eq!(settings.overlay.unwrap().instances.len(), 1);
+    }
+
+    #[test]
+    fn diff_reports_moved_added_and_removed_widgets() {
+        let layout = |instances: Value| {
+            Settings::from_value(json!({
+                "_v": SETTINGS_VERSION,
+                "overlay": { "instances": instances, "panelWidth": 320 }
+            }))
+            .unwrap()
+        };
+        let inst = |id: &str, x: i32| {
+            json!({
+                "instanceId": id, "typeId": "chat",
+                "x": x, "y": 0, "width": 100, "height": 100, "visible": true
+            })
+        };
+        let before = layout(json!([inst("a", 0), inst("b", 0)]));
+        let mut after = layout(json!([inst("a", 50), inst("c", 0)]));
+        after.overlay.as_mut().unwrap().panel_width = Some(400.0);
+
+        let diff = diff_settings(&before, &after);
+        assert_eq!(diff.moved.len(), 1);
+        assert_eq!(diff.moved[0].instance_id, "a");
+        assert!(diff.resized.is_empty());
+        assert_eq!(diff.added[0].instance_id, "c");
+        assert_eq!(diff.removed[0].instance_id, "b");
+        assert_eq!(diff.changed_settings, vec!["panelWidth".to_string()]);
+    }
+
+    fn temp_state() -> SettingsState {
+        SettingsState::new(
+            std::env::temp_dir().join(format!("settings-test-{}", uuid::Uuid::new_v4())),
+        )
+    }
+
+    /// Settings with one widget, one managed reward and one hotkey, tagged by `tag`.
+    fn tagged(tag: &str) -> Settings {
+        Settings::from_value(json!({
+            "_v": SETTINGS_VERSION,
+            "overlay": { "instances": [{
+                "instanceId": tag, "typeId": "chat",
+                "x": 0, "y": 0, "width": 1, "height": 1, "visible": true
+            }] },
+            "rewards": [{ "rewardId": tag, "feature": "sound", "title": tag }],
+            "hotkeys": [{
+                "id": tag, "shortcut": "Ctrl+Shift+K", "action": { "kind": "clearChat" }
+            }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn concurrent_updates_keep_every_change() {
+        let state = std::sync::Arc::new(temp_state());
+        state.save(&tagged("live")).unwrap();
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                let state = state.clone();
+                std::thread::spawn(move || {
+                    state
+                        .update(|s| {
+                            s.rewards.extend(tagged(&format!("r{i}")).rewards);
+                            Ok(())
+                        })
+                        .unwrap()
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        assert_eq!(state.load().unwrap().unwrap().rewards.len(), 9);
+        std::fs::remove_dir_all(state.path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn restoring_a_snapshot_keeps_live_rewards_and_hotkeys() {
+        let state = temp_state();
+        let id = state.snapshot(&tagged("old")).unwrap();
+        state.save(&tagged("live")).unwrap();
+
+        let restored = state.restore_snapshot(&id).unwrap();
+        assert_eq!(restored.overlay.unwrap().instances[0].instance_id, "old");
+        assert_eq!(restored.rewards[0].reward_id, "live");
+        assert_eq!(restored.hotkeys.unwrap()[0].id, "live");
+        assert_eq!(state.load().unwrap().unwrap().rewards[0].reward_id, "live");
+        std::fs::remove_dir_all(state.path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn profiles_swap_layouts_but_share_rewards_and_hotkeys() {
+        let state = temp_state();
+        state.save(&tagged("live")).unwrap();
+        state.create_profile("gaming").unwrap();
+        assert!(state.create_profile("gaming").is_err());
+        assert!(state.create_profile("Bad Name").is_err());
+        assert!(state.load_profile("gaming").unwrap().rewards.is_empty());
+
+        let switched = state.switch_profile("gaming").unwrap();
+        assert!(switched.overlay.unwrap().instances.is_empty());
+        assert_eq!(switched.rewards[0].reward_id, "live");
+        assert_eq!(switched.hotkeys.unwrap()[0].id, "live");
+        assert_eq!(state.active_profile().unwrap(), "gaming");
+
+        // The previous profile keeps its layout but not the global sections
+        let default = state.load_profile(DEFAULT_PROFILE).unwrap();
+        assert_eq!(default.overlay.unwrap().instances[0].instance_id, "live");
+        assert!(default.rewards.is_empty() && default.hotkeys.is_none());
+
+        assert!(state.delete_profile("gaming").is_err());
+        state.delete_profile(DEFAULT_PROFILE).unwrap();
+        assert_eq!(state.profile_names().unwrap(), vec!["gaming".to_string()]);
+        std::fs::remove_dir_all(state.path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn switching_adopts_rewards_from_legacy_profiles() {
+        let state = temp_state();
+        state.save(&tagged("live")).unwrap();
+        persist::write_json(&state.profile_path("old"), &tagged("legacy")).unwrap();
+
+        let switched = state.switch_profile("old").unwrap();
+        let ids: Vec<&str> = switched
+            .rewards
+            .iter()
+            .map(|r| r.reward_id.as_str())
+            .collect();
+        assert_eq!(ids, ["live", "legacy"]);
+        assert_eq!(switched.hotkeys.unwrap()[0].id, "live");
+        std::fs::remove_dir_all(state.path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn profile_commands_reject_hostile_names() {
+        let state = temp_state();
+        state.save(&tagged("live")).unwrap();
+        for name in ["../settings", "..", "a/b", "a\\b", ""] {
+            assert!(state.load_profile(name).is_err(), "{name}");
+            assert!(state.switch_profile(name).is_err(), "{name}");
+            assert!(state.clone_profile(name, "copy").is_err(), "{name}");
+            assert!(
+                state.clone_profile(DEFAULT_PROFILE, name).is_err(),
+                "{name}"
+            );
+            assert!(state.delete_profile(name).is_err(), "{name}");
+        }
+        assert_eq!(state.active_profile().unwrap(), DEFAULT_PROFILE);
+        let live = state.load().unwrap().unwrap();
+        assert_eq!(live.overlay.unwrap().instances[0].instance_id, "live");
+        std::fs::remove_dir_all(state.path.parent().unwrap()).ok();
+    }
+}
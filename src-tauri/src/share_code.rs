@@ -0,0 +1,110 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+
+/// Version prefix of the current share code format.
+///
+/// A share code looks like `sp1.<payload>.<checksum>`, where the payload is
+/// the minified preset JSON, deflated and base64url-encoded, and the checksum
+/// is the first 8 hex digits of the SHA-256 of the payload text.
+const SHARE_PREFIX: &str = "sp1";
+
+/// Upper bound for a decoded preset, to guard against decompression bombs.
+const MAX_DECODED_BYTES: u64 = 1024 * 1024;
+
+fn checksum(payload: &str) -> String {
+    Sha256::digest(payload.as_bytes())[..4]
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Encode a preset document as a share code.
+pub fn encode(preset: &Value) -> Result<String, String> {
+    let json = serde_json::to_vec(preset).map_err(|e| e.to_string())?;
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(&json).map_err(|e| e.to_string())?;
+    let compressed = encoder.finish().map_err(|e| e.to_string())?;
+
+    let payload = URL_SAFE_NO_PAD.encode(compressed);
+    let sum = checksum(&payload);
+    Ok(format!("{SHARE_PREFIX}.{payload}.{sum}"))
+}
+
+/// Decode a share code back into a preset document. The caller is
+/// responsible for validating the preset's shape.
+pub fn decode(code: &str) -> Result<Value, String> {
+    // Tolerate whitespace and line breaks picked up when copying from chat
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+
+    let mut parts = code.split('.');
+    let (Some(prefix), Some(payload), Some(sum), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err("Not a preset share code".into());
+    };
+    if prefix != SHARE_PREFIX {
+        return Err(format!("Unsupported share code version '{prefix}'"));
+    }
+    if checksum(payload) != sum.to_ascii_lowercase() {
+        return Err("Share code is damaged or incomplete (checksum mismatch)".into());
+    }
+
+    let compressed = URL_SAFE_NO_PAD
+        .decode(payload)
+        .map_err(|e| format!("Invalid share code: {e}"))?;
+    let mut json = Vec::new();
+    DeflateDecoder::new(compressed.as_slice())
+        .take(MAX_DECODED_BYTES + 1)
+        .read_to_end(&mut json)
+        .map_err(|e| format!("Invalid share code: {e}"))?;
+    if json.len() as u64 > MAX_DECODED_BYTES {
+        return Err("Shared preset is too large".into());
+    }
+    serde_json::from_slice(&json).map_err(|e| format!("Invalid preset in share code: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn round_trips_and_tolerates_whitespace() {
+        let preset = json!({ "name": "Stream", "instances": [{ "typeId": "chat", "x": 1 }] });
+        let code = encode(&preset).unwrap();
+        assert!(code.starts_with("sp1."));
+        let (head, tail) = code.split_at(code.len() / 2);
+        assert_eq!(decode(&format!(" {head}\n{tail} ")).unwrap(), preset);
+    }
+
+    #[test]
+    fn rejects_damaged_and_foreign_codes() {
+        let code = encode(&json!({ "name": "Stream" })).unwrap();
+        let damaged = format!("{}00000000", &code[..code.len() - 8]);
+        assert!(decode(&damaged).unwrap_err().contains("checksum mismatch"));
+        let foreign = code.replacen("sp1", "sp2", 1);
+        assert!(decode(&foreign)
+            .unwrap_err()
+            .contains("Unsupported share code version"));
+        assert!(decode("hello world")
+            .unwrap_err()
+            .contains("Not a preset share code"));
+    }
+
+    #[test]
+    fn rejects_inflate_bombs() {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+        encoder
+            .write_all(&vec![b' '; MAX_DECODED_BYTES as usize * 2])
+            .unwrap();
+        let payload = URL_SAFE_NO_PAD.encode(encoder.finish().unwrap());
+        let code = format!("{SHARE_PREFIX}.{payload}.{}", checksum(&payload));
+        assert_eq!(decode(&code).unwrap_err(), "Shared preset is too large");
+    }
+}
@@ -1,54 +1,1374 @@
-use serde_json::Value;
+use crate::auth::AuthState;
+use crate::events::{ChannelEvent, ChannelEventType};
+use crate::helix::{self, RedemptionStatus};
+use crate::persist;
+use crate::scenes;
+use crate::settings::{Settings, SettingsState};
+use crate::watcher::SUGGESTIONS_CHANGED_EVENT;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
-use tracing::{error, info};
+use std::sync::{Arc, Mutex};
+use tauri::{Emitter, Manager};
+use tracing::{error, info, warn};
 
-/// Shared suggestions state managed by Tauri.
+/// Longest suggestion text kept, in characters.
+const MAX_TEXT_CHARS: usize = 200;
+
+/// Minimum time between chat suggestions from the same user.
+const CHAT_COOLDOWN_MS: i64 = 30_000;
+
+/// Number of distinct two-digit hex ids (`00`-`FF`).
+const HEX_ID_COUNT: usize = 256;
+
+/// Done suggestions older than this are archived when the app starts.
+const AUTO_ARCHIVE_AFTER_MS: i64 = 12 * 60 * 60 * 1000;
+
+/// Most results returned by a search.
+const MAX_SEARCH_RESULTS: usize = 500;
+
+/// Emitted after the Twitch redemption behind a suggestion was updated (or failed to be).
+pub const REDEMPTION_UPDATED_EVENT: &str = "redemption-updated";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestionStatus {
+    Active,
+    Done,
+}
+
+/// A viewer suggestion. Mirrors `Suggestion` in
+/// `src/widgets/suggestion-box/suggestion-box-state.ts`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Suggestion {
+    pub id: String,
+    /// Short id viewers vote with, e.g. `!vote 3F`.
+    pub hex_id: String,
+    pub text: String,
+    pub username: String,
+    pub user_id: String,
+    /// Channel-point redemption id, or a synthetic `chat-…` id for chat suggestions.
+    pub redemption_id: String,
+    /// Empty for chat suggestions.
+    pub reward_id: String,
+    /// Unix millis.
+    pub created_at: i64,
+    pub status: SuggestionStatus,
+    pub votes: u32,
+    pub voters: Vec<String>,
+    /// Unix millis when marked done.
+    pub checked_at: Option<i64>,
+    /// Whether the redemption has been fulfilled on Twitch, which can only happen once.
+    #[serde(default)]
+    pub redemption_synced: bool,
+}
+
+/// A suggestion submitted from chat or a channel-point redemption.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewSuggestion {
+    pub text: String,
+    pub username: String,
+    pub user_id: String,
+    /// Present for redemptions; chat suggestions omit it and are rate limited.
+    #[serde(default)]
+    pub redemption_id: Option<String>,
+    #[serde(default)]
+    pub reward_id: Option<String>,
+}
+
+/// Result of [`SuggestionBox::add`].
+#[derive(Debug, Serialize)]
+#[serde(tag = "outcome", rename_all = "camelCase")]
+pub enum AddOutcome {
+    Added {
+        suggestion: Suggestion,
+    },
+    /// The redemption was already recorded, or a chat suggestion repeats the
+    /// text of an active one.
+    #[serde(rename_all = "camelCase")]
+    Duplicate {
+        hex_id: String,
+    },
+    /// The user suggested via chat too recently.
+    #[serde(rename_all = "camelCase")]
+    Cooldown {
+        remaining_ms: i64,
+    },
+    /// Every hex id is held by an active suggestion.
+    Full,
+}
+
+/// Payload of [`REDEMPTION_UPDATED_EVENT`].
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct RedemptionUpdate {
+    /// `None` when the redemption was turned away without becoming a suggestion.
+    suggestion_id: Option<String>,
+    redemption_id: String,
+    status: RedemptionStatus,
+    /// Set when Twitch rejected the update.
+    error: Option<String>,
+}
+
+/// Options of the suggestion box widget. Mirrors `SuggestionBoxConfig` in
+/// `src/widgets/suggestion-box/SuggestionBoxWidget.tsx`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct SuggestionConfig {
+    /// Channel-point reward that submits suggestions. When empty, viewers
+    /// suggest with the chat trigger instead.
+    reward_id: String,
+    vote_trigger: String,
+    suggest_trigger: String,
+}
+
+impl Default for SuggestionConfig {
+    fn default() -> Self {
+        Self {
+            reward_id: String::new(),
+            vote_trigger: "!vote".into(),
+            suggest_trigger: "!suggest".into(),
+        }
+    }
+}
+
+impl SuggestionConfig {
+    /// Config of the first suggestion box in the layout, or `None` if there is none.
+    fn from_settings(settings: &Settings) -> Option<Self> {
+        let inst = settings
+            .overlay
+            .as_ref()?
+            .instances
+            .iter()
+            .find(|inst| inst.type_id == "suggestion-box")?;
+        let config = inst.config.clone().unwrap_or_default();
+        Some(serde_json::from_value(Value::Object(config)).unwrap_or_default())
+    }
+}
+
+/// What a channel event asks of the suggestion box.
+#[derive(Debug, PartialEq)]
+enum Intake {
+    Add(NewSuggestion),
+    Vote { hex_id: String, user_id: String },
+}
+
+fn str_field<'a>(data: &'a Map<String, Value>, key: &str) -> &'a str {
+    data.get(key).and_then(Value::as_str).unwrap_or("")
+}
+
+/// Match a redemption of the configured reward, or a vote or (when no reward
+/// is configured) suggest command in chat.
+fn intake(event: &ChannelEvent, config: &SuggestionConfig) -> Option<Intake> {
+    // Replayed and simulated events must not reach the real list or Twitch
+    if event.is_simulated() {
+        return None;
+    }
+    let data = &event.data;
+    match event.kind {
+        ChannelEventType::ChannelPointsRedemption => {
+            let reward_id = data
+                .get("reward")
+                .and_then(|r| r.get("id"))
+                .and_then(Value::as_str)?;
+            let redemption_id = str_field(data, "id");
+            if config.reward_id.is_empty()
+                || reward_id != config.reward_id
+                || redemption_id.is_empty()
+            {
+                return None;
+            }
+            let username = str_field(data, "user_name");
+            Some(Intake::Add(NewSuggestion {
+                text: str_field(data, "user_input").to_string(),
+                username: if username.is_empty() {
+                    "unknown"
+                } else {
+                    username
+                }
+                .to_string(),
+                user_id: str_field(data, "user_id").to_string(),
+                redemption_id: Some(redemption_id.to_string()),
+                reward_id: Some(reward_id.to_string()),
+            }))
+        }
+        ChannelEventType::Chat => {
+            let text = str_field(data, "text");
+            let user_id = str_field(data, "userId");
+            if user_id.is_empty() {
+                return None;
+            }
+            if let Some(args) = scenes::command_args(text, &config.vote_trigger) {
+                let hex_id = normalise_hex_id(args)?;
+                return Some(Intake::Vote {
+                    hex_id,
+                    user_id: user_id.to_string(),
+                });
+            }
+            if !config.reward_id.is_empty() {
+                return None;
+            }
+            let args = scenes::command_args(text, &config.suggest_trigger)?;
+            if args.is_empty() {
+                return None;
+            }
+            let username = str_field(data, "username");
+            Some(Intake::Add(NewSuggestion {
+                text: args.to_string(),
+                username: if username.is_empty() {
+                    "unknown"
+                } else {
+                    username
+                }
+                .to_string(),
+                user_id: user_id.to_string(),
+                redemption_id: None,
+                reward_id: None,
+            }))
+        }
+        _ => None,
+    }
+}
+
+/// The suggestion list and the rules for changing it.
+#[derive(Default)]
+pub struct SuggestionBox {
+    suggestions: Vec<Suggestion>,
+    /// Last chat suggestion per user id, in Unix millis.
+    cooldowns: HashMap<String, i64>,
+}
+
+/// Normalise a hex id typed in chat: `a` → `0A`. Returns `None` if not hex.
+fn normalise_hex_id(hex_id: &str) -> Option<String> {
+    let hex_id = hex_id.trim();
+    let valid = (1..=2).contains(&hex_id.len()) && hex_id.chars().all(|c| c.is_ascii_hexdigit());
+    valid.then(|| format!("{:0>2}", hex_id.to_ascii_uppercase()))
+}
+
+/// Text used to detect duplicates: lowercase alphanumeric words, single spaced.
+fn duplicate_key(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl Suggestion {
+    /// Whether this suggestion came from a channel-point redemption Twitch is tracking.
+    fn has_redemption(&self) -> bool {
+        !self.reward_id.is_empty() && !self.redemption_id.starts_with("chat-")
+    }
+}
+
+/// Fulfil or refund the redemption behind `suggestion` in the background,
+/// reporting the outcome via [`REDEMPTION_UPDATED_EVENT`].
+fn sync_redemption(app: &tauri::AppHandle, suggestion: &Suggestion, status: RedemptionStatus) {
+    if !suggestion.has_redemption() {
+        return;
+    }
+    spawn_redemption_update(
+        app,
+        RedemptionUpdate {
+            suggestion_id: Some(suggestion.id.clone()),
+            redemption_id: suggestion.redemption_id.clone(),
+            status,
+            error: None,
+        },
+        suggestion.reward_id.clone(),
+    );
+}
+
+/// Whether a redemption that went through [`SuggestionBox::add`] was turned
+/// away and must be refunded. A duplicate is the same redemption delivered
+/// again, which the existing suggestion already holds.
+fn turned_away(outcome: &Result<AddOutcome, String>) -> bool {
+    !matches!(
+        outcome,
+        Ok(AddOutcome::Added { .. } | AddOutcome::Duplicate { .. })
+    )
+}
+
+/// Send `update` to Twitch in the background and report how it went.
+fn spawn_redemption_update(
+    app: &tauri::AppHandle,
+    mut update: RedemptionUpdate,
+    reward_id: String,
+) {
+    let app = app.clone();
+    let auth = app.state::<Arc<AuthState>>().inner().clone();
+    let status = update.status;
+    tauri::async_runtime::spawn(async move {
+        let result =
+            helix::update_redemption(&auth, &reward_id, &update.redemption_id, status).await;
+        if let Err(e) = result {
+            warn!(
+                "[suggestions] could not mark redemption {} {status:?}: {e}",
+                update.redemption_id
+            );
+            update.error = Some(e);
+            if let (RedemptionStatus::Fulfilled, Some(id)) = (status, update.suggestion_id.clone())
+            {
+                // Let a later completion try again
+                let released = app
+                    .state::<SuggestionsState>()
+                    .update(&app, |inner| Ok(((), inner.release_fulfilment(&id))));
+                if let Err(e) = released {
+                    warn!("[suggestions] could not release redemption {id}: {e}");
+                }
+            }
+        }
+        if let Err(e) = app.emit(REDEMPTION_UPDATED_EVENT, &update) {
+            warn!("[suggestions] emit failed: {e}");
+        }
+    });
+}
+
+impl SuggestionBox {
+    pub fn new(suggestions: Vec<Suggestion>) -> Self {
+        Self {
+            suggestions,
+            cooldowns: HashMap::new(),
+        }
+    }
+
+    pub fn suggestions(&self) -> &[Suggestion] {
+        &self.suggestions
+    }
+
+    fn active_by_hex(&mut self, hex_id: &str) -> Result<&mut Suggestion, String> {
+        let hex_id =
+            normalise_hex_id(hex_id).ok_or_else(|| format!("Invalid suggestion id '{hex_id}'"))?;
+        self.suggestions
+            .iter_mut()
+            .find(|s| s.hex_id == hex_id && s.status == SuggestionStatus::Active)
+            .ok_or_else(|| format!("No active suggestion {hex_id}"))
+    }
+
+    fn by_id(&mut self, id: &str) -> Result<&mut Suggestion, String> {
+        self.suggestions
+            .iter_mut()
+            .find(|s| s.id == id)
+            .ok_or_else(|| format!("Suggestion '{id}' not found"))
+    }
+
+    /// Pick a free hex id, reusing the one of the longest-done suggestion when
+    /// all are taken. Returns `None` if every id is held by an active suggestion.
+    fn allocate_hex_id(&self) -> Option<String> {
+        let used: HashSet<&str> = self.suggestions.iter().map(|s| s.hex_id.as_str()).collect();
+        let free: Vec<String> = (0..HEX_ID_COUNT)
+            .map(|n| format!("{n:02X}"))
+            .filter(|hex| !used.contains(hex.as_str()))
+            .collect();
+        if !free.is_empty() {
+            return Some(free[fastrand::usize(..free.len())].clone());
+        }
+
+        let active: HashSet<&str> = self
+            .suggestions
+            .iter()
+            .filter(|s| s.status == SuggestionStatus::Active)
+            .map(|s| s.hex_id.as_str())
+            .collect();
+        self.suggestions
+            .iter()
+            .filter(|s| s.status == SuggestionStatus::Done && !active.contains(s.hex_id.as_str()))
+            .min_by_key(|s| s.checked_at.unwrap_or(0))
+            .map(|s| s.hex_id.clone())
+    }
+
+    /// Remove and return done suggestions checked at or before `cutoff`.
+    pub fn take_done(&mut self, cutoff: i64) -> Vec<Suggestion> {
+        let (done, keep) = std::mem::take(&mut self.suggestions)
+            .into_iter()
+            .partition(|s| {
+                s.status == SuggestionStatus::Done && s.checked_at.unwrap_or(0) <= cutoff
+            });
+        self.suggestions = keep;
+        done
+    }
+
+    /// Add a suggestion unless it is a duplicate, rate limited or there is no room.
+    /// Redemptions are paid for, so they may be empty or repeat an active
+    /// suggestion; chat suggestions may not.
+    pub fn add(&mut self, new: NewSuggestion, now: i64) -> Result<AddOutcome, String> {
+        let from_chat = new.redemption_id.is_none();
+        let text: String = new.text.trim().chars().take(MAX_TEXT_CHARS).collect();
+        if from_chat && text.is_empty() {
+            return Err("Suggestion text must not be empty".into());
+        }
+        if new.user_id.is_empty() {
+            return Err("Suggestion must have a user id".into());
+        }
+
+        if let Some(redemption_id) = &new.redemption_id {
+            if let Some(existing) = self
+                .suggestions
+                .iter()
+                .find(|s| &s.redemption_id == redemption_id)
+            {
+                return Ok(AddOutcome::Duplicate {
+                    hex_id: existing.hex_id.clone(),
+                });
+            }
+        }
+        if from_chat {
+            let key = duplicate_key(&text);
+            if let Some(existing) = self
+                .suggestions
+                .iter()
+                .find(|s| s.status == SuggestionStatus::Active && duplicate_key(&s.text) == key)
+            {
+                return Ok(AddOutcome::Duplicate {
+                    hex_id: existing.hex_id.clone(),
+                });
+            }
+            if let Some(last) = self.cooldowns.get(&new.user_id) {
+                let remaining_ms = CHAT_COOLDOWN_MS - (now - last);
+                if remaining_ms > 0 {
+                    return Ok(AddOutcome::Cooldown { remaining_ms });
+                }
+            }
+        }
+
+        let Some(hex_id) = self.allocate_hex_id() else {
+            return Ok(AddOutcome::Full);
+        };
+        if from_chat {
+            self.cooldowns.insert(new.user_id.clone(), now);
+        }
+        let suggestion = Suggestion {
+            id: uuid::Uuid::new_v4().to_string(),
+            hex_id,
+            text,
+            redemption_id: new
+                .redemption_id
+                .unwrap_or_else(|| format!("chat-{now}-{}", new.user_id)),
+            reward_id: new.reward_id.unwrap_or_default(),
+            username: new.username,
+            user_id: new.user_id,
+            created_at: now,
+            status: SuggestionStatus::Active,
+            votes: 0,
+            voters: Vec::new(),
+            checked_at: None,
+            redemption_synced: false,
+        };
+        self.suggestions.push(suggestion.clone());
+        Ok(AddOutcome::Added { suggestion })
+    }
+
+    /// Record a vote. Returns `false` if the user had already voted.
+    pub fn vote(&mut self, hex_id: &str, user_id: &str) -> Result<bool, String> {
+        let suggestion = self.active_by_hex(hex_id)?;
+        if suggestion.voters.iter().any(|v| v == user_id) {
+            return Ok(false);
+        }
+        suggestion.voters.push(user_id.to_string());
+        suggestion.votes += 1;
+        Ok(true)
+    }
+
+    /// Withdraw a vote. Returns `false` if the user had not voted.
+    pub fn unvote(&mut self, hex_id: &str, user_id: &str) -> Result<bool, String> {
+        let suggestion = self.active_by_hex(hex_id)?;
+        let Some(pos) = suggestion.voters.iter().position(|v| v == user_id) else {
+            return Ok(false);
+        };
+        suggestion.voters.remove(pos);
+        suggestion.votes = suggestion.votes.saturating_sub(1);
+        Ok(true)
+    }
+
+    /// Mark a suggestion done, or active again. Returns `false` if unchanged.
+    pub fn complete(&mut self, id: &str, done: bool, now: i64) -> Result<bool, String> {
+        let suggestion = self.by_id(id)?;
+        let status = if done {
+            SuggestionStatus::Done
+        } else {
+            SuggestionStatus::Active
+        };
+        if suggestion.status == status {
+            return Ok(false);
+        }
+        suggestion.status = status;
+        suggestion.checked_at = done.then_some(now);
+        Ok(true)
+    }
+
+    /// Claim the one-off fulfilment of a done suggestion's redemption. Returns
+    /// the suggestion to fulfil, or `None` if it has no redemption or was
+    /// already fulfilled.
+    pub fn claim_fulfilment(&mut self, id: &str) -> Result<Option<Suggestion>, String> {
+        let suggestion = self.by_id(id)?;
+        if suggestion.status != SuggestionStatus::Done
+            || suggestion.redemption_synced
+            || !suggestion.has_redemption()
+        {
+            return Ok(None);
+        }
+        suggestion.redemption_synced = true;
+        Ok(Some(suggestion.clone()))
+    }
+
+    /// Undo [`Self::claim_fulfilment`] after Twitch rejected it. Returns
+    /// whether anything changed.
+    pub fn release_fulfilment(&mut self, id: &str) -> bool {
+        match self.by_id(id) {
+            Ok(suggestion) if suggestion.redemption_synced => {
+                suggestion.redemption_synced = false;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Remove a suggestion, returning it.
+    pub fn delete(&mut self, id: &str) -> Result<Suggestion, String> {
+        let pos = self
+            .suggestions
+            .iter()
+            .position(|s| s.id == id)
+            .ok_or_else(|| format!("Suggestion '{id}' not found"))?;
+        Ok(self.suggestions.remove(pos))
+    }
+}
+
+/// `YYYY-MM-DD` in local time for a Unix millis timestamp.
+fn local_date(millis: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(millis)
+        .unwrap_or_default()
+        .with_timezone(&chrono::Local)
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+/// `YYYY-MM-DD HH:MM` in local time for a Unix millis timestamp.
+fn local_time(millis: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(millis)
+        .unwrap_or_default()
+        .with_timezone(&chrono::Local)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+/// Case-insensitive match on suggestion text and on username or user id.
+/// `text` and `user` must already be lowercase.
+fn matches_search(suggestion: &Suggestion, text: Option<&str>, user: Option<&str>) -> bool {
+    let text_ok = text.is_none_or(|t| suggestion.text.to_lowercase().contains(t));
+    let user_ok = user
+        .is_none_or(|u| suggestion.username.to_lowercase().contains(u) || suggestion.user_id == u);
+    text_ok && user_ok
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+const EXPORT_COLUMNS: [&str; 7] = [
+    "ID",
+    "Status",
+    "Votes",
+    "Suggestion",
+    "User",
+    "Created",
+    "Completed",
+];
+
+fn export_row(s: &Suggestion) -> [String; 7] {
+    [
+        s.hex_id.clone(),
+        match s.status {
+            SuggestionStatus::Active => "active",
+            SuggestionStatus::Done => "done",
+        }
+        .to_string(),
+        s.votes.to_string(),
+        s.text.clone(),
+        s.username.clone(),
+        local_time(s.created_at),
+        s.checked_at.map(local_time).unwrap_or_default(),
+    ]
+}
+
+fn to_csv(suggestions: &[Suggestion]) -> String {
+    let mut out = EXPORT_COLUMNS.join(",") + "\n";
+    for s in suggestions {
+        let row: Vec<String> = export_row(s).iter().map(|v| csv_field(v)).collect();
+        out += &(row.join(",") + "\n");
+    }
+    out
+}
+
+fn to_markdown(suggestions: &[Suggestion]) -> String {
+    let cell = |v: &str| v.replace('|', "\\|").replace(['\r', '\n'], " ");
+    let mut out = format!(
+        "| {} |\n|{}\n",
+        EXPORT_COLUMNS.join(" | "),
+        "---|".repeat(EXPORT_COLUMNS.len())
+    );
+    for s in suggestions {
+        let row: Vec<String> = export_row(s).iter().map(|v| cell(v)).collect();
+        out += &format!("| {} |\n", row.join(" | "));
+    }
+    out
+}
+
+/// Shared suggestions state managed by Tauri. This is the single source of
+/// truth; windows render the list broadcast in [`SUGGESTIONS_CHANGED_EVENT`].
 pub struct SuggestionsState {
     path: PathBuf,
+    /// Holds one `YYYY-MM-DD.json` file of archived suggestions per completion date.
+    archive_dir: PathBuf,
+    inner: Mutex<SuggestionBox>,
 }
 
 impl SuggestionsState {
     pub fn new(data_dir: PathBuf) -> Self {
-        Self {
-            path: data_dir.join("suggestions.json"),
+        let path = data_dir.join("suggestions.json");
+        let suggestions = Self::read(&path).unwrap_or_else(|e| {
+            error!("[suggestions] read error: {e}");
+            Vec::new()
+        });
+        let state = Self {
+            path,
+            archive_dir: data_dir.join("suggestions-archive"),
+            inner: Mutex::new(SuggestionBox::new(suggestions)),
+        };
+        // Keep the live list short: completed suggestions from earlier streams go to the archive
+        let cutoff = chrono::Utc::now().timestamp_millis() - AUTO_ARCHIVE_AFTER_MS;
+        if let Err(e) = state.archive_done(cutoff) {
+            error!("[suggestions] auto-archive failed: {e}");
+        }
+        state
+    }
+
+    /// Move done suggestions checked at or before `cutoff` into the archive.
+    /// Returns the suggestions that were moved.
+    fn archive_done(&self, cutoff: i64) -> Result<Vec<Suggestion>, String> {
+        let mut inner = self.inner.lock().map_err(|e| e.to_string())?;
+        let done = inner.take_done(cutoff);
+        if done.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut by_date: BTreeMap<String, Vec<Suggestion>> = BTreeMap::new();
+        for suggestion in &done {
+            by_date
+                .entry(local_date(
+                    suggestion.checked_at.unwrap_or(suggestion.created_at),
+                ))
+                .or_default()
+                .push(suggestion.clone());
+        }
+        // Write the archive before the live list, so a crash can duplicate but never lose
+        for (date, suggestions) in by_date {
+            let path = self.archive_dir.join(format!("{date}.json"));
+            let mut archived = Self::read(&path)?;
+            archived.extend(suggestions);
+            persist::write_json(&path, &archived)?;
+        }
+        persist::write_json(&self.path, inner.suggestions())?;
+        info!("[suggestions] archived {} suggestions", done.len());
+        Ok(done)
+    }
+
+    /// Every archived suggestion, oldest archive first.
+    fn archived(&self) -> Result<Vec<Suggestion>, String> {
+        let entries = match std::fs::read_dir(&self.archive_dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.to_string()),
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        let mut archived = Vec::new();
+        for path in paths {
+            match Self::read(&path) {
+                Ok(suggestions) => archived.extend(suggestions),
+                Err(e) => warn!(
+                    "[suggestions] skipping unreadable archive {}: {e}",
+                    path.display()
+                ),
+            }
+        }
+        Ok(archived)
+    }
+
+    /// Live and archived suggestions matching `text` and `user`, newest first.
+    fn search(&self, text: Option<&str>, user: Option<&str>) -> Result<Vec<Suggestion>, String> {
+        let mut all = self.archived()?;
+        all.extend(
+            self.inner
+                .lock()
+                .map_err(|e| e.to_string())?
+                .suggestions()
+                .iter()
+                .cloned(),
+        );
+        let text = text.map(str::to_lowercase).filter(|t| !t.trim().is_empty());
+        let user = user.map(str::to_lowercase).filter(|u| !u.trim().is_empty());
+        let mut results: Vec<Suggestion> = all
+            .into_iter()
+            .filter(|s| matches_search(s, text.as_deref(), user.as_deref()))
+            .collect();
+        results.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+        results.truncate(MAX_SEARCH_RESULTS);
+        Ok(results)
+    }
+
+    fn read(path: &std::path::Path) -> Result<Vec<Suggestion>, String> {
+        let Some(value) = persist::read_json(path)? else {
+            return Ok(Vec::new());
+        };
+        serde_json::from_value(value).map_err(|e| e.to_string())
+    }
+
+    /// Replace the in-memory list with the file contents after an external edit.
+    pub fn reload(&self) -> Result<Vec<Suggestion>, String> {
+        let suggestions = Self::read(&self.path)?;
+        let mut inner = self.inner.lock().map_err(|e| e.to_string())?;
+        inner.suggestions = suggestions.clone();
+        Ok(suggestions)
+    }
+
+    /// Add a suggestion, persisting and broadcasting the list if it was added.
+    fn add(&self, app: &tauri::AppHandle, new: NewSuggestion) -> Result<AddOutcome, String> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let outcome = self.update(app, |inner| {
+            let outcome = inner.add(new, now)?;
+            let added = matches!(outcome, AddOutcome::Added { .. });
+            Ok((outcome, added))
+        })?;
+        match &outcome {
+            AddOutcome::Added { suggestion } => info!(
+                "[suggestions] added {} \"{}\" by {}",
+                suggestion.hex_id, suggestion.text, suggestion.username
+            ),
+            other => info!("[suggestions] not added: {other:?}"),
         }
+        Ok(outcome)
+    }
+
+    /// Record a vote. Returns `false` if the user had already voted.
+    fn vote(&self, app: &tauri::AppHandle, hex_id: &str, user_id: &str) -> Result<bool, String> {
+        self.update(app, |inner| {
+            let changed = inner.vote(hex_id, user_id)?;
+            Ok((changed, changed))
+        })
+    }
+
+    /// Apply `change` and, if it reports a modification, persist the list and
+    /// broadcast it to every window.
+    fn update<T>(
+        &self,
+        app: &tauri::AppHandle,
+        change: impl FnOnce(&mut SuggestionBox) -> Result<(T, bool), String>,
+    ) -> Result<T, String> {
+        let mut inner = self.inner.lock().map_err(|e| e.to_string())?;
+        let (result, changed) = change(&mut inner)?;
+        if changed {
+            persist::write_json(&self.path, inner.suggestions())?;
+            if let Err(e) = app.emit(SUGGESTIONS_CHANGED_EVENT, inner.suggestions()) {
+                warn!("[suggestions] emit failed: {e}");
+            }
+        }
+        Ok(result)
     }
 }
 
 #[tauri::command]
 pub fn read_suggestions(
     state: tauri::State<'_, SuggestionsState>,
-) -> Result<Option<Value>, String> {
-    match std::fs::read_to_string(&state.path) {
-        Ok(json) => {
-            info!("[suggestions] loaded from {}", state.path.display());
-            let data: Value = serde_json::from_str(&json).map_err(|e| e.to_string())?;
-            Ok(Some(data))
-        }
-        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
-            info!("[suggestions] no file at {}", state.path.display());
-            Ok(None)
+) -> Result<Vec<Suggestion>, String> {
+    let inner = state.inner.lock().map_err(|e| e.to_string())?;
+    Ok(inner.suggestions().to_vec())
+}
+
+#[tauri::command]
+pub fn add_suggestion(
+    suggestion: NewSuggestion,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SuggestionsState>,
+) -> Result<AddOutcome, String> {
+    state.add(&app, suggestion)
+}
+
+#[tauri::command]
+pub fn vote_suggestion(
+    hex_id: String,
+    user_id: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SuggestionsState>,
+) -> Result<bool, String> {
+    state.vote(&app, &hex_id, &user_id)
+}
+
+/// Feed a channel event (JSON) to the suggestion box: redemptions of the
+/// configured reward and the chat triggers add suggestions and votes, whether
+/// or not a window is showing the widget.
+#[tauri::command]
+pub fn suggestion_event(
+    event: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SuggestionsState>,
+    settings: tauri::State<'_, SettingsState>,
+) -> Result<(), String> {
+    let event = ChannelEvent::parse(&event)?;
+    if !matches!(
+        event.kind,
+        ChannelEventType::Chat | ChannelEventType::ChannelPointsRedemption
+    ) {
+        return Ok(());
+    }
+    let Some(config) = settings
+        .load()?
+        .as_ref()
+        .and_then(SuggestionConfig::from_settings)
+    else {
+        return Ok(());
+    };
+    match intake(&event, &config) {
+        Some(Intake::Add(new)) => {
+            let redemption = new.redemption_id.clone().zip(new.reward_id.clone());
+            let outcome = state.add(&app, new);
+            if let Some((redemption_id, reward_id)) = redemption {
+                if turned_away(&outcome) {
+                    // Refund the points so the redemption doesn't linger in the queue
+                    spawn_redemption_update(
+                        &app,
+                        RedemptionUpdate {
+                            suggestion_id: None,
+                            redemption_id,
+                            status: RedemptionStatus::Canceled,
+                            error: None,
+                        },
+                        reward_id,
+                    );
+                }
+            }
+            outcome?;
         }
-        Err(e) => {
-            error!("[suggestions] read error: {e}");
-            Err(e.to_string())
+        Some(Intake::Vote { hex_id, user_id }) => {
+            if let Err(e) = state.vote(&app, &hex_id, &user_id) {
+                info!("[suggestions] vote ignored: {e}");
+            }
         }
+        None => {}
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn unvote_suggestion(
+    hex_id: String,
+    user_id: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SuggestionsState>,
+) -> Result<bool, String> {
+    state.update(&app, |inner| {
+        let changed = inner.unvote(&hex_id, &user_id)?;
+        Ok((changed, changed))
+    })
+}
+
+/// Mark a suggestion done (`done` defaults to true) or reopen it. Completing
+/// a redeemed suggestion fulfils its redemption on Twitch.
+#[tauri::command]
+pub fn complete_suggestion(
+    id: String,
+    done: Option<bool>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SuggestionsState>,
+) -> Result<(), String> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let done = done.unwrap_or(true);
+    let completed = state.update(&app, |inner| {
+        let changed = inner.complete(&id, done, now)?;
+        let completed = if changed && done {
+            inner.claim_fulfilment(&id)?
+        } else {
+            None
+        };
+        Ok((completed, changed))
+    })?;
+    if let Some(suggestion) = completed {
+        sync_redemption(&app, &suggestion, RedemptionStatus::Fulfilled);
     }
+    Ok(())
 }
 
+/// Delete a suggestion. Deleting one that is still active rejects it, which
+/// refunds its redemption unless `refund` is false.
 #[tauri::command]
-pub fn write_suggestions(
-    data: Value,
+pub fn delete_suggestion(
+    id: String,
+    refund: Option<bool>,
+    app: tauri::AppHandle,
     state: tauri::State<'_, SuggestionsState>,
 ) -> Result<(), String> {
-    if let Some(parent) = state.path.parent() {
-        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    let removed = state.update(&app, |inner| Ok((inner.delete(&id)?, true)))?;
+    info!(
+        "[suggestions] deleted {} \"{}\"",
+        removed.hex_id, removed.text
+    );
+    // A fulfilled redemption can no longer be refunded, even if the suggestion was reopened
+    if removed.status == SuggestionStatus::Active
+        && !removed.redemption_synced
+        && refund.unwrap_or(true)
+    {
+        sync_redemption(&app, &removed, RedemptionStatus::Canceled);
     }
-    let json = serde_json::to_string_pretty(&data).map_err(|e| e.to_string())?;
-    std::fs::write(&state.path, &json).map_err(|e| {
-        error!("[suggestions] write error: {e}");
-        e.to_string()
-    })?;
-    info!("[suggestions] saved to {}", state.path.display());
     Ok(())
 }
+
+/// Move completed suggestions into the dated archive. Only those completed at
+/// least `min_age_ms` ago are moved; all of them when omitted.
+#[tauri::command]
+pub fn archive_suggestions(
+    min_age_ms: Option<i64>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SuggestionsState>,
+) -> Result<usize, String> {
+    let cutoff = chrono::Utc::now().timestamp_millis() - min_age_ms.unwrap_or(0);
+    let archived = state.archive_done(cutoff)?;
+    if !archived.is_empty() {
+        let inner = state.inner.lock().map_err(|e| e.to_string())?;
+        if let Err(e) = app.emit(SUGGESTIONS_CHANGED_EVENT, inner.suggestions()) {
+            warn!("[suggestions] emit failed: {e}");
+        }
+    }
+    Ok(archived.len())
+}
+
+/// Search live and archived suggestions by text and/or username, newest first.
+#[tauri::command]
+pub fn search_suggestions(
+    text: Option<String>,
+    user: Option<String>,
+    state: tauri::State<'_, SuggestionsState>,
+) -> Result<Vec<Suggestion>, String> {
+    state.search(text.as_deref(), user.as_deref())
+}
+
+/// Export matching live and archived suggestions to `path`. The format is
+/// picked from the extension: `.csv`, or `.md` for a Markdown table.
+/// Returns the number of suggestions written.
+#[tauri::command]
+pub fn export_suggestions(
+    path: String,
+    text: Option<String>,
+    user: Option<String>,
+    state: tauri::State<'_, SuggestionsState>,
+) -> Result<usize, String> {
+    let dest = PathBuf::from(&path);
+    let extension = dest
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+    let mut suggestions = state.search(text.as_deref(), user.as_deref())?;
+    // Oldest first reads better in a review document
+    suggestions.reverse();
+    let contents = match extension.as_deref() {
+        Some("csv") => to_csv(&suggestions),
+        Some("md" | "markdown") => to_markdown(&suggestions),
+        _ => return Err("Export path must end in .csv or .md".into()),
+    };
+    std::fs::write(&dest, contents).map_err(|e| e.to_string())?;
+    info!(
+        "[suggestions] exported {} suggestions to {}",
+        suggestions.len(),
+        dest.display()
+    );
+    Ok(suggestions.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000_000;
+
+    fn chat(user_id: &str, text: &str) -> NewSuggestion {
+        NewSuggestion {
+            text: text.into(),
+            username: format!("{user_id}-name"),
+            user_id: user_id.into(),
+            redemption_id: None,
+            reward_id: None,
+        }
+    }
+
+    fn redemption(redemption_id: &str, text: &str) -> NewSuggestion {
+        NewSuggestion {
+            redemption_id: Some(redemption_id.into()),
+            reward_id: Some("reward-1".into()),
+            ..chat("viewer", text)
+        }
+    }
+
+    fn added(outcome: AddOutcome) -> Suggestion {
+        match outcome {
+            AddOutcome::Added { suggestion } => suggestion,
+            other => panic!("expected Added, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn add_assigns_hex_id_and_truncates_text() {
+        let mut inbox = SuggestionBox::default();
+        let s = added(inbox.add(redemption("r1", &"x".repeat(300)), NOW).unwrap());
+        assert!(normalise_hex_id(&s.hex_id).as_deref() == Some(s.hex_id.as_str()));
+        assert_eq!(s.text.chars().count(), MAX_TEXT_CHARS);
+        assert_eq!(s.status, SuggestionStatus::Active);
+        assert_eq!((s.votes, s.checked_at), (0, None));
+    }
+
+    #[test]
+    fn add_rejects_repeated_redemptions_and_chat_duplicates() {
+        let mut inbox = SuggestionBox::default();
+        let s = added(inbox.add(redemption("r1", "Play Minecraft"), NOW).unwrap());
+        assert!(matches!(
+            inbox.add(redemption("r1", "Something else"), NOW).unwrap(),
+            AddOutcome::Duplicate { hex_id } if hex_id == s.hex_id
+        ));
+        assert!(matches!(
+            inbox.add(chat("u1", "  play   MINECRAFT! "), NOW).unwrap(),
+            AddOutcome::Duplicate { hex_id } if hex_id == s.hex_id
+        ));
+        // A paid redemption is kept even when its text repeats
+        added(inbox.add(redemption("r2", "Play Minecraft"), NOW).unwrap());
+
+        // Chat text may repeat once the earlier suggestions are done
+        for done in inbox.suggestions().to_vec() {
+            inbox.complete(&done.id, true, NOW).unwrap();
+        }
+        added(inbox.add(chat("u1", "Play Minecraft"), NOW).unwrap());
+    }
+
+    #[test]
+    fn chat_suggestions_have_per_user_cooldown() {
+        let mut inbox = SuggestionBox::default();
+        let s = added(inbox.add(chat("u1", "First idea"), NOW).unwrap());
+        assert!(s.redemption_id.starts_with("chat-"));
+        assert!(matches!(
+            inbox.add(chat("u1", "Second idea"), NOW + 1_000).unwrap(),
+            AddOutcome::Cooldown {
+                remaining_ms: 29_000
+            }
+        ));
+        added(inbox.add(chat("u2", "Other user"), NOW + 1_000).unwrap());
+        added(
+            inbox
+                .add(chat("u1", "Later idea"), NOW + CHAT_COOLDOWN_MS)
+                .unwrap(),
+        );
+        // Redemptions cost channel points and are not rate limited
+        added(inbox.add(redemption("r1", "Paid idea"), NOW).unwrap());
+    }
+
+    #[test]
+    fn add_rejects_empty_chat_text_and_missing_user() {
+        let mut inbox = SuggestionBox::default();
+        assert!(inbox.add(chat("u1", "   "), NOW).is_err());
+        assert!(inbox.add(chat("", "Anonymous"), NOW).is_err());
+        // Rewards without user input still record the redemption
+        let empty = added(inbox.add(redemption("r1", "  "), NOW).unwrap());
+        assert_eq!(empty.text, "");
+    }
+
+    #[test]
+    fn votes_are_deduplicated_and_can_be_withdrawn() {
+        let mut inbox = SuggestionBox::default();
+        let s = added(inbox.add(redemption("r1", "Idea"), NOW).unwrap());
+        let lower = s.hex_id.to_ascii_lowercase();
+
+        assert!(inbox.vote(&lower, "v1").unwrap());
+        assert!(!inbox.vote(&s.hex_id, "v1").unwrap());
+        assert!(inbox.vote(&s.hex_id, "v2").unwrap());
+        assert_eq!(inbox.suggestions()[0].votes, 2);
+
+        assert!(inbox.unvote(&s.hex_id, "v1").unwrap());
+        assert!(!inbox.unvote(&s.hex_id, "v1").unwrap());
+        assert_eq!(inbox.suggestions()[0].voters, ["v2"]);
+
+        assert!(inbox.vote("ZZZ", "v3").is_err());
+        inbox.complete(&s.id, true, NOW).unwrap();
+        assert!(inbox.vote(&s.hex_id, "v3").is_err());
+    }
+
+    #[test]
+    fn single_digit_hex_ids_are_padded() {
+        assert_eq!(normalise_hex_id("a").as_deref(), Some("0A"));
+        assert_eq!(normalise_hex_id("3f").as_deref(), Some("3F"));
+        assert_eq!(normalise_hex_id("100"), None);
+        assert_eq!(normalise_hex_id("zz"), None);
+    }
+
+    #[test]
+    fn complete_toggles_status_and_checked_at() {
+        let mut inbox = SuggestionBox::default();
+        let s = added(inbox.add(redemption("r1", "Idea"), NOW).unwrap());
+        assert!(inbox.complete(&s.id, true, NOW + 5).unwrap());
+        assert!(!inbox.complete(&s.id, true, NOW + 6).unwrap());
+        assert_eq!(inbox.suggestions()[0].checked_at, Some(NOW + 5));
+        assert!(inbox.complete(&s.id, false, NOW + 7).unwrap());
+        assert_eq!(inbox.suggestions()[0].checked_at, None);
+        assert!(inbox.complete("missing", true, NOW).is_err());
+    }
+
+    #[test]
+    fn redemption_is_fulfilled_only_once() {
+        let mut inbox = SuggestionBox::default();
+        let s = added(inbox.add(redemption("r1", "Idea"), NOW).unwrap());
+        assert!(inbox.claim_fulfilment(&s.id).unwrap().is_none());
+
+        inbox.complete(&s.id, true, NOW).unwrap();
+        let claimed = inbox.claim_fulfilment(&s.id).unwrap().unwrap();
+        assert!(claimed.redemption_synced);
+        inbox.complete(&s.id, false, NOW).unwrap();
+        inbox.complete(&s.id, true, NOW).unwrap();
+        assert!(inbox.claim_fulfilment(&s.id).unwrap().is_none());
+
+        // A rejected fulfilment can be retried
+        assert!(inbox.release_fulfilment(&s.id));
+        assert!(!inbox.release_fulfilment(&s.id));
+        assert!(inbox.claim_fulfilment(&s.id).unwrap().is_some());
+
+        let chat = added(inbox.add(chat("u1", "Chat idea"), NOW).unwrap());
+        inbox.complete(&chat.id, true, NOW).unwrap();
+        assert!(inbox.claim_fulfilment(&chat.id).unwrap().is_none());
+    }
+
+    #[test]
+    fn hex_ids_are_unique_and_recycled_from_done() {
+        let mut inbox = SuggestionBox::default();
+        let all: Vec<Suggestion> = (0..HEX_ID_COUNT)
+            .map(|i| {
+                added(
+                    inbox
+                        .add(redemption(&format!("r{i}"), &format!("idea {i}")), NOW)
+                        .unwrap(),
+                )
+            })
+            .collect();
+        let unique: HashSet<&str> = all.iter().map(|s| s.hex_id.as_str()).collect();
+        assert_eq!(unique.len(), HEX_ID_COUNT);
+        assert!(matches!(
+            inbox.add(redemption("full", "one more"), NOW).unwrap(),
+            AddOutcome::Full
+        ));
+
+        inbox.complete(&all[7].id, true, NOW).unwrap();
+        let recycled = added(inbox.add(redemption("again", "one more"), NOW).unwrap());
+        assert_eq!(recycled.hex_id, all[7].hex_id);
+
+        // Deleting frees the id outright
+        inbox.delete(&all[3].id).unwrap();
+        let reused = added(inbox.add(redemption("freed", "yet another"), NOW).unwrap());
+        assert_eq!(reused.hex_id, all[3].hex_id);
+    }
+
+    #[test]
+    fn redemptions_without_room_or_user_are_turned_away() {
+        let mut inbox = SuggestionBox::default();
+        for i in 0..HEX_ID_COUNT {
+            let outcome = inbox.add(redemption(&format!("r{i}"), "idea"), NOW);
+            assert!(!turned_away(&outcome));
+        }
+        assert!(!turned_away(&inbox.add(redemption("r0", "again"), NOW)));
+        assert!(turned_away(&inbox.add(redemption("full", "idea"), NOW)));
+
+        let anonymous = NewSuggestion {
+            user_id: String::new(),
+            ..redemption("anon", "idea")
+        };
+        assert!(turned_away(&SuggestionBox::default().add(anonymous, NOW)));
+    }
+
+    #[test]
+    fn take_done_only_moves_old_completed_suggestions() {
+        let mut inbox = SuggestionBox::default();
+        let old = added(inbox.add(redemption("r1", "Old"), NOW).unwrap());
+        let recent = added(inbox.add(redemption("r2", "Recent"), NOW).unwrap());
+        added(inbox.add(redemption("r3", "Open"), NOW).unwrap());
+        inbox.complete(&old.id, true, NOW + 10).unwrap();
+        inbox.complete(&recent.id, true, NOW + 1_000).unwrap();
+
+        let taken = inbox.take_done(NOW + 500);
+        assert_eq!(taken.len(), 1);
+        assert_eq!(taken[0].id, old.id);
+        assert_eq!(inbox.suggestions().len(), 2);
+    }
+
+    #[test]
+    fn search_matches_text_and_user_case_insensitively() {
+        let mut inbox = SuggestionBox::default();
+        let s = added(inbox.add(chat("u1", "Play Minecraft"), NOW).unwrap());
+        assert!(matches_search(&s, Some("minecraft"), None));
+        assert!(matches_search(&s, None, Some("u1-na")));
+        assert!(matches_search(&s, None, Some("u1")));
+        assert!(!matches_search(&s, Some("zelda"), None));
+        assert!(!matches_search(&s, Some("minecraft"), Some("someone")));
+    }
+
+    #[test]
+    fn exports_escape_csv_and_markdown_cells() {
+        let mut inbox = SuggestionBox::default();
+        added(
+            inbox
+                .add(chat("u1", "Say \"hi\", then | leave"), NOW)
+                .unwrap(),
+        );
+        let csv = to_csv(inbox.suggestions());
+        assert!(csv.starts_with("ID,Status,Votes,Suggestion,User,Created,Completed\n"));
+        assert!(csv.contains(",\"Say \"\"hi\"\", then | leave\","));
+
+        let md = to_markdown(inbox.suggestions());
+        assert!(md.contains("| Say \"hi\", then \\| leave |"));
+        assert_eq!(md.lines().count(), 3);
+    }
+
+    fn event(kind: &str, data: Value) -> ChannelEvent {
+        ChannelEvent::parse(
+            &serde_json::json!({ "type": kind, "timestamp": NOW, "data": data }).to_string(),
+        )
+        .unwrap()
+    }
+
+    fn config(reward_id: &str) -> SuggestionConfig {
+        SuggestionConfig {
+            reward_id: reward_id.into(),
+            ..SuggestionConfig::default()
+        }
+    }
+
+    fn chat_event(text: &str, user_id: &str) -> ChannelEvent {
+        event(
+            "chat",
+            serde_json::json!({ "text": text, "userId": user_id, "username": "viewer1" }),
+        )
+    }
+
+    #[test]
+    fn intake_matches_configured_redemptions() {
+        let redeemed = |reward: &str| {
+            event(
+                "channel_points_redemption",
+                serde_json::json!({
+                    "id": "redemption-1",
+                    "reward": { "id": reward },
+                    "user_input": "My suggestion",
+                    "user_name": "testuser",
+                    "user_id": "user-1",
+                }),
+            )
+        };
+        assert_eq!(
+            intake(&redeemed("reward-1"), &config("reward-1")),
+            Some(Intake::Add(NewSuggestion {
+                text: "My suggestion".into(),
+                username: "testuser".into(),
+                user_id: "user-1".into(),
+                redemption_id: Some("redemption-1".into()),
+                reward_id: Some("reward-1".into()),
+            }))
+        );
+        assert_eq!(intake(&redeemed("other"), &config("reward-1")), None);
+        assert_eq!(intake(&redeemed("reward-1"), &config("")), None);
+
+        let mut simulated = redeemed("reward-1");
+        simulated.data.insert("simulated".into(), Value::Bool(true));
+        assert_eq!(intake(&simulated, &config("reward-1")), None);
+    }
+
+    #[test]
+    fn intake_parses_chat_votes_and_fallback_suggestions() {
+        let vote = |hex_id: &str| {
+            Some(Intake::Vote {
+                hex_id: hex_id.into(),
+                user_id: "voter-1".into(),
+            })
+        };
+        let with_reward = config("reward-1");
+        assert_eq!(
+            intake(&chat_event("!vote 2A", "voter-1"), &with_reward),
+            vote("2A")
+        );
+        assert_eq!(
+            intake(&chat_event("!VOTE a", "voter-1"), &with_reward),
+            vote("0A")
+        );
+        for ignored in ["!voteforpedro", "!vote ZZZ", "!vote", "!suggest Something"] {
+            assert_eq!(intake(&chat_event(ignored, "voter-1"), &with_reward), None);
+        }
+        assert_eq!(intake(&chat_event("!vote 2A", ""), &with_reward), None);
+
+        let no_reward = config("");
+        let Some(Intake::Add(new)) =
+            intake(&chat_event("!SUGGEST Play Minecraft", "u1"), &no_reward)
+        else {
+            panic!("expected a chat suggestion");
+        };
+        assert_eq!(
+            (new.text.as_str(), new.redemption_id),
+            ("Play Minecraft", None)
+        );
+        assert_eq!(intake(&chat_event("!suggest   ", "u1"), &no_reward), None);
+        assert_eq!(intake(&chat_event("!suggest hi", ""), &no_reward), None);
+
+        let mut simulated = chat_event("!suggest Replayed idea", "u1");
+        simulated.data.insert("simulated".into(), Value::Bool(true));
+        assert_eq!(intake(&simulated, &no_reward), None);
+
+        let idea = SuggestionConfig {
+            suggest_trigger: "!idea".into(),
+            ..config("")
+        };
+        assert!(intake(&chat_event("!idea Great idea", "u1"), &idea).is_some());
+    }
+
+    #[test]
+    fn config_comes_from_the_first_suggestion_box() {
+        let settings = |instances: Value| {
+            Settings::from_value(serde_json::json!({ "overlay": { "instances": instances } }))
+                .unwrap()
+        };
+        let inst = |id: &str, type_id: &str, config: Value| {
+            serde_json::json!({
+                "instanceId": id, "typeId": type_id, "x": 0, "y": 0,
+                "width": 100, "height": 100, "visible": false, "config": config,
+            })
+        };
+        assert!(SuggestionConfig::from_settings(&settings(serde_json::json!([]))).is_none());
+
+        let found = SuggestionConfig::from_settings(&settings(serde_json::json!([
+            inst("chat", "chat", serde_json::json!({ "rewardId": "nope" })),
+            inst(
+                "box",
+                "suggestion-box",
+                serde_json::json!({ "rewardId": "reward-1", "maxActive": 7 })
+            ),
+        ])))
+        .unwrap();
+        assert_eq!(found.reward_id, "reward-1");
+        assert_eq!(found.vote_trigger, "!vote");
+    }
+}
@@ -0,0 +1,200 @@
+//! System tray icon with quick actions.
+//!
+//! The overlay window is undecorated and always on top, so the tray is the
+//! way to reach the app without hotkeys. The menu is rebuilt whenever the
+//! login, the preset list or the active preset changes.
+
+use crate::auth::{self, AuthState, AuthStatusResponse, AUTH_CHANGED_EVENT};
+use crate::hotkeys;
+use crate::presets::{self, PRESETS_CHANGED_EVENT};
+use crate::scenes::{self, SceneState, SCENE_SWITCHED_EVENT};
+use crate::settings::HotkeyAction;
+use crate::watcher::SETTINGS_CHANGED_EVENT;
+use std::sync::{Arc, Mutex};
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Listener, Manager, Wry};
+use tracing::{info, warn};
+
+const TRAY_ID: &str = "main";
+const PRESET_PREFIX: &str = "preset:";
+
+/// Events after which the menu may be out of date.
+const REFRESH_EVENTS: [&str; 4] = [
+    AUTH_CHANGED_EVENT,
+    PRESETS_CHANGED_EVENT,
+    SCENE_SWITCHED_EVENT,
+    SETTINGS_CHANGED_EVENT,
+];
+
+/// What the menu shows. The menu is rebuilt only when this changes.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct TraySnapshot {
+    username: Option<String>,
+    /// Preset slug and display name.
+    presets: Vec<(String, String)>,
+    active: Option<String>,
+}
+
+/// The snapshot the current menu was built from.
+pub struct TrayState {
+    shown: Mutex<TraySnapshot>,
+}
+
+fn snapshot(app: &AppHandle) -> TraySnapshot {
+    let presets = presets::list_presets()
+        .map(|list| list.into_iter().map(|p| (p.name, p.display_name)).collect())
+        .unwrap_or_default();
+    TraySnapshot {
+        username: app.state::<Arc<AuthState>>().stored_username(),
+        presets,
+        active: app.state::<SceneState>().active(),
+    }
+}
+
+fn build_menu(app: &AppHandle, snap: &TraySnapshot) -> tauri::Result<Menu<Wry>> {
+    let presets = Submenu::with_id(app, "presets", "Switch preset", true)?;
+    if snap.presets.is_empty() {
+        presets.append(&MenuItem::with_id(
+            app,
+            "presets-empty",
+            "No presets saved",
+            false,
+            None::<&str>,
+        )?)?;
+    }
+    for (name, display_name) in &snap.presets {
+        let active = snap.active.as_ref() == Some(name);
+        presets.append(&CheckMenuItem::with_id(
+            app,
+            format!("{PRESET_PREFIX}{name}"),
+            display_name,
+            true,
+            active,
+            None::<&str>,
+        )?)?;
+    }
+
+    let account = match &snap.username {
+        Some(name) => format!("Logged in as {name}"),
+        None => "Not logged in".to_string(),
+    };
+    Menu::with_items(
+        app,
+        &[
+            &MenuItem::with_id(
+                app,
+                "toggle-overlay",
+                "Show/hide overlay",
+                true,
+                None::<&str>,
+            )?,
+            &MenuItem::with_id(app, "edit-mode", "Edit mode", true, None::<&str>)?,
+            &presets,
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(app, "account", account, false, None::<&str>)?,
+            &MenuItem::with_id(
+                app,
+                "logout",
+                "Log out",
+                snap.username.is_some(),
+                None::<&str>,
+            )?,
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(app, "open-logs", "Open log folder", true, None::<&str>)?,
+            &MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?,
+        ],
+    )
+}
+
+/// Rebuild the menu if login or preset state changed since it was built.
+pub fn refresh(app: &AppHandle) {
+    let snap = snapshot(app);
+    let state = app.state::<TrayState>();
+    let Ok(mut shown) = state.shown.lock() else {
+        return;
+    };
+    if *shown == snap {
+        return;
+    }
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let result = build_menu(app, &snap).and_then(|menu| {
+        tray.set_menu(Some(menu))?;
+        tray.set_tooltip(Some(tooltip(&snap)))
+    });
+    match result {
+        Ok(()) => *shown = snap,
+        Err(e) => warn!("[tray] failed to update menu: {e}"),
+    }
+}
+
+fn tooltip(snap: &TraySnapshot) -> String {
+    match &snap.username {
+        Some(name) => format!("Streamer – {name}"),
+        None => "Streamer".to_string(),
+    }
+}
+
+fn on_menu(app: &AppHandle, id: &str) {
+    match id {
+        "toggle-overlay" => hotkeys::run_action(app, &HotkeyAction::ToggleOverlay),
+        "edit-mode" => hotkeys::run_action(app, &HotkeyAction::ToggleEditMode),
+        "logout" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let state = app.state::<Arc<AuthState>>();
+                if let Err(e) = auth::logout(&state).await {
+                    warn!("[tray] logout failed: {e}");
+                    return;
+                }
+                auth::announce_auth(&app, &AuthStatusResponse::logged_out());
+            });
+        }
+        "open-logs" => {
+            if let Err(e) = crate::open_log_folder() {
+                warn!("[tray] failed to open log folder: {e}");
+            }
+        }
+        "quit" => {
+            info!("[tray] quit");
+            app.exit(0);
+        }
+        id => {
+            if let Some(name) = id.strip_prefix(PRESET_PREFIX) {
+                if let Err(e) = scenes::switch_to(app, name, "tray") {
+                    warn!("[tray] failed to switch preset: {e}");
+                }
+                // Clicking toggles the native check mark; rebuild so it follows the active preset
+                if let Ok(mut shown) = app.state::<TrayState>().shown.lock() {
+                    *shown = TraySnapshot::default();
+                }
+                refresh(app);
+            }
+        }
+    }
+}
+
+/// Create the tray icon and keep its menu current.
+pub fn start(app: &AppHandle) -> tauri::Result<()> {
+    let snap = snapshot(app);
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip(tooltip(&snap))
+        .menu(&build_menu(app, &snap)?)
+        .show_menu_on_left_click(true)
+        .on_menu_event(|app, event| on_menu(app, event.id().as_ref()));
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+    app.manage(TrayState {
+        shown: Mutex::new(snap),
+    });
+
+    for event in REFRESH_EVENTS {
+        let handle = app.clone();
+        app.listen(event, move |_| refresh(&handle));
+    }
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use crate::persist;
+use crate::settings::SettingsState;
+use crate::suggestions::SuggestionsState;
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+use tracing::{info, warn};
+
+/// Emitted to every window with the migrated settings after an external edit.
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+/// Emitted to every window with the suggestion list whenever it changes.
+pub const SUGGESTIONS_CHANGED_EVENT: &str = "suggestions-changed";
+
+const SETTINGS_FILE: &str = "settings.json";
+const SUGGESTIONS_FILE: &str = "suggestions.json";
+
+/// Quiet period before a burst of file system events is reported.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// A watched file that changed and must be re-broadcast.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Change {
+    Settings,
+    Suggestions,
+}
+
+/// Decide whether a file system event on `path` is an external change worth
+/// broadcasting. Unrelated files, our own writes and writes that leave the
+/// contents unchanged (`last_seen` holds the last hash per path) are ignored.
+fn classify(
+    path: &Path,
+    last_seen: &mut HashMap<PathBuf, u64>,
+    is_own_write: impl Fn(&Path, &[u8]) -> bool,
+    read: impl Fn(&Path) -> Option<Vec<u8>>,
+) -> Option<Change> {
+    let change = match path.file_name().and_then(|n| n.to_str())? {
+        SETTINGS_FILE => Change::Settings,
+        SUGGESTIONS_FILE => Change::Suggestions,
+        _ => return None,
+    };
+    let contents = read(path)?;
+    let hash = persist::content_hash(&contents);
+    if last_seen.insert(path.to_path_buf(), hash) == Some(hash) || is_own_write(path, &contents) {
+        return None;
+    }
+    Some(change)
+}
+
+/// Keeps the data directory watcher alive for the lifetime of the app.
+pub struct DataWatcher {
+    _debouncer: Mutex<Debouncer<RecommendedWatcher>>,
+}
+
+impl DataWatcher {
+    /// Watch `data_dir` for external changes to the settings and suggestions files.
+    ///
+    /// The directory is watched rather than the files themselves, because
+    /// atomic writes replace the file and would orphan a per-file watch.
+    pub fn start(app: tauri::AppHandle, data_dir: &Path) -> Result<Self, String> {
+        // Content hash of the last version broadcast per file, to skip no-op events
+        let mut last_seen: HashMap<PathBuf, u64> = HashMap::new();
+
+        let mut debouncer = new_debouncer(DEBOUNCE, move |result: DebounceEventResult| {
+            let events = match result {
+                Ok(events) => events,
+                Err(e) => {
+                    warn!("[watcher] error: {e}");
+                    return;
+                }
+            };
+            let mut changes = Vec::new();
+            for event in events {
+                let change = classify(&event.path, &mut last_seen, persist::is_own_write, |p| {
+                    std::fs::read(p).ok()
+                });
+                if let Some(change) = change.filter(|c| !changes.contains(c)) {
+                    info!("[watcher] external change to {}", event.path.display());
+                    changes.push(change);
+                }
+            }
+            for change in changes {
+                match change {
+                    Change::Settings => broadcast_settings(&app),
+                    Change::Suggestions => broadcast_suggestions(&app),
+                }
+            }
+        })
+        .map_err(|e| e.to_string())?;
+
+        debouncer
+            .watcher()
+            .watch(data_dir, RecursiveMode::NonRecursive)
+            .map_err(|e| e.to_string())?;
+        info!("[watcher] watching {}", data_dir.display());
+
+        Ok(Self {
+            _debouncer: Mutex::new(debouncer),
+        })
+    }
+}
+
+fn broadcast_settings(app: &tauri::AppHandle) {
+    match app.state::<SettingsState>().load() {
+        Ok(Some(settings)) => {
+            if let Err(e) = app.emit(SETTINGS_CHANGED_EVENT, &settings) {
+                warn!("[watcher] emit failed: {e}");
+            }
+        }
+        Ok(None) => {}
+        // Likely a half-finished manual edit; the next save triggers another event
+        Err(e) => warn!("[watcher] ignoring unreadable settings: {e}"),
+    }
+}
+
+fn broadcast_suggestions(app: &tauri::AppHandle) {
+    match app.state::<SuggestionsState>().reload() {
+        Ok(suggestions) => {
+            if let Err(e) = app.emit(SUGGESTIONS_CHANGED_EVENT, &suggestions) {
+                warn!("[watcher] emit failed: {e}");
+            }
+        }
+        Err(e) => warn!("[watcher] ignoring unreadable suggestions: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_external_changes_once_per_content() {
+        let dir = Path::new("/data");
+        let mut seen = HashMap::new();
+        let never_own = |_: &Path, _: &[u8]| false;
+        let read = |_: &Path| Some(b"{\"v\":1}".to_vec());
+
+        let settings = dir.join(SETTINGS_FILE);
+        assert_eq!(
+            classify(&settings, &mut seen, never_own, read),
+            Some(Change::Settings)
+        );
+        // The same contents again, e.g. a second event from one save, is a no-op
+        assert_eq!(classify(&settings, &mut seen, never_own, read), None);
+        assert_eq!(
+            classify(&dir.join(SUGGESTIONS_FILE), &mut seen, never_own, read),
+            Some(Change::Suggestions)
+        );
+        assert_eq!(
+            classify(&dir.join("settings.json.tmp"), &mut seen, never_own, read),
+            None
+        );
+        assert_eq!(
+            classify(&dir.join("other.json"), &mut seen, never_own, read),
+            None
+        );
+        // Deleted or unreadable files are skipped
+        assert_eq!(classify(&settings, &mut seen, never_own, |_| None), None);
+    }
+
+    #[test]
+    fn suppresses_own_writes() {
+        let dir = std::env::temp_dir().join(format!("watcher-test-{}", uuid::Uuid::new_v4()));
+        let path = dir.join(SETTINGS_FILE);
+        let read = |p: &Path| std::fs::read(p).ok();
+        let mut seen = HashMap::new();
+
+        persist::write_atomic(&path, b"{\"theme\":\"dark\"}").unwrap();
+        assert_eq!(
+            classify(&path, &mut seen, persist::is_own_write, read),
+            None
+        );
+
+        std::fs::write(&path, b"{\"theme\":\"light\"}").unwrap();
+        assert_eq!(
+            classify(&path, &mut seen, persist::is_own_write, read),
+            Some(Change::Settings)
+        );
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
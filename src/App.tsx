@@ -1,20 +1,30 @@
 import { invoke } from '@tauri-apps/api/core'
-import { register, unregister } from '@tauri-apps/plugin-global-shortcut'
 import { useEffect } from 'react'
 import { Toaster } from 'react-hot-toast'
 import { initSoundAlerts } from './audio/listener'
+import { startBackendEvents, stopBackendEvents } from './events/backend'
 import { startFileLogger, stopFileLogger } from './events/file-logger'
+import { startHotkeys, stopHotkeys } from './events/hotkeys'
+import { startSceneAutomation, stopSceneAutomation } from './events/scenes'
+import { startSuggestionIntake, stopSuggestionIntake } from './events/suggestions'
 import {
   useSecondaryWindow,
   startBroadcasting,
   stopBroadcasting,
-  closeAllMonitorWindows,
+  listenMonitorWarnings,
 } from './multimonitor'
 import { useOverlayStore } from './stores/overlay'
-import { hydrate, hydrateChatHistory, startAutoSave } from './stores/persistence'
+import {
+  hydrate,
+  hydrateChatHistory,
+  startAutoSave,
+  startSettingsSync,
+  stopSettingsSync,
+} from './stores/persistence'
 import { useTwitchStore } from './stores/twitch'
-import { checkAuth } from './twitch/auth'
+import { checkAuth, listenAuthChanges } from './twitch/auth'
 import { fetchBadges } from './twitch/badges'
+import { listenClipToasts } from './twitch/clips'
 import { connectEventSub, disconnectEventSub } from './twitch/eventsub'
 import {
   startFollowerPolling,
@@ -78,9 +88,7 @@ function GuideLines() {
 function App() {
   const isSecondary = useSecondaryWindow()
   const overlayVisible = useOverlayStore((s) => s.overlayVisible)
-  const toggleOverlayVisible = useOverlayStore((s) => s.toggleOverlayVisible)
   const editMode = useOverlayStore((s) => s.editMode)
-  const toggleEditMode = useOverlayStore((s) => s.toggleEditMode)
   const seedIfNeeded = useOverlayStore((s) => s.seedIfNeeded)
   const hydrated = useOverlayStore((s) => s.hydrated)
   const instances = useOverlayStore((s) => s.instances)
@@ -115,34 +123,78 @@ function App() {
     }
   }, [seedIfNeeded, isSecondary])
 
+  // Forward backend-emitted channel events (replay, simulator) to the local bus
+  useEffect(() => {
+    startBackendEvents().catch(console.error)
+    return () => stopBackendEvents()
+  }, [])
+
+  // Apply backend scene switches (all windows); feed events to the engine (primary only)
+  useEffect(() => {
+    startSceneAutomation(!isSecondary).catch(console.error)
+    return () => stopSceneAutomation()
+  }, [isSecondary])
+
+  // Record suggestions and votes in the backend, with or without the widget (primary only)
+  useEffect(() => {
+    if (isSecondary) return
+    startSuggestionIntake()
+    return () => stopSuggestionIntake()
+  }, [isSecondary])
+
+  // Pick up external edits to settings.json (all windows)
+  useEffect(() => {
+    startSettingsSync().catch(console.error)
+    return () => stopSettingsSync()
+  }, [])
+
   // Start broadcasting state to secondary windows (primary only)
   useEffect(() => {
     if (isSecondary) return
     startBroadcasting()
     return () => {
       stopBroadcasting()
-      closeAllMonitorWindows().catch(console.error)
     }
   }, [isSecondary])
 
-  // Global shortcuts (primary only)
+  // Follow logouts from the tray menu (primary only)
   useEffect(() => {
     if (isSecondary) return
-    register('Ctrl+Shift+I', (e) => {
-      if (e.state === 'Pressed') toggleEditMode()
-    }).catch(console.error)
-    register('Ctrl+Shift+O', (e) => {
-      if (e.state === 'Pressed') toggleOverlayVisible()
-    }).catch(console.error)
+    const unlisten = listenAuthChanges()
     return () => {
-      unregister('Ctrl+Shift+I').catch(console.error)
-      unregister('Ctrl+Shift+O').catch(console.error)
+      unlisten.then((fn) => fn()).catch(console.error)
     }
-  }, [toggleEditMode, toggleOverlayVisible, isSecondary])
+  }, [isSecondary])
+
+  // Warn when a selected monitor is unplugged (primary only)
+  useEffect(() => {
+    if (isSecondary) return
+    const unlisten = listenMonitorWarnings()
+    return () => {
+      unlisten.then((fn) => fn()).catch(console.error)
+    }
+  }, [isSecondary])
+
+  // Announce clips created by hotkey or chat command (primary only)
+  useEffect(() => {
+    if (isSecondary) return
+    const unlisten = listenClipToasts()
+    return () => {
+      unlisten.then((fn) => fn()).catch(console.error)
+    }
+  }, [isSecondary])
+
+  // Global hotkeys are registered by the backend; run their actions (primary only)
+  useEffect(() => {
+    if (isSecondary) return
+    startHotkeys().catch(console.error)
+    return () => stopHotkeys()
+  }, [isSecondary])
 
-  // Cursor passthrough
+  // Cursor passthrough; outside edit mode the hit-test service manages it per widget
   useEffect(() => {
     invoke('set_ignore_cursor', { ignore: !editMode }).catch(console.error)
+    invoke('set_hit_test_paused', { paused: editMode }).catch(console.error)
   }, [editMode])
 
   // EventSub + follower polling when authenticated (primary only)
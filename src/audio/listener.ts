@@ -15,8 +15,15 @@ export function initSoundAlerts(): () => void {
     if (!soundEnabled) return
 
     const mapping = soundMappings[event.type]
-    if (!mapping?.enabled || !mapping.sound) return
+    if (mapping?.enabled && mapping.sound) playSound(mapping.sound, soundVolume)
 
-    playSound(mapping.sound, soundVolume)
+    // Managed sound rewards are mapped per reward as `reward:{id}`
+    if (event.type === 'channel_points_redemption') {
+      const reward = (event.data as { reward?: { id?: string } }).reward
+      const rewardMapping = reward?.id ? soundMappings[`reward:${reward.id}`] : undefined
+      if (rewardMapping?.enabled && rewardMapping.sound) {
+        playSound(rewardMapping.sound, soundVolume)
+      }
+    }
   })
 }
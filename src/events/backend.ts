@@ -0,0 +1,22 @@
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import { publish, type ChannelEvent } from './bus'
+
+/**
+ * Tauri event carrying channel events produced by the backend
+ * (replay, simulator, markers, clips).
+ */
+const CHANNEL_EVENT = 'channel-event'
+
+let unlistenFn: UnlistenFn | null = null
+
+/** Start forwarding backend-emitted channel events onto the local event bus. */
+export async function startBackendEvents(): Promise<void> {
+  if (unlistenFn) return
+  unlistenFn = await listen<ChannelEvent>(CHANNEL_EVENT, (event) => publish(event.payload))
+}
+
+/** Stop forwarding backend-emitted channel events. */
+export function stopBackendEvents(): void {
+  unlistenFn?.()
+  unlistenFn = null
+}
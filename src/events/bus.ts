@@ -16,6 +16,8 @@ export type ChannelEventType =
   | 'follower_count_update'
   | 'viewer_count_update'
   | 'channel_points_redemption'
+  | 'stream_marker'
+  | 'clip_created'
 
 export interface ChannelEvent {
   type: ChannelEventType
@@ -0,0 +1,16 @@
+import { invoke } from '@tauri-apps/api/core'
+
+/**
+ * Address and token of the local control API.
+ * Mirrors `ControlApiInfo` in `src-tauri/src/control.rs`.
+ */
+export interface ControlApiInfo {
+  url: string
+  /** Sent as `Authorization: Bearer <token>` with every request. */
+  token: string
+}
+
+/** Where the control API listens and the token it expects. */
+export function getControlApi(): Promise<ControlApiInfo> {
+  return invoke<ControlApiInfo>('get_control_api')
+}
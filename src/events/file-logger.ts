@@ -3,11 +3,17 @@ import { useOverlayStore } from '../stores/overlay'
 import { useTwitchStore } from '../stores/twitch'
 import { subscribe, type ChannelEvent } from './bus'
 
+/** Event types the backend writes to the log itself before announcing them. */
+const BACKEND_LOGGED = new Set<ChannelEvent['type']>(['stream_marker', 'clip_created'])
+
 let unsubscribe: (() => void) | null = null
 
 function handleEvent(event: ChannelEvent) {
   const { fileLogging } = useOverlayStore.getState()
   if (!fileLogging) return
+  // Replayed and synthetic events must not pollute the real channel log
+  if (event.data.simulated) return
+  if (BACKEND_LOGGED.has(event.type)) return
 
   const { channel } = useTwitchStore.getState()
   if (!channel) return
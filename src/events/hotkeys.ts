@@ -0,0 +1,96 @@
+import { invoke } from '@tauri-apps/api/core'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import toast from 'react-hot-toast'
+import { playSound } from '../audio/player'
+import { useOverlayStore } from '../stores/overlay'
+import { clearChatMessages } from '../widgets/chat/chat-state'
+
+/** Tauri event emitted by the backend when a frontend-handled hotkey fires. */
+const HOTKEY_ACTION_EVENT = 'hotkey-action'
+
+/** Tauri event emitted by the backend after (re)registering hotkeys. */
+const HOTKEY_STATUS_EVENT = 'hotkey-status'
+
+/** Hotkey actions the backend leaves to the frontend. */
+type HotkeyAction =
+  | { kind: 'toggleEditMode' }
+  | { kind: 'toggleOverlay' }
+  | { kind: 'playSound'; sound: string }
+  | { kind: 'clearChat' }
+
+/** Registration outcome for one binding, as reported by the backend. */
+export type HotkeyStatus = { id: string; shortcut: string } & (
+  | { status: 'registered' | 'disabled' }
+  | { status: 'invalid' | 'failed'; message: string }
+  | { status: 'conflict'; with: string }
+)
+
+/**
+ * Listeners of the current start, set before they are registered so a stop
+ * issued while they are still pending (e.g. a StrictMode remount) removes them.
+ */
+let listening: Promise<UnlistenFn[]> | null = null
+
+function runAction(action: HotkeyAction): void {
+  const store = useOverlayStore.getState()
+  switch (action.kind) {
+    case 'toggleEditMode':
+      store.toggleEditMode()
+      break
+    case 'toggleOverlay':
+      store.toggleOverlayVisible()
+      break
+    case 'playSound':
+      playSound(action.sound, store.soundVolume)
+      break
+    case 'clearChat':
+      clearChatMessages()
+      break
+  }
+}
+
+/** Describe a binding that could not be registered, or null if it is fine. */
+export function hotkeyProblem(s: HotkeyStatus): string | null {
+  switch (s.status) {
+    case 'invalid':
+      return `${s.shortcut} is not a valid shortcut: ${s.message}`
+    case 'conflict':
+      return `${s.shortcut} is already used by ${s.with}`
+    case 'failed':
+      return `${s.shortcut} could not be registered: ${s.message}`
+    default:
+      return null
+  }
+}
+
+function reportProblems(statuses: HotkeyStatus[]): void {
+  for (const s of statuses) {
+    const problem = hotkeyProblem(s)
+    if (problem) toast.error(`Hotkey ${problem}`)
+  }
+}
+
+/**
+ * Carry out hotkey actions sent by the backend and toast registration
+ * problems. Call in the primary window only; state reaches secondary
+ * windows through the usual sync.
+ */
+export async function startHotkeys(): Promise<void> {
+  if (listening) return
+  const current = Promise.all([
+    listen<HotkeyAction>(HOTKEY_ACTION_EVENT, (e) => runAction(e.payload)),
+    listen<HotkeyStatus[]>(HOTKEY_STATUS_EVENT, (e) => reportProblems(e.payload)),
+  ])
+  listening = current
+  await current
+  // Registration at startup happens before this window is listening
+  const statuses = await invoke<HotkeyStatus[]>('hotkey_status')
+  if (listening === current) reportProblems(statuses)
+}
+
+/** Stop handling hotkey actions, including listeners still being registered. */
+export function stopHotkeys(): void {
+  const current = listening
+  listening = null
+  current?.then((fns) => fns.forEach((fn) => fn())).catch(console.error)
+}
@@ -0,0 +1,39 @@
+import { invoke } from '@tauri-apps/api/core'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import type { ChannelEvent, ChannelEventType } from './bus'
+
+/** Tauri event emitted when a replay runs to completion or is stopped. */
+const REPLAY_FINISHED_EVENT = 'replay-finished'
+
+/** Summary of a started replay. Mirrors `ReplayInfo` in `src-tauri/src/replay.rs`. */
+export interface ReplayInfo {
+  events: number
+  /** Total playback time at the requested speed. */
+  durationMs: number
+}
+
+/**
+ * Replay a recorded event log (`.jsonl`) or scenario file (`.json`) onto the bus.
+ * Replayed events are marked simulated and never switch scenes or reach Twitch.
+ */
+export function replayStart(path: string, speed = 1): Promise<ReplayInfo> {
+  return invoke<ReplayInfo>('replay_start', { path, speed })
+}
+
+/** Stop the running replay, if any. */
+export function replayStop(): Promise<void> {
+  return invoke('replay_stop')
+}
+
+/** Publish one synthetic event of `type`; `data` overrides the generated payload. */
+export function simulateEvent(
+  type: ChannelEventType,
+  data?: Record<string, unknown>,
+): Promise<ChannelEvent> {
+  return invoke<ChannelEvent>('simulate_event', { kind: type, data })
+}
+
+/** Call `handler` when a replay finishes or is stopped. */
+export function listenReplayFinished(handler: () => void): Promise<UnlistenFn> {
+  return listen(REPLAY_FINISHED_EVENT, () => handler())
+}
@@ -0,0 +1,52 @@
+import { invoke } from '@tauri-apps/api/core'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import { useOverlayStore, type WidgetInstance } from '../stores/overlay'
+import { subscribe, type ChannelEvent } from './bus'
+
+/** Tauri event emitted by the backend scene engine when it switches preset. */
+const SCENE_SWITCHED_EVENT = 'scene-switched'
+
+/** Event types the backend scene engine reacts to. */
+const SCENE_EVENT_TYPES = new Set<ChannelEvent['type']>([
+  'stream_online',
+  'stream_offline',
+  'chat',
+])
+
+interface SceneSwitched {
+  preset: string
+  trigger: string
+  data: { instances: WidgetInstance[] }
+}
+
+let unlistenFn: UnlistenFn | null = null
+let unsubscribeBus: (() => void) | null = null
+
+/**
+ * Apply presets switched by the backend scene engine. Safe to call in any window.
+ * When `forwardEvents` is set, relevant bus events are also fed to the engine;
+ * do this in one window only so each event triggers at most one switch.
+ */
+export async function startSceneAutomation(forwardEvents: boolean): Promise<void> {
+  if (forwardEvents && !unsubscribeBus) {
+    unsubscribeBus = subscribe((event) => {
+      if (!SCENE_EVENT_TYPES.has(event.type)) return
+      invoke('scene_event', { event: JSON.stringify(event) }).catch(console.error)
+    })
+  }
+  if (unlistenFn) return
+  unlistenFn = await listen<SceneSwitched>(SCENE_SWITCHED_EVENT, (event) => {
+    const { preset, trigger, data } = event.payload
+    if (!Array.isArray(data.instances)) return
+    console.info(`[scenes] switching to "${preset}" (${trigger})`)
+    useOverlayStore.setState({ instances: data.instances })
+  })
+}
+
+/** Stop applying and forwarding scene switches. */
+export function stopSceneAutomation(): void {
+  unlistenFn?.()
+  unlistenFn = null
+  unsubscribeBus?.()
+  unsubscribeBus = null
+}
@@ -0,0 +1,26 @@
+import { invoke } from '@tauri-apps/api/core'
+import { subscribe, type ChannelEvent } from './bus'
+
+/** Event types the backend suggestion box reacts to. */
+const SUGGESTION_EVENT_TYPES = new Set<ChannelEvent['type']>(['chat', 'channel_points_redemption'])
+
+let unsubscribeBus: (() => void) | null = null
+
+/**
+ * Feed chat and redemptions to the backend suggestion box, which records
+ * suggestions and votes whether or not the widget is shown. Call this in one
+ * window only so each event is handled once.
+ */
+export function startSuggestionIntake(): void {
+  if (unsubscribeBus) return
+  unsubscribeBus = subscribe((event) => {
+    if (!SUGGESTION_EVENT_TYPES.has(event.type)) return
+    invoke('suggestion_event', { event: JSON.stringify(event) }).catch(console.error)
+  })
+}
+
+/** Stop feeding events to the backend suggestion box. */
+export function stopSuggestionIntake(): void {
+  unsubscribeBus?.()
+  unsubscribeBus = null
+}
@@ -1,9 +1,11 @@
 export {
+  type MonitorFallback,
   type MonitorInfo,
-  openMonitorWindow,
-  closeMonitorWindow,
   closeAllMonitorWindows,
+  getMonitorFallback,
   getOpenMonitorIds,
+  listenMonitorWarnings,
+  setMonitorFallback,
   syncMonitorWindows,
 } from './manager'
 export { startBroadcasting, stopBroadcasting, startListening, stopListening } from './sync'
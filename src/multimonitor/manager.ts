@@ -1,99 +1,72 @@
-import { WebviewWindow } from '@tauri-apps/api/webviewWindow'
+import { invoke } from '@tauri-apps/api/core'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import toast from 'react-hot-toast'
 
 /** Monitor info as returned by the Rust `list_monitors` command. */
 export interface MonitorInfo {
+  /** Stable id that survives reconnects, re-ordering and mode changes. */
   id: string
   name: string
   x: number
   y: number
   width: number
   height: number
+  scaleFactor: number
+  /** The primary monitor is covered by the main window. */
+  primary: boolean
 }
 
-/** Track which overlay windows we have opened, keyed by monitor id. */
-const openWindows = new Map<string, WebviewWindow>()
+/** How overlays for disconnected monitors are handled. */
+export type MonitorFallback = 'wait' | 'nextAvailable'
 
-/**
- * Open an overlay window positioned on the given monitor.
- * The window loads the same front-end with a `?monitor=true` query param
- * so the app can detect it is a secondary (display-only) window.
- */
-export async function openMonitorWindow(monitor: MonitorInfo): Promise<void> {
-  if (openWindows.has(monitor.id)) return
-
-  const label = `overlay-${monitor.id}`
-  const webview = new WebviewWindow(label, {
-    url: `index.html?monitor=true&monitorId=${monitor.id}`,
-    x: monitor.x,
-    y: monitor.y,
-    width: monitor.width,
-    height: monitor.height,
-    decorations: false,
-    transparent: true,
-    alwaysOnTop: true,
-    skipTaskbar: true,
-  })
-
-  openWindows.set(monitor.id, webview)
-
-  webview.once('tauri://error', () => {
-    openWindows.delete(monitor.id)
-  })
+/** Payload of the backend `monitor-missing` event. */
+interface MissingMonitor {
+  id: string
+  name: string | null
+  /** Monitor showing the overlay instead, when the fallback is `nextAvailable`. */
+  fallback: string | null
 }
 
-/** Close the overlay window for a specific monitor. */
-export async function closeMonitorWindow(monitorId: string): Promise<void> {
-  const webview = openWindows.get(monitorId)
-  if (!webview) return
-  try {
-    await webview.close()
-  } catch {
-    // Window may already be closed
-  }
-  openWindows.delete(monitorId)
+/**
+ * Set the monitors that get a secondary overlay window. The backend opens,
+ * positions and closes windows to match, and keeps them fitted as monitors
+ * change scale or are plugged in and out. Disconnected monitors stay
+ * selected and get their window back when reconnected.
+ *
+ * @returns The ids of monitors that currently have a window.
+ */
+export function syncMonitorWindows(selectedIds: string[]): Promise<string[]> {
+  return invoke<string[]>('sync_overlay_windows', { monitorIds: selectedIds })
 }
 
 /** Close all secondary overlay windows. */
-export async function closeAllMonitorWindows(): Promise<void> {
-  const ids = [...openWindows.keys()]
-  await Promise.all(ids.map((id) => closeMonitorWindow(id)))
+export function closeAllMonitorWindows(): Promise<void> {
+  return invoke('close_overlay_windows')
 }
 
-/** Return the set of currently open monitor window ids. */
-export function getOpenMonitorIds(): string[] {
-  return [...openWindows.keys()]
+/** Return the ids of monitors that currently have an overlay window. */
+export function getOpenMonitorIds(): Promise<string[]> {
+  return invoke<string[]>('list_overlay_windows')
 }
 
-/**
- * Reconcile open windows with the desired set of monitor ids.
- * Opens missing windows, closes removed ones.
- * Also handles monitor disconnect: if a selected monitor is no longer
- * in the available list, it is removed from the selection.
- *
- * @returns The (potentially pruned) list of selected monitor ids.
- */
-export async function syncMonitorWindows(
-  selectedIds: string[],
-  availableMonitors: MonitorInfo[],
-): Promise<string[]> {
-  const availableIds = new Set(availableMonitors.map((m) => m.id))
-
-  // Prune selections for disconnected monitors
-  const validIds = selectedIds.filter((id) => availableIds.has(id))
-
-  const desired = new Set(validIds)
-  const current = new Set(openWindows.keys())
-
-  // Close windows that are no longer selected
-  const toClose = [...current].filter((id) => !desired.has(id))
-  await Promise.all(toClose.map((id) => closeMonitorWindow(id)))
+/** Read the fallback policy for disconnected monitors. */
+export function getMonitorFallback(): Promise<MonitorFallback> {
+  return invoke<MonitorFallback>('get_monitor_fallback')
+}
 
-  // Open windows for newly selected monitors
-  const toOpen = validIds.filter((id) => !current.has(id))
-  for (const id of toOpen) {
-    const monitor = availableMonitors.find((m) => m.id === id)
-    if (monitor) await openMonitorWindow(monitor)
-  }
+/** Change the fallback policy for disconnected monitors. */
+export function setMonitorFallback(fallback: MonitorFallback): Promise<void> {
+  return invoke('set_monitor_fallback', { fallback })
+}
 
-  return validIds
+/** Show a toast whenever a selected monitor is not connected. */
+export function listenMonitorWarnings(): Promise<UnlistenFn> {
+  return listen<MissingMonitor>('monitor-missing', ({ payload }) => {
+    const name = payload.name ?? payload.id
+    toast.error(
+      payload.fallback
+        ? `Monitor "${name}" is not connected; its overlay moved to ${payload.fallback}`
+        : `Monitor "${name}" is not connected; its overlay will return when it is`,
+    )
+  })
 }
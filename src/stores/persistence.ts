@@ -1,4 +1,6 @@
 import { invoke } from '@tauri-apps/api/core'
+import type { UnlistenFn } from '@tauri-apps/api/event'
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow'
 import type { SoundMapping } from '../audio/sounds'
 import { defaultColourForUsername } from '../twitch/irc'
 import {
@@ -8,27 +10,20 @@ import {
   loadChatMessages,
   subscribeChatMessages,
 } from '../widgets/chat/chat-state'
-import { getWidget } from '../widgets/registry'
 import { useOverlayStore, type WidgetInstance } from './overlay'
 import { useTwitchStore } from './twitch'
 
-/** Old split format had widgetStates as a separate record */
-interface OldWidgetState {
-  x: number
-  y: number
-  width: number
-  height: number
-  visible: boolean
-}
-
+/**
+ * Settings as read from and written to the backend. Mirrors `Settings` in
+ * `src-tauri/src/settings.rs`, which migrates older files before handing them
+ * out and stamps the current version on every write.
+ */
 interface PersistedSettings {
-  _v?: number
   overlay?: {
-    instances: (WidgetInstance | { instanceId: string; typeId: string })[]
-    widgetStates?: Record<string, OldWidgetState>
+    instances: WidgetInstance[]
     fileLogging: boolean
     twitchColours?: boolean
-    presenceThreshold: number
+    presenceThreshold?: number
     commands?: { trigger: string; response: string; enabled: boolean }[]
     soundEnabled?: boolean
     soundVolume?: number
@@ -50,10 +45,6 @@ interface PersistedSettings {
   twitch?: {
     channel: string
   }
-  /** Legacy: custom text config was stored globally before being moved to instance config */
-  customText?: {
-    config: Record<string, unknown>
-  }
 }
 
 interface PersistedChatHistory {
@@ -68,72 +59,44 @@ const TEN_MINUTES = 10 * 60 * 1000
 export async function hydrate(): Promise<void> {
   const data = (await invoke('read_settings')) as PersistedSettings | null
   if (!data) return
+  applySettings(data)
+}
 
-  if (data.overlay) {
-    const instances: WidgetInstance[] = data.overlay.instances.map((inst) => {
-      if ('visible' in inst) return inst as WidgetInstance
-      // Migrate old format: merge widgetStates or fall back to registry defaults
-      const old = data.overlay!.widgetStates?.[inst.instanceId]
-      const def = getWidget(inst.typeId)
-      return {
-        ...inst,
-        x: old?.x ?? def?.defaults.x ?? 0,
-        y: old?.y ?? def?.defaults.y ?? 0,
-        width: old?.width ?? def?.defaults.width ?? 300,
-        height: old?.height ?? def?.defaults.height ?? 200,
-        visible: old?.visible ?? true,
-        locked: false,
-      }
-    })
-
-    // Migrate: add locked/opacity defaults for existing settings missing them
-    for (const inst of instances) {
-      if (inst.locked === undefined) inst.locked = false
-    }
-
-    // Migrate v1: clear per-widget style overrides so they use new global defaults
-    if ((data._v ?? 0) < 1) {
-      for (const inst of instances) {
-        delete inst.bgColour
-        delete inst.bgOpacity
-        delete inst.textColour
-      }
-    }
-
-    // Migrate v2: remove dead instance.opacity; carry panelBgOpacity forward as widgetBgOpacity
-    let migratedWidgetBgOpacity: number | undefined
-    if ((data._v ?? 0) < 2) {
-      for (const inst of instances) {
-        delete (inst as unknown as Record<string, unknown>).opacity
-      }
-      if (data.overlay.panelBgOpacity !== undefined) {
-        migratedWidgetBgOpacity = data.overlay.panelBgOpacity
-      }
-    }
-
-    // Migrate v3: remove liveBg from instances
-    if ((data._v ?? 0) < 3) {
-      for (const inst of instances) {
-        delete (inst as unknown as Record<string, unknown>).liveBg
-      }
-    }
+let unlistenSettings: UnlistenFn | null = null
+
+/**
+ * Re-apply settings whenever the backend reports that they changed outside
+ * this window (hand edits, another window, profile switches). Listens on this
+ * window so the backend can skip echoing a window's own saves back to it.
+ * Safe to call in any window.
+ */
+export async function startSettingsSync(): Promise<void> {
+  if (unlistenSettings) return
+  unlistenSettings = await getCurrentWebviewWindow().listen<PersistedSettings>(
+    'settings-changed',
+    (event) => {
+      applySettings(event.payload)
+    },
+  )
+}
 
-    // Migrate legacy global customText config into custom-text instances that lack config
-    if (data.customText?.config) {
-      for (const inst of instances) {
-        if (inst.typeId === 'custom-text' && !inst.config) {
-          inst.config = { ...data.customText.config }
-        }
-      }
-    }
+/** Stop listening for external settings changes. */
+export function stopSettingsSync(): void {
+  unlistenSettings?.()
+  unlistenSettings = null
+}
 
+function applySettings(data: PersistedSettings): void {
+  if (data.overlay) {
     useOverlayStore.setState({
-      instances,
+      instances: data.overlay.instances,
       fileLogging: data.overlay.fileLogging,
       ...(data.overlay.twitchColours !== undefined && {
         twitchColours: data.overlay.twitchColours,
       }),
-      presenceThreshold: data.overlay.presenceThreshold,
+      ...(data.overlay.presenceThreshold !== undefined && {
+        presenceThreshold: data.overlay.presenceThreshold,
+      }),
       ...(data.overlay.commands && { commands: data.overlay.commands }),
       ...(data.overlay.soundEnabled !== undefined && { soundEnabled: data.overlay.soundEnabled }),
       ...(data.overlay.soundVolume !== undefined && { soundVolume: data.overlay.soundVolume }),
@@ -150,11 +113,9 @@ export async function hydrate(): Promise<void> {
       ...(data.overlay.widgetBgColour !== undefined && {
         widgetBgColour: data.overlay.widgetBgColour,
       }),
-      ...(migratedWidgetBgOpacity !== undefined
-        ? { widgetBgOpacity: migratedWidgetBgOpacity }
-        : data.overlay.widgetBgOpacity !== undefined && {
-            widgetBgOpacity: data.overlay.widgetBgOpacity,
-          }),
+      ...(data.overlay.widgetBgOpacity !== undefined && {
+        widgetBgOpacity: data.overlay.widgetBgOpacity,
+      }),
       ...(data.overlay.textBgOpacity !== undefined && {
         textBgOpacity: data.overlay.textBgOpacity,
       }),
@@ -192,7 +153,6 @@ function gatherState(): PersistedSettings {
   const twitch = useTwitchStore.getState()
 
   return {
-    _v: 3,
     overlay: {
       instances: overlay.instances,
       fileLogging: overlay.fileLogging,
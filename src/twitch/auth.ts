@@ -1,11 +1,15 @@
 import { invoke } from '@tauri-apps/api/core'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
 import { openUrl } from '@tauri-apps/plugin-opener'
+import toast from 'react-hot-toast'
 import { useTwitchStore } from '../stores/twitch'
 import { connectChat } from './irc'
 
 interface AuthStatusResponse {
   authenticated: boolean
   username: string | null
+  /** Scopes the stored token lacks; the user must log in again to grant them. */
+  missing_scopes: string[]
 }
 
 interface DeviceCodeInfo {
@@ -65,6 +69,14 @@ export async function logout(): Promise<void> {
   console.log('[auth] logged out')
 }
 
+/** Follow logins and logouts made by the backend, such as from the tray menu. */
+export function listenAuthChanges(): Promise<UnlistenFn> {
+  return listen<AuthStatusResponse>('auth-changed', ({ payload }) => {
+    console.log('[auth] session changed — authenticated=%s', payload.authenticated)
+    useTwitchStore.getState().setAuth(payload.authenticated, payload.username ?? '')
+  })
+}
+
 /** Check if we have a valid stored session and auto-connect if so. */
 export async function checkAuth(): Promise<void> {
   try {
@@ -75,6 +87,10 @@ export async function checkAuth(): Promise<void> {
       status.username,
     )
     useTwitchStore.getState().setAuth(status.authenticated, status.username ?? '')
+    if (status.missing_scopes.length > 0) {
+      console.warn('[auth] token lacks scopes: %s', status.missing_scopes.join(' '))
+      toast.error('New Twitch permissions are needed. Please log out and log in again.')
+    }
     const { channel } = useTwitchStore.getState()
     if (status.authenticated && channel) {
       connectChat(channel)
@@ -0,0 +1,39 @@
+import { invoke } from '@tauri-apps/api/core'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import toast from 'react-hot-toast'
+
+/** Tauri event emitted by the backend once a clip has been processed. */
+const CLIP_CREATED_EVENT = 'clip-created'
+
+/** A processed clip. Mirrors `Clip` in `src-tauri/src/clips.rs`. */
+export interface Clip {
+  id: string
+  url: string
+  title: string
+  thumbnailUrl: string
+  duration: number
+  /** What triggered the clip: hotkey, chat or manual. */
+  source: string
+  /** Unix timestamp in milliseconds; set for clips read from the event log. */
+  timestamp?: number
+}
+
+/** Clip the live stream and resolve once Twitch has processed the clip. */
+export function createClip(): Promise<Clip> {
+  return invoke<Clip>('create_clip')
+}
+
+/** Clips recorded in the broadcaster's event log, oldest first. */
+export function listClips(): Promise<Clip[]> {
+  return invoke<Clip[]>('list_clips')
+}
+
+/** Call `handler` for every clip created, whatever triggered it. */
+export function listenClips(handler: (clip: Clip) => void): Promise<UnlistenFn> {
+  return listen<Clip>(CLIP_CREATED_EVENT, ({ payload }) => handler(payload))
+}
+
+/** Show a toast with the link whenever a clip is created. */
+export function listenClipToasts(): Promise<UnlistenFn> {
+  return listenClips((clip) => toast.success(`Clip created: ${clip.url}`))
+}
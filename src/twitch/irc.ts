@@ -305,7 +305,12 @@ function handleMessage(event: MessageEvent<string>) {
         publish({
           type: 'chat',
           timestamp,
-          data: { username: parsed.username, text: parsed.text, userId: parsed.userId },
+          data: {
+            username: parsed.username,
+            text: parsed.text,
+            userId: parsed.userId,
+            badges: parsed.badges,
+          },
         })
       }
 
@@ -394,6 +399,7 @@ export function sendChatMessage(text: string): void {
 
   const { username, userColour, userId } = useTwitchStore.getState()
   const timestamp = Date.now()
+  const isOwnChannel = !!username && username.toLowerCase() === currentChannel.toLowerCase()
   pushChatMessage({
     id: `${timestamp}-${Math.random().toString(36).slice(2, 8)}`,
     username: username || botNick || 'me',
@@ -407,7 +413,13 @@ export function sendChatMessage(text: string): void {
   publish({
     type: 'chat',
     timestamp,
-    data: { username: username || botNick || 'me', text, userId },
+    data: {
+      username: username || botNick || 'me',
+      text,
+      userId,
+      // Own messages never echo back with tags; infer the badge the IRC echo would carry
+      badges: isOwnChannel ? [{ setId: 'broadcaster', versionId: '1' }] : [],
+    },
   })
 
   handleChatCommand(text)
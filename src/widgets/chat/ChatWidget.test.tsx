@@ -12,6 +12,12 @@ vi.mock('@tauri-apps/api/core', () => ({
   invoke: vi.fn(),
 }))
 
+// The hit-test service needs a Tauri window
+vi.mock('../shared/hit-region', () => ({
+  useHitRegion: () => false,
+  holdHitRegion: vi.fn(() => Promise.resolve()),
+}))
+
 // Mock badges to avoid network calls
 vi.mock('../../twitch/badges', () => ({
   getBadgeUrl: () => null,
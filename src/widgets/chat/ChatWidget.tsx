@@ -1,10 +1,10 @@
-import { invoke } from '@tauri-apps/api/core'
 import { useRef, useEffect, useReducer, useState } from 'react'
 import { useOverlayStore } from '../../stores/overlay'
 import { useTwitchStore } from '../../stores/twitch'
 import { getBadgeUrl } from '../../twitch/badges'
 import { sendChatMessage, defaultColourForUsername } from '../../twitch/irc'
 import type { WidgetInstanceProps } from '../registry'
+import { holdHitRegion, useHitRegion } from '../shared/hit-region'
 import { Widget, useContentAlign, contentAlignClass } from '../Widget'
 import { messages, listeners, messageOpacity } from './chat-state'
 import type { ChatEmote } from './chat-state'
@@ -173,8 +173,6 @@ function ChatContent({ instanceId }: { instanceId: string }) {
 }
 
 const LONG_HOVER_MS = 500
-const POLL_INTERVAL_MS = 200
-
 function ChatInputContainer({ instanceId }: { instanceId: string }) {
   const [text, setText] = useState('')
   const editMode = useOverlayStore((s) => s.editMode)
@@ -184,7 +182,6 @@ function ChatInputContainer({ instanceId }: { instanceId: string }) {
   const align = useContentAlign(instanceId)
   const inputRef = useRef<HTMLInputElement>(null)
   const containerRef = useRef<HTMLDivElement>(null)
-  const hoverStartRef = useRef<number | null>(null)
 
   const disabled = !authenticated || !connected
   const placeholder = !connected
@@ -203,43 +200,24 @@ function ChatInputContainer({ instanceId }: { instanceId: string }) {
     }
   }
 
-  function handleBlur() {
-    if (!editMode) invoke('set_ignore_cursor', { ignore: true }).catch(console.error)
-  }
+  const regionId = `chat-input-${instanceId}`
+  const hovered = useHitRegion(containerRef, regionId, {
+    hoverMs: LONG_HOVER_MS,
+    enabled: !editMode,
+  })
 
-  // Poll OS cursor position to detect long hover when cursor events are ignored
+  // Focus the input once a long hover has made the window interactive
   useEffect(() => {
-    if (editMode) return
-
-    const interval = setInterval(async () => {
-      if (!containerRef.current || !inputRef.current) return
-      if (document.activeElement === inputRef.current) return
-
-      try {
-        const [cx, cy] = await invoke<[number, number]>('get_cursor_position')
-        const rect = containerRef.current.getBoundingClientRect()
-
-        if (cx >= rect.left && cx <= rect.right && cy >= rect.top && cy <= rect.bottom) {
-          if (hoverStartRef.current === null) {
-            hoverStartRef.current = Date.now()
-          } else if (Date.now() - hoverStartRef.current >= LONG_HOVER_MS) {
-            await invoke('set_ignore_cursor', { ignore: false })
-            inputRef.current.focus()
-            hoverStartRef.current = null
-          }
-        } else {
-          hoverStartRef.current = null
-        }
-      } catch {
-        // cursor position unavailable
-      }
-    }, POLL_INTERVAL_MS)
+    if (hovered) inputRef.current?.focus()
+  }, [hovered])
 
-    return () => {
-      clearInterval(interval)
-      hoverStartRef.current = null
-    }
-  }, [editMode])
+  function handleFocus() {
+    if (!editMode) holdHitRegion(regionId, true).catch(console.error)
+  }
+
+  function handleBlur() {
+    holdHitRegion(regionId, false).catch(console.error)
+  }
 
   return (
     <div
@@ -252,6 +230,7 @@ function ChatInputContainer({ instanceId }: { instanceId: string }) {
         value={text}
         onChange={(e) => setText(e.target.value)}
         onKeyDown={handleKeyDown}
+        onFocus={handleFocus}
         onBlur={handleBlur}
         disabled={disabled}
         placeholder={placeholder}
@@ -46,6 +46,11 @@ export function loadChatMessages(saved: ChatMessage[]): void {
   listeners.forEach((fn) => fn())
 }
 
+/** Remove all messages from the chat widget. */
+export function clearChatMessages(): void {
+  loadChatMessages([])
+}
+
 /** Subscribe to message changes. Returns an unsubscribe function. */
 export function subscribeChatMessages(fn: () => void): () => void {
   listeners.add(fn)
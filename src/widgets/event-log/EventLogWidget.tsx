@@ -34,6 +34,8 @@ const badgeColours: Record<ChannelEventType, string> = {
   follower_count_update: 'bg-emerald-500',
   viewer_count_update: 'bg-sky-500',
   channel_points_redemption: 'bg-amber-500',
+  stream_marker: 'bg-rose-500',
+  clip_created: 'bg-fuchsia-500',
 }
 
 function summarise(event: ChannelEvent): string {
@@ -71,6 +73,10 @@ function summarise(event: ChannelEvent): string {
       return `Viewer count: ${d.count}`
     case 'channel_points_redemption':
       return `${d.user_name || d.username || 'Someone'} redeemed ${(d.reward as Record<string, unknown>)?.title || 'a reward'}`
+    case 'stream_marker':
+      return `Marker at ${d.positionSeconds}s${d.description ? `: ${d.description}` : ''}`
+    case 'clip_created':
+      return `Clip created: ${d.url}`
     default:
       return JSON.stringify(d)
   }
@@ -0,0 +1,30 @@
+import { useEffect, useState } from 'react'
+import { getControlApi, type ControlApiInfo } from '../../events/control'
+
+const FIELD_CLS =
+  'min-w-0 flex-1 bg-white/10 text-white rounded px-1 py-0.5 outline-none select-all'
+
+/** Where stream decks and scripts send `POST /markers` and `POST /clips` requests. */
+export function ControlApiSection() {
+  const [info, setInfo] = useState<ControlApiInfo | null>(null)
+
+  useEffect(() => {
+    getControlApi().then(setInfo).catch(console.error)
+  }, [])
+
+  if (!info) return null
+
+  return (
+    <div className="space-y-2">
+      <h3 className="text-white/70 text-xs font-medium">Control API</h3>
+      <div className="flex items-center gap-1 text-xs">
+        <span className="w-10 text-white/50">URL</span>
+        <input readOnly value={info.url} className={FIELD_CLS} />
+      </div>
+      <div className="flex items-center gap-1 text-xs">
+        <span className="w-10 text-white/50">Token</span>
+        <input readOnly value={info.token} className={FIELD_CLS} />
+      </div>
+    </div>
+  )
+}
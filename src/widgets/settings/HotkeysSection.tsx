@@ -0,0 +1,175 @@
+import { invoke } from '@tauri-apps/api/core'
+import { useEffect, useState } from 'react'
+import { type HotkeyStatus, hotkeyProblem } from '../../events/hotkeys'
+
+type HotkeyAction =
+  | { kind: 'toggleEditMode' }
+  | { kind: 'toggleOverlay' }
+  | { kind: 'loadPreset'; name: string }
+  | { kind: 'playSound'; sound: string }
+  | { kind: 'streamMarker'; description?: string }
+  | { kind: 'clearChat' }
+  | { kind: 'createClip' }
+
+interface HotkeyBinding {
+  id: string
+  shortcut: string
+  action: HotkeyAction
+  enabled: boolean
+}
+
+const ACTION_LABELS: Record<HotkeyAction['kind'], string> = {
+  toggleEditMode: 'Toggle edit mode',
+  toggleOverlay: 'Toggle overlay',
+  loadPreset: 'Load preset',
+  playSound: 'Play sound',
+  streamMarker: 'Stream marker',
+  clearChat: 'Clear chat',
+  createClip: 'Create clip',
+}
+
+/** A fresh action of the given kind, with empty parameters. */
+function makeAction(kind: HotkeyAction['kind']): HotkeyAction {
+  switch (kind) {
+    case 'loadPreset':
+      return { kind, name: '' }
+    case 'playSound':
+      return { kind, sound: 'chime' }
+    default:
+      return { kind }
+  }
+}
+
+/** The action's free-text parameter, if it has one, with a setter. */
+function actionParam(
+  action: HotkeyAction,
+): { value: string; placeholder: string; set: (v: string) => HotkeyAction } | null {
+  switch (action.kind) {
+    case 'loadPreset':
+      return { value: action.name, placeholder: 'Preset', set: (name) => ({ ...action, name }) }
+    case 'playSound':
+      return { value: action.sound, placeholder: 'Sound', set: (sound) => ({ ...action, sound }) }
+    case 'streamMarker':
+      return {
+        value: action.description ?? '',
+        placeholder: 'Description',
+        set: (d) => ({ ...action, description: d || undefined }),
+      }
+    default:
+      return null
+  }
+}
+
+const INPUT_CLS =
+  'min-w-0 bg-white/10 text-white rounded px-1 py-0.5 outline-none focus:ring-1 focus:ring-blue-400'
+
+/** Global hotkey bindings, registered by the backend. */
+export function HotkeysSection() {
+  const [bindings, setBindings] = useState<HotkeyBinding[]>([])
+  const [statuses, setStatuses] = useState<HotkeyStatus[]>([])
+  const [error, setError] = useState<string | null>(null)
+
+  useEffect(() => {
+    invoke<HotkeyBinding[]>('get_hotkeys').then(setBindings).catch(console.error)
+    invoke<HotkeyStatus[]>('hotkey_status').then(setStatuses).catch(console.error)
+  }, [])
+
+  function update(index: number, patch: Partial<HotkeyBinding>) {
+    setBindings((list) => list.map((b, i) => (i === index ? { ...b, ...patch } : b)))
+  }
+
+  function handleAdd() {
+    setBindings((list) => [
+      ...list,
+      { id: crypto.randomUUID(), shortcut: '', action: { kind: 'clearChat' }, enabled: true },
+    ])
+  }
+
+  async function handleSave() {
+    setError(null)
+    try {
+      setStatuses(await invoke<HotkeyStatus[]>('set_hotkeys', { bindings }))
+    } catch (e) {
+      setError(String(e))
+    }
+  }
+
+  return (
+    <div className="space-y-2">
+      <h3 className="text-white/70 text-xs font-medium">Hotkeys</h3>
+      <div className="space-y-1">
+        {bindings.map((b, i) => {
+          const param = actionParam(b.action)
+          const status = statuses.find((s) => s.id === b.id && s.shortcut === b.shortcut)
+          const problem = status && hotkeyProblem(status)
+          return (
+            <div key={b.id} className="space-y-0.5">
+              <div className="flex items-center gap-1 text-xs">
+                <input
+                  type="checkbox"
+                  checked={b.enabled}
+                  onChange={(e) => update(i, { enabled: e.target.checked })}
+                  className="accent-blue-500"
+                />
+                <input
+                  type="text"
+                  value={b.shortcut}
+                  onChange={(e) => update(i, { shortcut: e.target.value })}
+                  placeholder="Ctrl+Shift+K"
+                  className={`w-24 ${INPUT_CLS}`}
+                />
+                <select
+                  value={b.action.kind}
+                  onChange={(e) =>
+                    update(i, { action: makeAction(e.target.value as HotkeyAction['kind']) })
+                  }
+                  className={`flex-1 ${INPUT_CLS}`}
+                >
+                  {Object.entries(ACTION_LABELS).map(([kind, label]) => (
+                    <option key={kind} value={kind}>
+                      {label}
+                    </option>
+                  ))}
+                </select>
+                {param && (
+                  <input
+                    type="text"
+                    value={param.value}
+                    onChange={(e) => update(i, { action: param.set(e.target.value) })}
+                    placeholder={param.placeholder}
+                    className={`w-20 ${INPUT_CLS}`}
+                  />
+                )}
+                <button
+                  onClick={() => setBindings((list) => list.filter((_, j) => j !== i))}
+                  className="text-red-400 hover:text-red-300 transition-colors"
+                  title="Remove hotkey"
+                >
+                  ✕
+                </button>
+              </div>
+              {problem && <p className="text-red-400 text-xs">{problem}</p>}
+            </div>
+          )
+        })}
+      </div>
+      <div className="flex gap-1">
+        <button
+          onClick={handleAdd}
+          className="flex-1 bg-white/10 hover:bg-white/20 text-white text-xs px-2 py-1 rounded transition-colors"
+        >
+          Add hotkey
+        </button>
+        <button
+          onClick={() => {
+            handleSave().catch(console.error)
+          }}
+          className="bg-blue-600 hover:bg-blue-700 text-white text-xs px-2 py-1 rounded transition-colors"
+        >
+          Save
+        </button>
+      </div>
+      {error && <p className="text-red-400 text-xs break-words">{error}</p>}
+    </div>
+  )
+}
@@ -7,8 +7,17 @@ import { useOverlayStore, type WidgetInstance } from '../../stores/overlay'
 const PRESET_VERSION = 1
 
 interface PresetInfo {
+  /** Slug used to load, delete and export the preset. */
   name: string
+  displayName: string
+  description: string
   path: string
+  createdAt: number
+  updatedAt: number
+  appVersion: string | null
+  widgetCount: number
+  widgetTypes: string[]
+  thumbnail: string | null
 }
 
 interface PresetData {
@@ -65,7 +74,14 @@ export function PresetsSection() {
     try {
       const instances = useOverlayStore.getState().instances
       const data: PresetData = { version: PRESET_VERSION, instances }
-      await invoke('save_preset', { name, data: JSON.stringify(data, null, 2) })
+      const json = JSON.stringify(data, null, 2)
+      try {
+        await invoke('save_preset', { name, data: json })
+      } catch (e) {
+        if (!String(e).includes('already exists')) throw e
+        if (!window.confirm(`A preset named "${name}" already exists. Overwrite it?`)) return
+        await invoke('save_preset', { name, data: json, onConflict: 'overwrite' })
+      }
       setNewName('')
       await refreshPresets()
     } catch (e) {
@@ -111,10 +127,13 @@ export function PresetsSection() {
       const raw = await invoke<string>('load_preset', { name })
       const filePath = await save({
         defaultPath: `${name}.json`,
-        filters: [{ name: 'Preset', extensions: ['json'] }],
+        filters: [
+          { name: 'Preset', extensions: ['json'] },
+          { name: 'Preset bundle with assets', extensions: ['zip'] },
+        ],
       })
       if (!filePath) return
-      await invoke('export_preset', { path: filePath, data: raw })
+      await invoke('export_preset', { path: filePath, data: raw, name })
     } catch (e) {
       setError(String(e))
     }
@@ -126,7 +145,7 @@ export function PresetsSection() {
     try {
       const filePath = await open({
         multiple: false,
-        filters: [{ name: 'Preset', extensions: ['json'] }],
+        filters: [{ name: 'Preset', extensions: ['json', 'zip'] }],
       })
       if (!filePath) return
 
@@ -172,8 +191,17 @@ export function PresetsSection() {
         <div className="space-y-1 max-h-32 overflow-y-auto">
           {presets.map((preset) => (
             <div key={preset.name} className="flex items-center gap-1 text-xs">
-              <span className="flex-1 text-white truncate" title={preset.name}>
-                {preset.name}
+              <span
+                className="flex-1 text-white truncate"
+                title={[
+                  preset.description,
+                  `${preset.widgetCount} widgets: ${preset.widgetTypes.join(', ')}`,
+                  `Updated ${new Date(preset.updatedAt).toLocaleString()}`,
+                ]
+                  .filter(Boolean)
+                  .join('\n')}
+              >
+                {preset.displayName}
               </span>
               <button
                 onClick={() => {
@@ -1,18 +1,28 @@
 import { invoke, convertFileSrc } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
 import { open } from '@tauri-apps/plugin-dialog'
 import { useState, useEffect, useCallback, useRef } from 'react'
 import toast from 'react-hot-toast'
 import { playSound } from '../../audio/player'
 import { DEFAULT_SOUND_MAPPINGS, type SoundMapping } from '../../audio/sounds'
 import { BUILTIN_SOUNDS, BUILTIN_SOUND_LABELS, type BuiltinSound } from '../../audio/synth'
-import { type MonitorInfo, syncMonitorWindows } from '../../multimonitor'
+import {
+  type MonitorFallback,
+  type MonitorInfo,
+  getMonitorFallback,
+  setMonitorFallback,
+  syncMonitorWindows,
+} from '../../multimonitor'
 import { useOverlayStore, type ChatCommand } from '../../stores/overlay'
 import { useTwitchStore } from '../../stores/twitch'
 import { login, logout } from '../../twitch/auth'
 import { connectChat, disconnectChat } from '../../twitch/irc'
 import { getWidgets } from '../registry'
 import { FontPicker } from '../shared/FontPicker'
+import { ControlApiSection } from './ControlApiSection'
+import { HotkeysSection } from './HotkeysSection'
 import { PresetsSection } from './PresetsSection'
+import { SimulatorSection } from './SimulatorSection'
 
 const TABS = ['General', 'Widgets', 'Twitch', 'Appearance'] as const
 type Tab = (typeof TABS)[number]
@@ -21,6 +31,7 @@ function AuthSection() {
   const authenticated = useTwitchStore((s) => s.authenticated)
   const username = useTwitchStore((s) => s.username)
   const [loading, setLoading] = useState(false)
+  const [fallback, setFallback] = useState<MonitorFallback>('wait')
   const [deviceCode, setDeviceCode] = useState<string | null>(null)
 
   async function handleLogin() {
@@ -281,6 +292,12 @@ function GeneralTab() {
         </>
       )}
       <hr className="border-white/10" />
+      <HotkeysSection />
+      <hr className="border-white/10" />
+      <SimulatorSection />
+      <hr className="border-white/10" />
+      <ControlApiSection />
+      <hr className="border-white/10" />
       <RestoreDefaults />
     </div>
   )
@@ -303,14 +320,25 @@ function MonitorSelection() {
 
   useEffect(() => {
     refresh()
+    getMonitorFallback().then(setFallback).catch(console.error)
+    // The backend re-fits windows itself; this just keeps the list current
+    const unlisten = listen<MonitorInfo[]>('monitors-changed', (e) => setMonitors(e.payload))
+    return () => {
+      unlisten.then((fn) => fn()).catch(console.error)
+    }
   }, [refresh])
 
   async function handleToggle(id: string, checked: boolean) {
     const next = checked ? [...selectedMonitors, id] : selectedMonitors.filter((m) => m !== id)
 
-    // Reconcile windows and prune disconnected monitors
-    const pruned = await syncMonitorWindows(next, monitors)
-    setSelectedMonitors(pruned)
+    // Disconnected monitors stay selected and reopen when plugged back in
+    setSelectedMonitors(next)
+    await syncMonitorWindows(next).catch(console.error)
+  }
+
+  function handleFallback(next: MonitorFallback) {
+    setFallback(next)
+    setMonitorFallback(next).catch(console.error)
   }
 
   if (monitors.length <= 1) return null
@@ -336,17 +364,30 @@ function MonitorSelection() {
           >
             <input
               type="checkbox"
-              checked={selectedMonitors.includes(m.id)}
+              checked={m.primary || selectedMonitors.includes(m.id)}
+              disabled={m.primary}
               onChange={(e) => handleToggle(m.id, e.target.checked)}
               className="accent-blue-500"
             />
             <span>{m.name}</span>
+            {m.primary && <span className="text-white/40">(main)</span>}
             <span className="text-white/40">
               {m.width}x{m.height}
             </span>
           </label>
         ))}
       </div>
+      <label className="flex items-center gap-2 text-xs text-white/80">
+        <span>If a monitor is missing</span>
+        <select
+          value={fallback}
+          onChange={(e) => handleFallback(e.target.value as MonitorFallback)}
+          className="flex-1 bg-white/10 text-white rounded px-1 py-0.5 outline-none focus:ring-1 focus:ring-blue-400"
+        >
+          <option value="wait">Wait for it to return</option>
+          <option value="nextAvailable">Use another monitor</option>
+        </select>
+      </label>
     </div>
   )
 }
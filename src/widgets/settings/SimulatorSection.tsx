@@ -0,0 +1,108 @@
+import { open } from '@tauri-apps/plugin-dialog'
+import { useEffect, useState } from 'react'
+import toast from 'react-hot-toast'
+import type { ChannelEventType } from '../../events/bus'
+import { listenReplayFinished, replayStart, replayStop, simulateEvent } from '../../events/replay'
+
+const SIMULATED_LABELS: Partial<Record<ChannelEventType, string>> = {
+  follow: 'Follow',
+  subscribe: 'Subscription',
+  gift_sub: 'Gift subs',
+  raid: 'Raid',
+  cheer: 'Cheer',
+  channel_points_redemption: 'Channel points redemption',
+  chat: 'Chat message',
+}
+
+const INPUT_CLS =
+  'min-w-0 bg-white/10 text-white rounded px-1 py-0.5 outline-none focus:ring-1 focus:ring-blue-400'
+
+/** Send test events or replay a recorded log without touching the real channel. */
+export function SimulatorSection() {
+  const [type, setType] = useState<ChannelEventType>('follow')
+  const [speed, setSpeed] = useState(1)
+  const [replaying, setReplaying] = useState(false)
+
+  useEffect(() => {
+    const unlisten = listenReplayFinished(() => setReplaying(false))
+    return () => {
+      unlisten.then((fn) => fn()).catch(console.error)
+    }
+  }, [])
+
+  function handleSimulate() {
+    simulateEvent(type).catch((e) => toast.error(`Failed to simulate event: ${e}`))
+  }
+
+  async function handleReplay() {
+    const path = await open({
+      filters: [{ name: 'Event log or scenario', extensions: ['jsonl', 'json'] }],
+    })
+    if (typeof path !== 'string') return
+    try {
+      const info = await replayStart(path, speed)
+      setReplaying(true)
+      toast.success(`Replaying ${info.events} events`)
+    } catch (e) {
+      toast.error(`Failed to start replay: ${e}`)
+    }
+  }
+
+  return (
+    <div className="space-y-2">
+      <h3 className="text-white/70 text-xs font-medium">Test events</h3>
+      <div className="flex items-center gap-1 text-xs">
+        <select
+          value={type}
+          onChange={(e) => setType(e.target.value as ChannelEventType)}
+          className={`flex-1 ${INPUT_CLS}`}
+        >
+          {Object.entries(SIMULATED_LABELS).map(([kind, label]) => (
+            <option key={kind} value={kind}>
+              {label}
+            </option>
+          ))}
+        </select>
+        <button
+          onClick={handleSimulate}
+          className="bg-blue-600 hover:bg-blue-700 text-white text-xs px-2 py-1 rounded transition-colors"
+        >
+          Send
+        </button>
+      </div>
+      <div className="flex items-center gap-1 text-xs">
+        <select
+          value={speed}
+          onChange={(e) => setSpeed(Number(e.target.value))}
+          className={INPUT_CLS}
+          title="Replay speed"
+        >
+          {[1, 2, 5, 10].map((s) => (
+            <option key={s} value={s}>
+              {s}x
+            </option>
+          ))}
+        </select>
+        {replaying ? (
+          <button
+            onClick={() => {
+              replayStop().catch(console.error)
+            }}
+            className="flex-1 bg-red-600/60 hover:bg-red-600/80 text-white text-xs px-2 py-1 rounded transition-colors"
+          >
+            Stop replay
+          </button>
+        ) : (
+          <button
+            onClick={() => {
+              handleReplay().catch(console.error)
+            }}
+            className="flex-1 bg-white/10 hover:bg-white/20 text-white text-xs px-2 py-1 rounded transition-colors"
+          >
+            Replay log or scenario...
+          </button>
+        )}
+      </div>
+    </div>
+  )
+}
@@ -0,0 +1,66 @@
+/** Click-through hit regions, tracked by the backend hit-test service. */
+
+import { invoke } from '@tauri-apps/api/core'
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow'
+import { useEffect, useState } from 'react'
+
+/** Payload of the backend `hit-region` event. */
+interface HitRegionEvent {
+  regionId: string
+  active: boolean
+}
+
+/**
+ * Register an element as an interactive region of this window. While the
+ * overlay is click-through, the backend watches the OS cursor and makes the
+ * window interactive once the cursor has rested over the element for
+ * `hoverMs`, then click-through again when it leaves.
+ *
+ * @returns Whether the region is currently active (window interactive).
+ */
+export function useHitRegion(
+  ref: React.RefObject<HTMLElement | null>,
+  id: string,
+  { hoverMs = 0, enabled = true }: { hoverMs?: number; enabled?: boolean } = {},
+): boolean {
+  const [active, setActive] = useState(false)
+
+  useEffect(() => {
+    if (!enabled) {
+      setActive(false)
+      return
+    }
+    const el = ref.current
+    if (!el) return
+
+    const register = () => {
+      const rect = el.getBoundingClientRect()
+      invoke('set_hit_region', {
+        region: { id, x: rect.left, y: rect.top, width: rect.width, height: rect.height, hoverMs },
+      }).catch(console.error)
+    }
+    register()
+    const observer = new ResizeObserver(register)
+    observer.observe(el)
+    window.addEventListener('resize', register)
+
+    const unlisten = getCurrentWebviewWindow().listen<HitRegionEvent>('hit-region', (e) => {
+      if (e.payload.regionId === id) setActive(e.payload.active)
+    })
+
+    return () => {
+      observer.disconnect()
+      window.removeEventListener('resize', register)
+      unlisten.then((fn) => fn()).catch(console.error)
+      invoke('remove_hit_region', { id }).catch(console.error)
+      setActive(false)
+    }
+  }, [ref, id, hoverMs, enabled])
+
+  return active
+}
+
+/** Keep a region active after the cursor leaves it, e.g. while its input has focus. */
+export function holdHitRegion(id: string, held: boolean): Promise<void> {
+  return invoke('hold_hit_region', { id, held })
+}
@@ -1,19 +1,19 @@
 import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
 import { useEffect, useReducer, useRef, useState } from 'react'
 import { useOverlayStore } from '../../stores/overlay'
 import { useTwitchStore } from '../../stores/twitch'
 import type { WidgetInstanceProps } from '../registry'
+import { useHitRegion } from '../shared/hit-region'
 import { Widget, useContentAlign, contentAlignClass } from '../Widget'
 import {
   type Suggestion,
   getSortedActive,
   getDoneItems,
   subscribeSuggestions,
-  ensureSubscribed,
   toggleDone,
-  setConfig,
   loadSuggestions,
-  saveSuggestions,
+  applySuggestions,
 } from './suggestion-box-state'
 
 // ---------------------------------------------------------------------------
@@ -45,7 +45,6 @@ const DEFAULT_CONFIG: SuggestionBoxConfig = {
 function useSuggestions() {
   const [, rerender] = useReducer((x: number) => x + 1, 0)
   useEffect(() => {
-    ensureSubscribed()
     const unsub = subscribeSuggestions(rerender)
     return unsub
   }, [rerender])
@@ -57,69 +56,18 @@ function useSuggestions() {
 // ---------------------------------------------------------------------------
 
 const LONG_HOVER_MS = 500
-const POLL_INTERVAL_MS = 200
 
+/** Reveal the checkboxes after a long hover, or always in edit mode. */
 function useHoverReveal(
   containerRef: React.RefObject<HTMLDivElement | null>,
+  instanceId: string,
   editMode: boolean,
 ): boolean {
-  const [revealed, setRevealed] = useState(false)
-  const hoverStartRef = useRef<number | null>(null)
-
-  useEffect(() => {
-    if (editMode) {
-      setRevealed(true)
-      return
-    }
-
-    setRevealed(false)
-
-    const interval = setInterval(async () => {
-      if (!containerRef.current) return
-
-      try {
-        const [cx, cy] = await invoke<[number, number]>('get_cursor_position')
-        const rect = containerRef.current.getBoundingClientRect()
-
-        if (cx >= rect.left && cx <= rect.right && cy >= rect.top && cy <= rect.bottom) {
-          if (hoverStartRef.current === null) {
-            hoverStartRef.current = Date.now()
-          } else if (Date.now() - hoverStartRef.current >= LONG_HOVER_MS) {
-            await invoke('set_ignore_cursor', { ignore: false })
-            setRevealed(true)
-          }
-        } else {
-          if (hoverStartRef.current !== null) {
-            hoverStartRef.current = null
-            setRevealed(false)
-            await invoke('set_ignore_cursor', { ignore: true })
-          }
-        }
-      } catch {
-        // cursor position unavailable
-      }
-    }, POLL_INTERVAL_MS)
-
-    return () => {
-      clearInterval(interval)
-      hoverStartRef.current = null
-    }
-  }, [editMode, containerRef])
-
-  return revealed
-}
-
-// ---------------------------------------------------------------------------
-// Debounced auto-save
-// ---------------------------------------------------------------------------
-
-let saveTimer: ReturnType<typeof setTimeout> | null = null
-
-function scheduleSuggestionSave(): void {
-  if (saveTimer) clearTimeout(saveTimer)
-  saveTimer = setTimeout(() => {
-    saveSuggestions().catch(console.error)
-  }, 500)
+  const hovered = useHitRegion(containerRef, `suggestion-box-${instanceId}`, {
+    hoverMs: LONG_HOVER_MS,
+    enabled: !editMode,
+  })
+  return editMode || hovered
 }
 
 // ---------------------------------------------------------------------------
@@ -148,7 +96,7 @@ function SuggestionRow({
         <input
           type="checkbox"
           checked={isDone}
-          onChange={() => toggleDone(suggestion.id)}
+          onChange={() => toggleDone(suggestion.id).catch(console.error)}
           className="accent-blue-500 shrink-0 cursor-pointer"
         />
       )}
@@ -182,22 +130,21 @@ function SuggestionBoxContent({ instanceId }: { instanceId: string }) {
   }
   const { active, done } = useSuggestions()
 
-  const showCheckbox = useHoverReveal(containerRef, editMode)
+  const showCheckbox = useHoverReveal(containerRef, instanceId, editMode)
 
-  // Sync config to state module
+  // Load suggestions on mount
   useEffect(() => {
-    setConfig({
-      rewardId: config.rewardId,
-      voteTrigger: config.voteTrigger,
-      suggestTrigger: config.suggestTrigger,
-    })
-  }, [config.rewardId, config.voteTrigger, config.suggestTrigger])
+    loadSuggestions().catch(console.error)
+  }, [])
 
-  // Load suggestions on mount + subscribe for auto-save
+  // The backend broadcasts the full list after every change, from any window or source
   useEffect(() => {
-    loadSuggestions().catch(console.error)
-    const unsub = subscribeSuggestions(scheduleSuggestionSave)
-    return unsub
+    const unlisten = listen<Suggestion[]>('suggestions-changed', (event) => {
+      applySuggestions(event.payload)
+    })
+    return () => {
+      unlisten.then((fn) => fn()).catch(console.error)
+    }
   }, [])
 
   // Auto-scroll when active list changes